@@ -0,0 +1,409 @@
+use serde::Serialize;
+
+use crate::{
+    aqua_monitor::{AquaMonitorClient, TankReading},
+    challenges,
+    health_score::{self, HealthScore},
+    species_hub::{SpeciesHubClient, SpeciesProfile},
+    rules::{Parameter, RuleSet},
+    trend::{self, TrendReport},
+    AnalysisParams, AnalysisResult, ApiError, FeedingStatus, OverallHealth, ParameterStatus,
+};
+
+/// Where the analysis data comes from.
+#[derive(Clone)]
+pub enum AnalysisSource {
+    Live {
+        aqua_monitor: AquaMonitorClient,
+        species_hub: SpeciesHubClient,
+    },
+    /// The canned per-tank results from `challenges::get_analysis_result`, kept as a fixture
+    /// for running the dashboard and tests without the other services
+    Demo,
+}
+
+/// An analysis result together with the trend forecast from the tank's recent readings
+/// and the health score derived from both.
+#[derive(Serialize)]
+pub struct TankAnalysis {
+    #[serde(flatten)]
+    pub result: AnalysisResult,
+    /// `None` for the demo fixture and when there aren't enough readings to fit a trend
+    pub trend: Option<TrendReport>,
+    pub health_score: HealthScore,
+}
+
+impl TankAnalysis {
+    fn new(result: AnalysisResult, trend: Option<TrendReport>) -> Self {
+        let health_score = health_score::score(&result, trend.as_ref());
+        TankAnalysis { result, trend, health_score }
+    }
+}
+
+/// The tanks served by the demo analysis fixture.
+const DEMO_TANK_IDS: [&str; 3] = ["Tank-A1", "Tank-B2", "Tank-C3"];
+
+fn severity(status: ParameterStatus) -> u8 {
+    match status {
+        ParameterStatus::Unknown => 0,
+        ParameterStatus::Normal => 1,
+        ParameterStatus::Warning => 2,
+        ParameterStatus::Critical => 3,
+    }
+}
+
+/// The worst parameter decides; a tank is only unknown if nothing could be assessed.
+pub fn overall_health(statuses: &[ParameterStatus]) -> OverallHealth {
+    if statuses.contains(&ParameterStatus::Critical) {
+        OverallHealth::Critical
+    } else if statuses.contains(&ParameterStatus::Warning) {
+        OverallHealth::AtRisk
+    } else if statuses.contains(&ParameterStatus::Normal) {
+        OverallHealth::Good
+    } else {
+        OverallHealth::Unknown
+    }
+}
+
+/// The species the analysis is measured against: the requested one, or everything in the tank.
+///
+/// IDs species-hub doesn't know are returned separately so they can be reported.
+async fn species_in_scope(
+    species_hub: &SpeciesHubClient,
+    tank_id: &str,
+    species_id: Option<i32>,
+) -> Result<(Vec<SpeciesProfile>, Vec<i32>), ApiError> {
+    let species_ids: Vec<i32> = match species_id {
+        Some(species_id) => vec![species_id],
+        None => species_hub
+            .tank_inhabitants(tank_id)
+            .await?
+            .unwrap_or_default()
+            .into_iter()
+            .map(|inhabitant| inhabitant.species_id)
+            .collect(),
+    };
+
+    let mut species = Vec::with_capacity(species_ids.len());
+    let mut unresolved = Vec::new();
+    for species_id in species_ids {
+        match species_hub.species(species_id).await? {
+            Some(profile) => species.push(profile),
+            None => {
+                tracing::warn!(species_id, tank_id = %tank_id, "Species not found in species-hub");
+                unresolved.push(species_id);
+            }
+        }
+    }
+    Ok((species, unresolved))
+}
+
+/// Outcome of resolving a species ID to a name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpeciesLookup {
+    Resolved,
+    /// species-hub has no species with this ID
+    NotFound,
+    /// species-hub couldn't be reached and nothing was cached
+    Unavailable,
+    /// The result isn't about a particular species
+    NoSpecies,
+}
+
+/// Resolves a species name through species-hub's cached client.
+pub async fn species_name(source: &AnalysisSource, species_id: i32) -> (Option<String>, SpeciesLookup) {
+    if species_id <= 0 {
+        return (None, SpeciesLookup::NoSpecies);
+    }
+    let species_hub = match source {
+        AnalysisSource::Live { species_hub, .. } => species_hub,
+        // The demo fixture's species IDs don't refer to species-hub's catalog
+        AnalysisSource::Demo => return (None, SpeciesLookup::Unavailable),
+    };
+
+    match species_hub.species(species_id).await {
+        Ok(Some(profile)) => (Some(profile.name), SpeciesLookup::Resolved),
+        Ok(None) => (None, SpeciesLookup::NotFound),
+        Err(e) => {
+            tracing::warn!(species_id, error = %e, "Species name unavailable");
+            (None, SpeciesLookup::Unavailable)
+        }
+    }
+}
+
+fn unresolved_recommendations(unresolved: &[i32]) -> impl Iterator<Item = String> + '_ {
+    unresolved.iter().map(|species_id| {
+        format!(
+            "Species ID {} is not in the species-hub catalog; its tolerances were not checked",
+            species_id
+        )
+    })
+}
+
+async fn feeding_status(species_hub: &SpeciesHubClient, tank_id: &str) -> (FeedingStatus, Vec<String>) {
+    match species_hub.feeding_status(tank_id).await {
+        Ok(Some(status)) => match status.status.as_str() {
+            "normal" => (FeedingStatus::Normal, Vec::new()),
+            "overdue" => (FeedingStatus::Overdue, status.reasons),
+            "overfed" => (FeedingStatus::Overfed, status.reasons),
+            _ => (FeedingStatus::Unknown, Vec::new()),
+        },
+        Ok(None) => (FeedingStatus::Unknown, Vec::new()),
+        // Feeding is secondary to water quality, so its absence doesn't fail the analysis
+        Err(e) => {
+            tracing::warn!(tank_id = %tank_id, error = %e, "Feeding status unavailable");
+            (FeedingStatus::Unknown, Vec::new())
+        }
+    }
+}
+
+fn unknown_result(tank_id: String, species_id: i32, recommendations: Vec<String>) -> AnalysisResult {
+    AnalysisResult {
+        tank_id,
+        species_id,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        temperature_status: ParameterStatus::Unknown,
+        ph_status: ParameterStatus::Unknown,
+        oxygen_status: ParameterStatus::Unknown,
+        feeding_status: FeedingStatus::Unknown,
+        overall_health: OverallHealth::Unknown,
+        recommendations,
+    }
+}
+
+/// Assesses the latest reading against every species in scope using the configured rules,
+/// keeping the worst status per parameter. The reported species is the one most at risk.
+fn assess_reading(
+    rules: &RuleSet,
+    tank_id: String,
+    reading: &TankReading,
+    species: &[SpeciesProfile],
+    feeding: (FeedingStatus, Vec<String>),
+    forecasts: Vec<String>,
+) -> AnalysisResult {
+    let mut temperature_status = ParameterStatus::Unknown;
+    let mut ph_status = ParameterStatus::Unknown;
+    let mut oxygen_status = ParameterStatus::Unknown;
+    let mut recommendations = Vec::new();
+    let mut most_at_risk: Option<(u8, i32)> = None;
+
+    for profile in species {
+        let assessments = Parameter::ALL
+            .map(|parameter| rules.evaluate(parameter, parameter.reading(reading), profile));
+
+        for (current, assessment) in [&mut temperature_status, &mut ph_status, &mut oxygen_status]
+            .into_iter()
+            .zip(&assessments)
+        {
+            if severity(assessment.status) > severity(*current) {
+                *current = assessment.status;
+            }
+        }
+        recommendations.extend(assessments.iter().filter_map(|a| a.recommendation.clone()));
+
+        let worst = assessments.iter().map(|a| severity(a.status)).max().unwrap_or(0);
+        if !matches!(most_at_risk, Some((severity, _)) if worst <= severity) {
+            most_at_risk = Some((worst, profile.id));
+        }
+    }
+
+    let (feeding_status, feeding_reasons) = feeding;
+    recommendations.extend(feeding_reasons);
+    recommendations.extend(forecasts);
+
+    let overall_health = overall_health(&[temperature_status, ph_status, oxygen_status]);
+    if recommendations.is_empty() && overall_health == OverallHealth::Good {
+        recommendations.push("Maintain current parameters".to_string());
+    }
+
+    AnalysisResult {
+        tank_id,
+        species_id: most_at_risk.map(|(_, id)| id).unwrap_or(0),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        temperature_status,
+        ph_status,
+        oxygen_status,
+        feeding_status,
+        overall_health,
+        recommendations,
+    }
+}
+
+/// Lists the tanks to analyse, sorted by ID.
+pub async fn discover_tanks(source: &AnalysisSource) -> Result<Vec<String>, ApiError> {
+    let mut tank_ids = match source {
+        AnalysisSource::Live { aqua_monitor, .. } => aqua_monitor.tank_ids().await?,
+        AnalysisSource::Demo => DEMO_TANK_IDS.iter().map(|id| id.to_string()).collect(),
+    };
+    tank_ids.sort();
+    tank_ids.dedup();
+    Ok(tank_ids)
+}
+
+/// Analyses a tank's latest reading against the tolerance ranges of its species,
+/// forecasts where its parameters are heading from the recent readings and scores the result.
+pub async fn analyze_tank(
+    source: &AnalysisSource,
+    rules: &RuleSet,
+    params: AnalysisParams,
+) -> Result<TankAnalysis, ApiError> {
+    let (aqua_monitor, species_hub) = match source {
+        AnalysisSource::Live { aqua_monitor, species_hub } => (aqua_monitor, species_hub),
+        AnalysisSource::Demo => {
+            return Ok(TankAnalysis::new(challenges::get_analysis_result(params), None))
+        }
+    };
+
+    let tank_id = params.tank_id.unwrap_or_else(|| "Tank-A1".to_string());
+    let horizon_hours = trend::horizon_hours(params.horizon_hours);
+
+    let readings = aqua_monitor.recent_readings(&tank_id).await?.unwrap_or_default();
+    let Some(latest) = readings.iter().max_by_key(|r| r.timestamp) else {
+        return Ok(TankAnalysis::new(
+            unknown_result(
+                tank_id,
+                params.species_id.unwrap_or(0),
+                vec!["Verify tank ID".to_string(), "Setup monitoring system".to_string()],
+            ),
+            None,
+        ));
+    };
+
+    let (species, unresolved) = species_in_scope(species_hub, &tank_id, params.species_id).await?;
+    let trend = trend::analyze(&readings, &species, horizon_hours);
+    if species.is_empty() {
+        let recommendations = if unresolved.is_empty() {
+            vec!["Register the tank's inhabitants in species-hub".to_string()]
+        } else {
+            unresolved_recommendations(&unresolved).collect()
+        };
+        return Ok(TankAnalysis::new(
+            unknown_result(tank_id, params.species_id.unwrap_or(0), recommendations),
+            trend,
+        ));
+    }
+
+    let feeding = feeding_status(species_hub, &tank_id).await;
+    let forecasts = trend.as_ref().map(trend::forecast_recommendations).unwrap_or_default();
+    let mut result = assess_reading(rules, tank_id, latest, &species, feeding, forecasts);
+    result.recommendations.extend(unresolved_recommendations(&unresolved));
+    Ok(TankAnalysis::new(result, trend))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn rules() -> RuleSet {
+        RuleSet::parse("analysis_rules.json", include_str!("../config/analysis_rules.json")).unwrap()
+    }
+
+    fn params(tank_id: &str) -> AnalysisParams {
+        AnalysisParams {
+            tank_id: Some(tank_id.to_string()),
+            species_id: None,
+            horizon_hours: None,
+        }
+    }
+
+    fn species(id: i32, name: &str, ph: (f64, f64)) -> SpeciesProfile {
+        SpeciesProfile {
+            min_ph: ph.0,
+            max_ph: ph.1,
+            ..test_support::species(id, name)
+        }
+    }
+
+    fn reading(temperature: f64, ph: f64, oxygen_level: f64) -> TankReading {
+        TankReading {
+            temperature,
+            ph,
+            oxygen_level,
+            timestamp: chrono::Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn demo_fixture_is_scored() {
+        let rules = rules();
+        let analyze = |tank_id: &str| analyze_tank(&AnalysisSource::Demo, &rules, params(tank_id));
+
+        let a1 = analyze("Tank-A1").await.unwrap();
+        assert_eq!(a1.result.overall_health, OverallHealth::AtRisk);
+        assert!(a1.trend.is_none());
+        // 12.5 + 0 + 25 + 0 of the 85 points available without a trend
+        assert_eq!(a1.health_score.score, Some(44));
+
+        let b2 = analyze("Tank-B2").await.unwrap();
+        assert_eq!(b2.result.overall_health, OverallHealth::Good);
+        assert_eq!(b2.health_score.score, Some(100));
+
+        let c3 = analyze("Tank-C3").await.unwrap();
+        assert_eq!(c3.health_score.score, Some(59));
+
+        let unknown = analyze("Tank-Z9").await.unwrap();
+        assert_eq!(unknown.result.overall_health, OverallHealth::Unknown);
+        assert_eq!(unknown.health_score.score, None);
+    }
+
+    #[tokio::test]
+    async fn demo_source_lists_its_tanks_without_species_names() {
+        assert_eq!(
+            discover_tanks(&AnalysisSource::Demo).await.unwrap(),
+            vec!["Tank-A1", "Tank-B2", "Tank-C3"]
+        );
+        assert_eq!(species_name(&AnalysisSource::Demo, 1).await, (None, SpeciesLookup::Unavailable));
+        assert_eq!(species_name(&AnalysisSource::Demo, 0).await, (None, SpeciesLookup::NoSpecies));
+    }
+
+    #[test]
+    fn overall_health_follows_the_worst_parameter() {
+        use ParameterStatus::{Critical, Normal, Unknown, Warning};
+
+        assert_eq!(overall_health(&[Normal, Warning, Critical]), OverallHealth::Critical);
+        assert_eq!(overall_health(&[Normal, Warning, Unknown]), OverallHealth::AtRisk);
+        assert_eq!(overall_health(&[Normal, Unknown, Unknown]), OverallHealth::Good);
+        assert_eq!(overall_health(&[Unknown, Unknown, Unknown]), OverallHealth::Unknown);
+        assert_eq!(overall_health(&[]), OverallHealth::Unknown);
+    }
+
+    #[test]
+    fn reading_is_assessed_against_the_species_most_at_risk() {
+        let result = assess_reading(
+            &rules(),
+            "Tank-A1".to_string(),
+            &reading(24.0, 6.4, 8.0),
+            &[species(1, "Hardy", (6.0, 8.0)), species(2, "Fussy", (6.8, 7.5))],
+            (FeedingStatus::Overfed, vec!["Too much food".to_string()]),
+            vec!["Forecast".to_string()],
+        );
+
+        assert_eq!(result.species_id, 2);
+        assert_eq!(result.temperature_status, ParameterStatus::Normal);
+        assert_eq!(result.ph_status, ParameterStatus::Critical);
+        assert_eq!(result.oxygen_status, ParameterStatus::Normal);
+        assert_eq!(result.feeding_status, FeedingStatus::Overfed);
+        assert_eq!(result.overall_health, OverallHealth::Critical);
+        assert_eq!(
+            result.recommendations,
+            vec!["Raise pH by 0.4 to reach Fussy's minimum of 6.8", "Too much food", "Forecast"]
+        );
+    }
+
+    #[test]
+    fn healthy_reading_keeps_current_parameters() {
+        let result = assess_reading(
+            &rules(),
+            "Tank-B2".to_string(),
+            &reading(24.0, 7.2, 8.0),
+            &[species(1, "Hardy", (6.0, 8.0))],
+            (FeedingStatus::Normal, Vec::new()),
+            Vec::new(),
+        );
+
+        assert_eq!(result.overall_health, OverallHealth::Good);
+        assert_eq!(result.recommendations, vec!["Maintain current parameters"]);
+    }
+}
@@ -0,0 +1,103 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::ApiError;
+
+/// Default aqua-monitor address when running all services locally (see README ports).
+pub const DEFAULT_AQUA_MONITOR_URL: &str = "http://localhost:8000";
+
+/// A single tank reading as returned by aqua-monitor's readings endpoint.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TankReading {
+    pub temperature: f64,
+    pub ph: f64,
+    pub oxygen_level: f64,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+struct TankReadingsResponse {
+    readings: Vec<TankReading>,
+}
+
+/// Client for the aqua-monitor service, created once and shared through `AppState`.
+#[derive(Clone)]
+pub struct AquaMonitorClient {
+    client: reqwest::Client,
+    base_url: reqwest::Url,
+}
+
+impl AquaMonitorClient {
+    pub fn new(base_url: &str) -> Result<Self, String> {
+        let base_url = reqwest::Url::parse(base_url)
+            .ok()
+            .filter(|url| !url.cannot_be_a_base())
+            .ok_or_else(|| format!("invalid aqua-monitor URL: {:?}", base_url))?;
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+            .map_err(|e| format!("failed to build aqua-monitor HTTP client: {}", e))?;
+
+        Ok(Self {
+            client,
+            base_url,
+        })
+    }
+
+    /// Appends percent-encoded path segments to the base URL, so an ID containing
+    /// `/`, `?` or `#` stays a single segment instead of reaching another route.
+    fn endpoint(&self, segments: &[&str]) -> reqwest::Url {
+        let mut url = self.base_url.clone();
+        // Always succeeds: `new` rejects base URLs that can't have a path
+        if let Ok(mut path) = url.path_segments_mut() {
+            path.pop_if_empty().extend(segments);
+        }
+        url
+    }
+
+    /// Lists the IDs of every tank aqua-monitor has readings for.
+    pub async fn tank_ids(&self) -> Result<Vec<String>, ApiError> {
+        let unavailable =
+            |e: reqwest::Error| ApiError::SystemStatusUnavailable(format!("aqua-monitor: {}", e));
+
+        self.client
+            .get(self.endpoint(&["api", "tanks"]))
+            .send()
+            .await
+            .map_err(unavailable)?
+            .error_for_status()
+            .map_err(unavailable)?
+            .json()
+            .await
+            .map_err(unavailable)
+    }
+
+    /// Fetches the most recent readings for a tank, newest first.
+    ///
+    /// Returns `None` when aqua-monitor has no readings for the tank.
+    pub async fn recent_readings(&self, tank_id: &str) -> Result<Option<Vec<TankReading>>, ApiError> {
+        let unavailable =
+            |e: reqwest::Error| ApiError::SystemStatusUnavailable(format!("aqua-monitor: {}", e));
+
+        let response = self
+            .client
+            .get(self.endpoint(&["api", "tanks", tank_id, "readings"]))
+            .send()
+            .await
+            .map_err(unavailable)?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        let body: TankReadingsResponse = response
+            .error_for_status()
+            .map_err(unavailable)?
+            .json()
+            .await
+            .map_err(unavailable)?;
+        Ok(Some(body.readings))
+    }
+}
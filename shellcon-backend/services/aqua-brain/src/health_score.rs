@@ -0,0 +1,299 @@
+use serde::Serialize;
+
+use crate::{
+    rules::Parameter,
+    trend::{self, LimitKind, TrendReport},
+    AnalysisResult, FeedingStatus, ParameterStatus,
+};
+
+/// Weights of the score components; they add up to 100.
+const TEMPERATURE_WEIGHT: f64 = 25.0;
+const PH_WEIGHT: f64 = 25.0;
+const OXYGEN_WEIGHT: f64 = 25.0;
+const FEEDING_WEIGHT: f64 = 10.0;
+const ANOMALY_WEIGHT: f64 = 15.0;
+
+/// Share of the anomaly weight lost per anomaly found.
+const ANOMALY_PENALTY: f64 = 1.0 / 3.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreComponentKind {
+    Temperature,
+    Ph,
+    Oxygen,
+    Feeding,
+    Anomalies,
+}
+
+/// One component's part in the score.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScoreComponent {
+    pub component: ScoreComponentKind,
+    pub weight: f64,
+    /// Points contributed to the 0–100 score; `None` when the component couldn't be assessed
+    pub contribution: Option<f64>,
+    /// Points this component cost the tank
+    pub points_lost: f64,
+    pub detail: String,
+}
+
+/// A 0–100 health score with the contribution of each component.
+///
+/// Components that couldn't be assessed are left out and the others scaled up, so
+/// missing data doesn't read as poor health. The score is `None` unless at least one
+/// water parameter was assessed, since feeding and anomalies alone say little about health.
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthScore {
+    pub score: Option<u8>,
+    pub components: Vec<ScoreComponent>,
+}
+
+/// A component's weight and the fraction of it earned, before scaling.
+struct Assessed {
+    component: ScoreComponentKind,
+    weight: f64,
+    earned: Option<f64>,
+    detail: String,
+}
+
+fn parameter_component(parameter: Parameter, status: ParameterStatus) -> Assessed {
+    let (component, weight) = match parameter {
+        Parameter::Temperature => (ScoreComponentKind::Temperature, TEMPERATURE_WEIGHT),
+        Parameter::Ph => (ScoreComponentKind::Ph, PH_WEIGHT),
+        Parameter::Oxygen => (ScoreComponentKind::Oxygen, OXYGEN_WEIGHT),
+    };
+    let (label, _) = trend::label(parameter);
+    let (earned, detail) = match status {
+        ParameterStatus::Normal => (Some(1.0), format!("{} is within range", label)),
+        ParameterStatus::Warning => (Some(0.5), format!("{} is at or near a species limit", label)),
+        ParameterStatus::Critical => (Some(0.0), format!("{} is critically out of range", label)),
+        ParameterStatus::Unknown => (None, format!("{} could not be assessed", label)),
+    };
+    Assessed { component, weight, earned, detail }
+}
+
+fn feeding_component(status: FeedingStatus) -> Assessed {
+    let (earned, detail) = match status {
+        FeedingStatus::Normal => (Some(1.0), "Feeding is on schedule".to_string()),
+        FeedingStatus::Overdue => (Some(0.0), "Feeding is overdue".to_string()),
+        // Uneaten food fouls the water, but the animals are still fed
+        FeedingStatus::Overfed => (Some(0.5), "Tank is being overfed".to_string()),
+        FeedingStatus::Unknown => (None, "Feeding status is unknown".to_string()),
+    };
+    Assessed {
+        component: ScoreComponentKind::Feeding,
+        weight: FEEDING_WEIGHT,
+        earned,
+        detail,
+    }
+}
+
+/// Readings that break from their trend, and parameters forecast to leave range within the horizon.
+fn anomaly_component(trend: Option<&TrendReport>) -> Assessed {
+    let Some(trend) = trend else {
+        return Assessed {
+            component: ScoreComponentKind::Anomalies,
+            weight: ANOMALY_WEIGHT,
+            earned: None,
+            detail: "Not enough readings to look for anomalies".to_string(),
+        };
+    };
+
+    let mut anomalies = Vec::new();
+    for parameter in &trend.parameters {
+        let (label, _) = trend::label(parameter.parameter);
+        if parameter.anomalous {
+            anomalies.push(format!("{} reading breaks from its recent trend", label));
+        }
+        if let Some(limit) = parameter.limit.as_ref().filter(|limit| limit.within_horizon && limit.hours_until > 0.0) {
+            let bound = match limit.limit {
+                LimitKind::Minimum => "minimum",
+                LimitKind::Maximum => "maximum",
+            };
+            anomalies.push(format!(
+                "{} forecast to pass {}'s {} in {:.1} hours",
+                label, limit.species_name, bound, limit.hours_until
+            ));
+        }
+    }
+
+    let detail = if anomalies.is_empty() {
+        "No anomalies in recent readings".to_string()
+    } else {
+        anomalies.join("; ")
+    };
+    Assessed {
+        component: ScoreComponentKind::Anomalies,
+        weight: ANOMALY_WEIGHT,
+        earned: Some((1.0 - ANOMALY_PENALTY * anomalies.len() as f64).max(0.0)),
+        detail,
+    }
+}
+
+/// Scores an analysis from its parameter and feeding statuses and the trend's anomalies.
+pub fn score(result: &AnalysisResult, trend: Option<&TrendReport>) -> HealthScore {
+    let assessed = [
+        parameter_component(Parameter::Temperature, result.temperature_status),
+        parameter_component(Parameter::Ph, result.ph_status),
+        parameter_component(Parameter::Oxygen, result.oxygen_status),
+        feeding_component(result.feeding_status),
+        anomaly_component(trend),
+    ];
+
+    let available: f64 = assessed
+        .iter()
+        .filter(|component| component.earned.is_some())
+        .map(|component| component.weight)
+        .sum();
+    // Scales weights so the assessed components add up to 100
+    let scale = if available > 0.0 { 100.0 / available } else { 0.0 };
+
+    let components: Vec<ScoreComponent> = assessed
+        .into_iter()
+        .map(|component| {
+            let contribution = component.earned.map(|earned| component.weight * scale * earned);
+            let points_lost = component
+                .earned
+                .map(|earned| component.weight * scale * (1.0 - earned))
+                .unwrap_or(0.0);
+            ScoreComponent {
+                component: component.component,
+                weight: component.weight,
+                contribution,
+                points_lost,
+                detail: component.detail,
+            }
+        })
+        .collect();
+
+    let parameters_assessed = components.iter().any(|component| {
+        component.contribution.is_some()
+            && matches!(
+                component.component,
+                ScoreComponentKind::Temperature | ScoreComponentKind::Ph | ScoreComponentKind::Oxygen
+            )
+    });
+    let score = parameters_assessed.then(|| {
+        components
+            .iter()
+            .filter_map(|component| component.contribution)
+            .sum::<f64>()
+            .round()
+            .clamp(0.0, 100.0) as u8
+    });
+
+    HealthScore { score, components }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        trend::{ParameterTrend, TrendDirection},
+        OverallHealth,
+    };
+
+    fn result(
+        statuses: [ParameterStatus; 3],
+        feeding_status: FeedingStatus,
+    ) -> AnalysisResult {
+        AnalysisResult {
+            tank_id: "Tank-A1".to_string(),
+            species_id: 1,
+            timestamp: "2025-06-01T12:00:00Z".to_string(),
+            temperature_status: statuses[0],
+            ph_status: statuses[1],
+            oxygen_status: statuses[2],
+            feeding_status,
+            overall_health: OverallHealth::Good,
+            recommendations: Vec::new(),
+        }
+    }
+
+    fn trend(anomalous: bool) -> TrendReport {
+        TrendReport {
+            horizon_hours: 6.0,
+            reading_count: 5,
+            window_hours: 4.0,
+            parameters: vec![ParameterTrend {
+                parameter: Parameter::Temperature,
+                latest: 25.0,
+                slope_per_hour: 0.0,
+                direction: TrendDirection::Stable,
+                projected: 25.0,
+                limit: None,
+                anomalous,
+            }],
+        }
+    }
+
+    fn contribution(score: &HealthScore, kind: ScoreComponentKind) -> Option<f64> {
+        score.components.iter().find(|c| c.component == kind).unwrap().contribution
+    }
+
+    const NORMAL: [ParameterStatus; 3] = [ParameterStatus::Normal; 3];
+
+    #[test]
+    fn healthy_tank_scores_full_marks() {
+        let score = score(&result(NORMAL, FeedingStatus::Normal), Some(&trend(false)));
+
+        assert_eq!(score.score, Some(100));
+        assert_eq!(score.components.len(), 5);
+        assert!(score.components.iter().all(|c| c.points_lost == 0.0));
+        assert_eq!(contribution(&score, ScoreComponentKind::Anomalies), Some(15.0));
+    }
+
+    #[test]
+    fn unassessed_components_are_left_out_and_the_rest_scaled() {
+        // Feeding unknown and no trend: 75 points available, scaled to 100
+        let score = score(
+            &result(
+                [ParameterStatus::Normal, ParameterStatus::Warning, ParameterStatus::Critical],
+                FeedingStatus::Unknown,
+            ),
+            None,
+        );
+
+        assert_eq!(score.score, Some(50));
+        assert!((contribution(&score, ScoreComponentKind::Ph).unwrap() - 100.0 / 6.0).abs() < 1e-9);
+        assert_eq!(contribution(&score, ScoreComponentKind::Feeding), None);
+        assert_eq!(contribution(&score, ScoreComponentKind::Anomalies), None);
+        let lost: f64 = score.components.iter().map(|c| c.points_lost).sum();
+        assert!((lost - 50.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn no_score_without_a_water_parameter() {
+        let score = score(
+            &result([ParameterStatus::Unknown; 3], FeedingStatus::Normal),
+            Some(&trend(false)),
+        );
+
+        assert_eq!(score.score, None);
+        assert_eq!(contribution(&score, ScoreComponentKind::Feeding), Some(40.0));
+    }
+
+    #[test]
+    fn feeding_problems_cost_points() {
+        let overdue = score(&result(NORMAL, FeedingStatus::Overdue), Some(&trend(false)));
+        let overfed = score(&result(NORMAL, FeedingStatus::Overfed), Some(&trend(false)));
+
+        assert_eq!(overdue.score, Some(90));
+        assert_eq!(overfed.score, Some(95));
+    }
+
+    #[test]
+    fn each_anomaly_costs_a_third_of_its_weight() {
+        let score = score(&result(NORMAL, FeedingStatus::Normal), Some(&trend(true)));
+
+        assert_eq!(score.score, Some(95));
+        let anomalies = score
+            .components
+            .iter()
+            .find(|c| c.component == ScoreComponentKind::Anomalies)
+            .unwrap();
+        assert!((anomalies.points_lost - 5.0).abs() < 1e-9);
+        assert_eq!(anomalies.detail, "Temperature reading breaks from its recent trend");
+    }
+}
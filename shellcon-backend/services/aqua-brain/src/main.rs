@@ -5,11 +5,13 @@ use shuttle_axum::axum::routing::get;
 use shuttle_axum::axum::Json;
 use shuttle_axum::axum::Router;
 // CORS removed - managed by frontend
+use futures::stream::{self, StreamExt};
+use std::sync::Arc;
 use serde::{Deserialize, Serialize};
 // No unused imports
 use thiserror::Error;
 use tokio::fs;
-use tracing;
+use tracing::Instrument;
 
 // 🔱 Challenge 3: Core Types 🔱
 // These enums are used in AnalysisResult and should be used by the participant
@@ -40,6 +42,7 @@ impl std::fmt::Display for ParameterStatus {
 pub enum FeedingStatus {
     Normal,
     Overdue,
+    Overfed,
     Unknown,
 }
 
@@ -48,6 +51,7 @@ impl std::fmt::Display for FeedingStatus {
         match self {
             FeedingStatus::Normal => write!(f, "normal"),
             FeedingStatus::Overdue => write!(f, "overdue"),
+            FeedingStatus::Overfed => write!(f, "overfed"),
             FeedingStatus::Unknown => write!(f, "unknown"),
         }
     }
@@ -139,7 +143,15 @@ async fn load_solution(challenge_number: usize) -> (String, String) {
 }
 
 // Import challenges module
+mod analysis;
+mod aqua_monitor;
 mod challenges;
+mod health_score;
+mod rules;
+mod species_hub;
+#[cfg(test)]
+mod test_support;
+mod trend;
 
 // Define a custom error type for better error handling
 #[derive(Debug, Error)]
@@ -190,7 +202,10 @@ impl IntoResponse for ApiError {
 
 // Define application state
 #[derive(Clone)]
-struct AppState {}
+struct AppState {
+    analysis_source: analysis::AnalysisSource,
+    rules: Arc<rules::RuleSet>,
+}
 
 // Define analysis result structure
 #[derive(Debug, Serialize, Clone)] 
@@ -210,6 +225,8 @@ pub struct AnalysisResult {
 struct AnalysisParams {
     tank_id: Option<String>,
     species_id: Option<i32>,
+    // How many hours ahead trends are projected; see trend::horizon_hours
+    horizon_hours: Option<f64>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -364,13 +381,57 @@ async fn get_current_challenge() -> impl IntoResponse {
 }
 
 #[shuttle_runtime::main]
-async fn axum() -> shuttle_axum::ShuttleAxum {
-    // Initialize state - no clients needed following our KISS architecture
-    let state = AppState {};
+async fn axum(
+    #[shuttle_runtime::Secrets] secrets: shuttle_runtime::SecretStore,
+) -> shuttle_axum::ShuttleAxum {
+    // Shared clients for the services the analysis reads from;
+    // set AQUA_MONITOR_URL = "stub" to serve the demo analysis instead
+    let analysis_source = match secrets.get("AQUA_MONITOR_URL").as_deref() {
+        Some("stub") => analysis::AnalysisSource::Demo,
+        aqua_monitor_url => analysis::AnalysisSource::Live {
+            aqua_monitor: aqua_monitor::AquaMonitorClient::new(
+                aqua_monitor_url.unwrap_or(aqua_monitor::DEFAULT_AQUA_MONITOR_URL),
+            )
+            .map_err(anyhow::Error::msg)?,
+            species_hub: species_hub::SpeciesHubClient::new(
+                &secrets
+                    .get("SPECIES_HUB_URL")
+                    .unwrap_or_else(|| species_hub::DEFAULT_SPECIES_HUB_URL.to_string()),
+            )
+            .map_err(anyhow::Error::msg)?,
+        },
+    };
+    tracing::info!(
+        demo = matches!(analysis_source, analysis::AnalysisSource::Demo),
+        "Configured analysis data source for aqua-brain."
+    );
+
+    // Thresholds and recommendations come from the rules file; refuse to start on invalid rules
+    let rules_path = secrets
+        .get("ANALYSIS_RULES_PATH")
+        .unwrap_or_else(|| rules::DEFAULT_RULES_PATH.to_string());
+    let rules = rules::RuleSet::load(&rules_path)
+        .await
+        .map_err(anyhow::Error::msg)?;
+    tracing::info!(
+        operation = "load_analysis_rules",
+        operation_status = "success",
+        source = %rules.source,
+        rule_count = rules.rules.len(),
+        uncovered_parameters = ?rules.uncovered_parameters(),
+        "Loaded analysis rules."
+    );
+
+    // Initialize state
+    let state = AppState {
+        analysis_source,
+        rules: Arc::new(rules),
+    };
     
     // Build router
     let router = Router::new()
         .layer(tower_http::trace::TraceLayer::new_for_http())
+        .route("/api/analysis/rules", get(rules::get_analysis_rules))
         .route("/api/analysis/tanks", get(get_all_tank_analysis))
         .route("/api/analysis/tanks/:tank_id", get(get_tank_analysis_by_id))
         .route("/api/challenges/current", get(get_current_challenge))
@@ -393,120 +454,178 @@ async fn health_check() -> impl IntoResponse {
 struct TankSummary {
     tank_id: String,
     species_id: i32,
-    species_name: String,
+    // None when the ID couldn't be resolved; species_lookup says why
+    species_name: Option<String>,
+    species_lookup: analysis::SpeciesLookup,
     overall_health: OverallHealth, // Changed from String
+    // 0–100; the full analysis breaks it down by component
+    health_score: Option<u8>,
+    // Same as the full analysis: None for the demo fixture or too few readings
+    trend: Option<trend::TrendReport>,
     timestamp: String,
+    // Why this tank couldn't be analysed; the other tanks are still reported
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
 }
 
-// Map species_id to species_name for the demo
-fn get_species_name(species_id: i32) -> String {
-    match species_id {
-        1 => "Neon Tetra".to_string(),
-        2 => "Clownfish".to_string(),
-        3 => "Blue Tang".to_string(),
-        4 => "Guppy".to_string(),
-        5 => "Betta".to_string(),
-        _ => format!("Unknown Species (ID: {})", species_id),
+impl TankSummary {
+    fn failed(tank_id: String, error: String) -> Self {
+        TankSummary {
+            tank_id,
+            species_id: 0,
+            species_name: None,
+            species_lookup: analysis::SpeciesLookup::NoSpecies,
+            overall_health: OverallHealth::Unknown,
+            health_score: None,
+            trend: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            error: Some(error),
+        }
     }
 }
 
+// Tanks analysed at the same time in the multi-tank overview
+const MAX_CONCURRENT_TANK_ANALYSES: usize = 4;
+// A slow tank is reported as failed rather than holding up the overview
+const TANK_ANALYSIS_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(8);
+
 // Handler for all tanks analysis - returns summarized information
 async fn get_all_tank_analysis(
     Query(params): Query<AnalysisParams>,
-    State(_state): State<AppState>,
-) -> impl IntoResponse {
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
     // Create a span for tracking multi-tank environmental analysis
     let span = tracing::info_span!("multi_tank_analysis");
-    let _guard = span.enter();
-    
-    // Add request ID for correlation and timing
-    let request_id = uuid::Uuid::new_v4().to_string();
-    let start_time = std::time::Instant::now();
-    
-    tracing::info!(
-        request_id = %request_id,
-        operation = "multi_tank_analysis",
-        tanks_requested = "all",
-        "Starting multi-tank environmental analysis"
-    );
-    // Defined tank IDs in our system
-    let tank_ids = vec!["Tank-A1", "Tank-B2", "Tank-C3"];
-    
-    // Create summary results for all defined tanks
-    let results: Vec<TankSummary> = tank_ids
-        .into_iter()
-        .map(|tank_id| {
-            let mut tank_params = params.clone();
-            tank_params.tank_id = Some(tank_id.to_string());
-            
-            // Get full analysis but only return summary
-            let full_analysis = challenges::get_analysis_result(tank_params);
+    async move {
+        // Add request ID for correlation and timing
+        let request_id = uuid::Uuid::new_v4().to_string();
+        let start_time = std::time::Instant::now();
+        
+        tracing::info!(
+            request_id = %request_id,
+            operation = "multi_tank_analysis",
+            tanks_requested = "all",
+            "Starting multi-tank environmental analysis"
+        );
+        // Tanks come from aqua-monitor so new ones show up without a deploy
+        let tank_ids = analysis::discover_tanks(&state.analysis_source).await?;
+        
+        // Analyse tanks concurrently, keeping discovery order in the response
+        let results: Vec<TankSummary> = stream::iter(tank_ids)
+            .map(|tank_id| {
+                let mut tank_params = params.clone();
+                tank_params.tank_id = Some(tank_id.clone());
+                let source = &state.analysis_source;
+                let rules = state.rules.as_ref();
+                let request_id = &request_id;
+                let tank_span = tracing::info_span!("tank_analysis", tank_id = %tank_id);
+                
+                async move {
+                    let analysis = tokio::time::timeout(
+                        TANK_ANALYSIS_TIMEOUT,
+                        analysis::analyze_tank(source, rules, tank_params),
+                    )
+                    .await;
+                    
+                    let error = match analysis {
+                        // Get full analysis but only return summary
+                        Ok(Ok(analysis::TankAnalysis { result: full_analysis, trend, health_score })) => {
+                            let (species_name, species_lookup) =
+                                analysis::species_name(source, full_analysis.species_id).await;
+                            return TankSummary {
+                                tank_id: full_analysis.tank_id,
+                                species_id: full_analysis.species_id,
+                                species_name,
+                                species_lookup,
+                                overall_health: full_analysis.overall_health,
+                                health_score: health_score.score,
+                                trend,
+                                timestamp: full_analysis.timestamp,
+                                error: None,
+                            };
+                        }
+                        Ok(Err(e)) => e.to_string(),
+                        Err(_) => format!(
+                            "Analysis timed out after {}s",
+                            TANK_ANALYSIS_TIMEOUT.as_secs()
+                        ),
+                    };
+                    
+                    tracing::warn!(
+                        request_id = %request_id,
+                        tank_id = %tank_id,
+                        error.message = %error,
+                        "Tank analysis failed; reporting partial results"
+                    );
+                    TankSummary::failed(tank_id, error)
+                }
+                .instrument(tank_span)
+            })
+            .buffered(MAX_CONCURRENT_TANK_ANALYSES)
+            .collect()
+            .await;
             
-            // Convert to summary
-            TankSummary {
-                tank_id: full_analysis.tank_id,
-                species_id: full_analysis.species_id,
-                species_name: get_species_name(full_analysis.species_id),
-                overall_health: full_analysis.overall_health,
-                timestamp: full_analysis.timestamp,
-            }
-        })
-        .collect();
+        // Log timing information on completion
+        let elapsed = start_time.elapsed().as_millis() as f64;
+        tracing::info!(
+            request_id = %request_id,
+            operation = "multi_tank_analysis",
+            tanks_analyzed = results.len(),
+            tanks_failed = results.iter().filter(|r| r.error.is_some()).count(),
+            analysis_duration_ms = elapsed,
+            operation_status = "success",
+            "Multi-tank environmental analysis completed"
+        );
         
-    // Log timing information on completion
-    let elapsed = start_time.elapsed().as_millis() as f64;
-    tracing::info!(
-        request_id = %request_id,
-        operation = "multi_tank_analysis",
-        tanks_analyzed = results.len(),
-        analysis_duration_ms = elapsed,
-        operation_status = "success",
-        "Multi-tank environmental analysis completed"
-    );
-    
-    Json(results)
+        Ok::<_, ApiError>(Json(results))
+    }
+    .instrument(span)
+    .await
 }
 
 // Handler for single tank analysis by ID
 async fn get_tank_analysis_by_id(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     Path(tank_id): Path<String>,
     Query(params): Query<AnalysisParams>,
-) -> impl IntoResponse {
+) -> Result<impl IntoResponse, ApiError> {
     // Create a span for tracking single tank environmental analysis
     let span = tracing::info_span!("single_tank_analysis");
-    let _guard = span.enter();
-    
-    // Add request ID for correlation and timing
-    let request_id = uuid::Uuid::new_v4().to_string();
-    let start_time = std::time::Instant::now();
-    
-    tracing::info!(
-        request_id = %request_id,
-        tank_id = %tank_id,
-        operation = "single_tank_analysis",
-        "Starting tank environmental analysis"
-    );
-    // Override tank_id from path parameter
-    let mut tank_params = params;
-    // Clone tank_id directly in the assignment to keep the original for logging
-    tank_params.tank_id = Some(tank_id.clone());
-    
-    // Get single tank analysis
-    let result = challenges::get_analysis_result(tank_params);
-    
-    // Log timing information on completion
-    let elapsed = start_time.elapsed().as_millis() as f64;
-    tracing::info!(
-        request_id = %request_id,
-        tank_id = %tank_id,
-        analysis_duration_ms = elapsed,
-        overall_health = %result.overall_health,
-        operation_status = "success",
-        "Tank environmental analysis completed"
-    );
-    
-    Json(result)
+    async move {
+        // Add request ID for correlation and timing
+        let request_id = uuid::Uuid::new_v4().to_string();
+        let start_time = std::time::Instant::now();
+        
+        tracing::info!(
+            request_id = %request_id,
+            tank_id = %tank_id,
+            operation = "single_tank_analysis",
+            "Starting tank environmental analysis"
+        );
+        // Override tank_id from path parameter
+        let mut tank_params = params;
+        // Clone tank_id directly in the assignment to keep the original for logging
+        tank_params.tank_id = Some(tank_id.clone());
+        
+        // Get single tank analysis
+        let analysis = analysis::analyze_tank(&state.analysis_source, &state.rules, tank_params).await?;
+        
+        // Log timing information on completion
+        let elapsed = start_time.elapsed().as_millis() as f64;
+        tracing::info!(
+            request_id = %request_id,
+            tank_id = %tank_id,
+            analysis_duration_ms = elapsed,
+            overall_health = %analysis.result.overall_health,
+            health_score = ?analysis.health_score.score,
+            operation_status = "success",
+            "Tank environmental analysis completed"
+        );
+        
+        Ok::<_, ApiError>(Json(analysis))
+    }
+    .instrument(span)
+    .await
 }
 
 /// Validates the implementation of Challenge #3: String Allocation Optimization
@@ -0,0 +1,515 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use shuttle_axum::axum::{extract::State, response::IntoResponse, Json};
+
+use crate::{aqua_monitor::TankReading, species_hub::SpeciesProfile, AppState, ParameterStatus};
+
+/// Default location of the rules file, relative to the service's working directory.
+pub const DEFAULT_RULES_PATH: &str = "./config/analysis_rules.json";
+
+/// Placeholders a recommendation template may use.
+const PLACEHOLDERS: [&str; 5] = ["species", "value", "bound", "threshold", "delta"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Parameter {
+    Temperature,
+    Ph,
+    Oxygen,
+}
+
+impl Parameter {
+    pub const ALL: [Parameter; 3] = [Parameter::Temperature, Parameter::Ph, Parameter::Oxygen];
+
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "temperature" => Some(Parameter::Temperature),
+            "ph" => Some(Parameter::Ph),
+            "oxygen" => Some(Parameter::Oxygen),
+            _ => None,
+        }
+    }
+
+    pub fn reading(&self, reading: &TankReading) -> f64 {
+        match self {
+            Parameter::Temperature => reading.temperature,
+            Parameter::Ph => reading.ph,
+            Parameter::Oxygen => reading.oxygen_level,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum SpeciesField {
+    MinTemperature,
+    MaxTemperature,
+    MinPh,
+    MaxPh,
+    MinOxygen,
+}
+
+impl SpeciesField {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "min_temperature" => Some(SpeciesField::MinTemperature),
+            "max_temperature" => Some(SpeciesField::MaxTemperature),
+            "min_ph" => Some(SpeciesField::MinPh),
+            "max_ph" => Some(SpeciesField::MaxPh),
+            "min_oxygen" => Some(SpeciesField::MinOxygen),
+            _ => None,
+        }
+    }
+
+    /// `None` when the species has no tolerance recorded for this field.
+    fn value(&self, species: &SpeciesProfile) -> Option<f64> {
+        match self {
+            SpeciesField::MinTemperature => Some(species.min_temperature),
+            SpeciesField::MaxTemperature => Some(species.max_temperature),
+            SpeciesField::MinPh => Some(species.min_ph),
+            SpeciesField::MaxPh => Some(species.max_ph),
+            SpeciesField::MinOxygen => species.min_oxygen,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Comparison {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Comparison {
+    fn parse(op: &str) -> Option<Self> {
+        match op {
+            "<" => Some(Comparison::Lt),
+            "<=" => Some(Comparison::Le),
+            ">" => Some(Comparison::Gt),
+            ">=" => Some(Comparison::Ge),
+            _ => None,
+        }
+    }
+
+    fn holds(&self, value: f64, threshold: f64) -> bool {
+        match self {
+            Comparison::Lt => value < threshold,
+            Comparison::Le => value <= threshold,
+            Comparison::Gt => value > threshold,
+            Comparison::Ge => value >= threshold,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Operand {
+    Constant(f64),
+    Species { field: SpeciesField, offset: f64 },
+}
+
+impl Operand {
+    /// Returns the species bound (or the constant) and the threshold after the offset.
+    fn resolve(&self, species: &SpeciesProfile) -> Option<(f64, f64)> {
+        match self {
+            Operand::Constant(value) => Some((*value, *value)),
+            Operand::Species { field, offset } => field.value(species).map(|bound| (bound, bound + offset)),
+        }
+    }
+}
+
+/// A parsed `when` expression: `<parameter> <op> <number>` or
+/// `<parameter> <op> species.<field> [+|- <number>]`, tokens separated by spaces.
+#[derive(Debug, Clone, Copy)]
+struct Condition {
+    parameter: Parameter,
+    comparison: Comparison,
+    operand: Operand,
+}
+
+impl Condition {
+    fn parse(expression: &str) -> Result<Self, String> {
+        let tokens: Vec<&str> = expression.split_whitespace().collect();
+        let (parameter, op, operand, offset) = match tokens.as_slice() {
+            [parameter, op, operand] => (parameter, op, operand, None),
+            [parameter, op, operand, sign, amount] => (parameter, op, operand, Some((sign, amount))),
+            _ => {
+                return Err(format!(
+                    "expected `<parameter> <op> <value>` with tokens separated by spaces, got `{}`",
+                    expression
+                ))
+            }
+        };
+
+        let parameter = Parameter::parse(parameter).ok_or_else(|| {
+            format!("unknown parameter `{}` (expected temperature, ph or oxygen)", parameter)
+        })?;
+        let comparison = Comparison::parse(op)
+            .ok_or_else(|| format!("unknown operator `{}` (expected <, <=, > or >=)", op))?;
+
+        let offset = match offset {
+            None => 0.0,
+            Some((sign, amount)) => {
+                let amount: f64 = amount
+                    .parse()
+                    .map_err(|_| format!("`{}` is not a number", amount))?;
+                match *sign {
+                    "+" => amount,
+                    "-" => -amount,
+                    other => return Err(format!("expected + or - before the offset, got `{}`", other)),
+                }
+            }
+        };
+
+        let operand = match operand.strip_prefix("species.") {
+            Some(field) => Operand::Species {
+                field: SpeciesField::parse(field)
+                    .ok_or_else(|| format!("unknown species field `{}`", field))?,
+                offset,
+            },
+            None => {
+                let value: f64 = operand.parse().map_err(|_| {
+                    format!("`{}` is neither a number nor a species.<field> reference", operand)
+                })?;
+                Operand::Constant(value + offset)
+            }
+        };
+
+        Ok(Condition { parameter, comparison, operand })
+    }
+}
+
+/// The statuses a rule may produce; `unknown` is reserved for missing data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleStatus {
+    Normal,
+    Warning,
+    Critical,
+}
+
+impl RuleStatus {
+    fn parameter_status(&self) -> ParameterStatus {
+        match self {
+            RuleStatus::Normal => ParameterStatus::Normal,
+            RuleStatus::Warning => ParameterStatus::Warning,
+            RuleStatus::Critical => ParameterStatus::Critical,
+        }
+    }
+
+    fn severity(&self) -> u8 {
+        match self {
+            RuleStatus::Normal => 0,
+            RuleStatus::Warning => 1,
+            RuleStatus::Critical => 2,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RulesFile {
+    rules: Vec<RuleDefinition>,
+}
+
+#[derive(Deserialize)]
+struct RuleDefinition {
+    id: String,
+    when: String,
+    status: RuleStatus,
+    recommendation: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Rule {
+    pub id: String,
+    pub parameter: Parameter,
+    pub when: String,
+    pub status: RuleStatus,
+    pub recommendation: Option<String>,
+    #[serde(skip)]
+    condition: Condition,
+}
+
+/// The validated analysis rules loaded at startup.
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleSet {
+    pub source: String,
+    pub rules: Vec<Rule>,
+}
+
+/// A parameter's status against one species with the recommendation of the deciding rule.
+pub struct RuleOutcome {
+    pub status: ParameterStatus,
+    pub recommendation: Option<String>,
+}
+
+fn check_placeholders(template: &str) -> Result<(), String> {
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| "unclosed `{` in recommendation".to_string())?;
+        let name = &rest[start + 1..start + end];
+        if !PLACEHOLDERS.contains(&name) {
+            return Err(format!(
+                "unknown placeholder `{{{}}}` in recommendation (expected one of {})",
+                name,
+                PLACEHOLDERS.join(", ")
+            ));
+        }
+        rest = &rest[start + end + 1..];
+    }
+    Ok(())
+}
+
+fn render(template: &str, species: &SpeciesProfile, value: f64, bound: f64, threshold: f64) -> String {
+    template
+        .replace("{species}", &species.name)
+        .replace("{value}", &format!("{:.1}", value))
+        .replace("{bound}", &format!("{:.1}", bound))
+        .replace("{threshold}", &format!("{:.1}", threshold))
+        .replace("{delta}", &format!("{:.1}", (value - bound).abs()))
+}
+
+impl RuleSet {
+    /// Parses and validates a rules file, reporting every invalid rule at once.
+    pub fn parse(source: &str, contents: &str) -> Result<Self, String> {
+        let file: RulesFile =
+            serde_json::from_str(contents).map_err(|e| format!("{}: invalid rules file: {}", source, e))?;
+
+        let mut ids = HashSet::new();
+        let mut rules = Vec::with_capacity(file.rules.len());
+        let mut errors = Vec::new();
+
+        for definition in file.rules {
+            if !ids.insert(definition.id.clone()) {
+                errors.push(format!("rule `{}`: duplicate id", definition.id));
+                continue;
+            }
+            let checked = Condition::parse(&definition.when).and_then(|condition| {
+                if let Some(template) = &definition.recommendation {
+                    check_placeholders(template)?;
+                }
+                Ok(condition)
+            });
+            match checked {
+                Ok(condition) => rules.push(Rule {
+                    id: definition.id,
+                    parameter: condition.parameter,
+                    when: definition.when,
+                    status: definition.status,
+                    recommendation: definition.recommendation,
+                    condition,
+                }),
+                Err(e) => errors.push(format!("rule `{}`: {}", definition.id, e)),
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(format!("{}: {}", source, errors.join("; ")));
+        }
+
+        Ok(RuleSet {
+            source: source.to_string(),
+            rules,
+        })
+    }
+
+    pub async fn load(path: &str) -> Result<Self, String> {
+        let contents = tokio::fs::read_to_string(path)
+            .await
+            .map_err(|e| format!("{}: failed to read rules file: {}", path, e))?;
+        Self::parse(path, &contents)
+    }
+
+    /// Parameters no rule covers; their status is always unknown.
+    pub fn uncovered_parameters(&self) -> Vec<Parameter> {
+        Parameter::ALL
+            .into_iter()
+            .filter(|parameter| !self.rules.iter().any(|rule| rule.parameter == *parameter))
+            .collect()
+    }
+
+    /// Evaluates a parameter value against a species.
+    ///
+    /// Rules referring to a tolerance the species doesn't have are skipped; if none apply
+    /// the status is unknown. Otherwise the most severe matching rule decides, the first
+    /// in file order among equals, and no match at all is normal.
+    pub fn evaluate(&self, parameter: Parameter, value: f64, species: &SpeciesProfile) -> RuleOutcome {
+        let mut applicable = false;
+        let mut decided: Option<(&Rule, f64, f64)> = None;
+
+        for rule in self.rules.iter().filter(|rule| rule.parameter == parameter) {
+            let Some((bound, threshold)) = rule.condition.operand.resolve(species) else {
+                continue;
+            };
+            applicable = true;
+            if !rule.condition.comparison.holds(value, threshold) {
+                continue;
+            }
+            if !matches!(decided, Some((current, _, _)) if current.status.severity() >= rule.status.severity()) {
+                decided = Some((rule, bound, threshold));
+            }
+        }
+
+        match decided {
+            Some((rule, bound, threshold)) => RuleOutcome {
+                status: rule.status.parameter_status(),
+                recommendation: rule
+                    .recommendation
+                    .as_deref()
+                    .map(|template| render(template, species, value, bound, threshold)),
+            },
+            None if applicable => RuleOutcome {
+                status: ParameterStatus::Normal,
+                recommendation: None,
+            },
+            None => RuleOutcome {
+                status: ParameterStatus::Unknown,
+                recommendation: None,
+            },
+        }
+    }
+}
+
+/// Returns the analysis rules currently in effect.
+pub async fn get_analysis_rules(State(state): State<AppState>) -> impl IntoResponse {
+    Json(state.rules.as_ref().clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn species(min_oxygen: Option<f64>) -> SpeciesProfile {
+        SpeciesProfile {
+            min_oxygen,
+            ..test_support::species(1, "Cherry Shrimp")
+        }
+    }
+
+    fn rules(definitions: &str) -> RuleSet {
+        RuleSet::parse("test", &format!("{{\"rules\": [{}]}}", definitions)).unwrap()
+    }
+
+    fn parse_error(expression: &str) -> String {
+        Condition::parse(expression).unwrap_err()
+    }
+
+    #[test]
+    fn condition_parses_constants_and_species_fields() {
+        let condition = Condition::parse("temperature >= 25").unwrap();
+        assert_eq!(condition.parameter, Parameter::Temperature);
+        assert!(matches!(condition.comparison, Comparison::Ge));
+        assert!(matches!(condition.operand, Operand::Constant(value) if value == 25.0));
+
+        let condition = Condition::parse("ph < species.min_ph - 0.3").unwrap();
+        assert_eq!(condition.parameter, Parameter::Ph);
+        assert!(matches!(
+            condition.operand,
+            Operand::Species { field: SpeciesField::MinPh, offset } if (offset + 0.3).abs() < 1e-9
+        ));
+        assert_eq!(condition.operand.resolve(&species(None)), Some((6.5, 6.2)));
+
+        let condition = Condition::parse("oxygen <= 4 + 0.5").unwrap();
+        assert!(matches!(condition.operand, Operand::Constant(value) if value == 4.5));
+    }
+
+    #[test]
+    fn condition_rejects_malformed_expressions() {
+        assert!(parse_error("temperature<25").contains("tokens separated by spaces"));
+        assert!(parse_error("temperature < species.min_temperature -").contains("tokens separated by spaces"));
+        assert!(parse_error("salinity > 30").contains("unknown parameter `salinity`"));
+        assert!(parse_error("ph == 7").contains("unknown operator `==`"));
+        assert!(parse_error("ph < species.min_ph * 2").contains("expected + or -"));
+        assert!(parse_error("ph < species.min_ph + lots").contains("`lots` is not a number"));
+        assert!(parse_error("ph < species.min_kh").contains("unknown species field `min_kh`"));
+        assert!(parse_error("ph < neutral").contains("neither a number nor a species.<field> reference"));
+    }
+
+    #[test]
+    fn bundled_rules_file_is_valid() {
+        let rules = RuleSet::parse(DEFAULT_RULES_PATH, include_str!("../config/analysis_rules.json")).unwrap();
+
+        assert_eq!(rules.rules.len(), 15);
+        assert!(rules.uncovered_parameters().is_empty());
+    }
+
+    #[test]
+    fn rule_set_reports_every_invalid_rule() {
+        let error = RuleSet::parse(
+            "rules.json",
+            r#"{"rules": [
+                {"id": "a", "when": "ph < 6", "status": "warning"},
+                {"id": "a", "when": "ph < 5", "status": "critical"},
+                {"id": "b", "when": "ph <", "status": "warning"},
+                {"id": "c", "when": "ph < 6", "status": "warning", "recommendation": "Fix {colour}"},
+                {"id": "d", "when": "ph < 6", "status": "warning", "recommendation": "Fix {value"}
+            ]}"#,
+        )
+        .unwrap_err();
+
+        assert!(error.starts_with("rules.json: "));
+        assert!(error.contains("rule `a`: duplicate id"));
+        assert!(error.contains("rule `b`: expected `<parameter> <op> <value>`"));
+        assert!(error.contains("rule `c`: unknown placeholder `{colour}`"));
+        assert!(error.contains("rule `d`: unclosed `{`"));
+    }
+
+    #[test]
+    fn rule_set_rejects_unknown_statuses_and_bad_json() {
+        let error = RuleSet::parse("rules.json", r#"{"rules": [{"id": "a", "when": "ph < 6", "status": "unknown"}]}"#)
+            .unwrap_err();
+        assert!(error.starts_with("rules.json: invalid rules file"));
+
+        assert!(RuleSet::parse("rules.json", "[]").is_err());
+    }
+
+    #[test]
+    fn uncovered_parameters_are_listed() {
+        let rules = rules(r#"{"id": "a", "when": "ph < 6", "status": "warning"}"#);
+        assert_eq!(rules.uncovered_parameters(), vec![Parameter::Temperature, Parameter::Oxygen]);
+    }
+
+    #[test]
+    fn most_severe_match_wins_and_file_order_breaks_ties() {
+        let rules = rules(
+            r#"
+            {"id": "first", "when": "temperature > species.max_temperature - 2", "status": "warning", "recommendation": "first {value}"},
+            {"id": "second", "when": "temperature > species.max_temperature - 1", "status": "warning", "recommendation": "second"},
+            {"id": "worst", "when": "temperature > species.max_temperature + 2", "status": "critical", "recommendation": "{species}: lower by {delta} to {bound} (limit {threshold})"}
+            "#,
+        );
+        let profile = species(None);
+
+        let outcome = rules.evaluate(Parameter::Temperature, 27.5, &profile);
+        assert_eq!(outcome.status, ParameterStatus::Warning);
+        assert_eq!(outcome.recommendation.as_deref(), Some("first 27.5"));
+
+        let outcome = rules.evaluate(Parameter::Temperature, 31.0, &profile);
+        assert_eq!(outcome.status, ParameterStatus::Critical);
+        assert_eq!(
+            outcome.recommendation.as_deref(),
+            Some("Cherry Shrimp: lower by 3.0 to 28.0 (limit 30.0)")
+        );
+
+        let outcome = rules.evaluate(Parameter::Temperature, 24.0, &profile);
+        assert_eq!(outcome.status, ParameterStatus::Normal);
+        assert!(outcome.recommendation.is_none());
+    }
+
+    #[test]
+    fn missing_tolerances_leave_the_parameter_unknown() {
+        let rules = rules(
+            r#"
+            {"id": "low-oxygen", "when": "oxygen < species.min_oxygen", "status": "warning"},
+            {"id": "no-ph-rule-here", "when": "temperature > 40", "status": "critical"}
+            "#,
+        );
+
+        assert_eq!(rules.evaluate(Parameter::Oxygen, 3.0, &species(None)).status, ParameterStatus::Unknown);
+        assert_eq!(rules.evaluate(Parameter::Oxygen, 3.0, &species(Some(5.0))).status, ParameterStatus::Warning);
+        assert_eq!(rules.evaluate(Parameter::Ph, 7.0, &species(None)).status, ParameterStatus::Unknown);
+    }
+}
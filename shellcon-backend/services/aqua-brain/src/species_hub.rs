@@ -0,0 +1,194 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use serde::{de::DeserializeOwned, Deserialize};
+
+use crate::ApiError;
+
+/// Default species-hub address when running all services locally (see README ports).
+pub const DEFAULT_SPECIES_HUB_URL: &str = "http://localhost:8001";
+
+/// Cached species are served without contacting species-hub for this long.
+const SPECIES_FRESH_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Past the fresh TTL, entries up to this age are served while a background refresh runs.
+const SPECIES_STALE_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// The tolerance ranges of a species, as served by species-hub's species profile endpoint.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpeciesProfile {
+    pub id: i32,
+    pub name: String,
+    pub min_temperature: f64,
+    pub max_temperature: f64,
+    pub min_ph: f64,
+    pub max_ph: f64,
+    pub min_oxygen: Option<f64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TankInhabitant {
+    pub species_id: i32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TankFeedingStatus {
+    /// One of species-hub's feeding statuses: normal, overdue, overfed or unknown
+    pub status: String,
+    pub reasons: Vec<String>,
+}
+
+/// A cached lookup; `None` records that species-hub doesn't have the species.
+struct CachedSpecies {
+    profile: Option<SpeciesProfile>,
+    fetched_at: Instant,
+}
+
+#[derive(Default)]
+struct SpeciesCache {
+    entries: HashMap<i32, CachedSpecies>,
+    // Species with a background refresh in flight, so concurrent requests don't pile on
+    refreshing: HashSet<i32>,
+}
+
+/// Client for the species-hub service, created once and shared through `AppState`.
+///
+/// Species lookups are cached with a TTL and served stale while they revalidate.
+#[derive(Clone)]
+pub struct SpeciesHubClient {
+    client: reqwest::Client,
+    base_url: reqwest::Url,
+    species_cache: Arc<Mutex<SpeciesCache>>,
+}
+
+impl SpeciesHubClient {
+    pub fn new(base_url: &str) -> Result<Self, String> {
+        let base_url = reqwest::Url::parse(base_url)
+            .ok()
+            .filter(|url| !url.cannot_be_a_base())
+            .ok_or_else(|| format!("invalid species-hub URL: {:?}", base_url))?;
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+            .map_err(|e| format!("failed to build species-hub HTTP client: {}", e))?;
+
+        Ok(Self {
+            client,
+            base_url,
+            species_cache: Arc::default(),
+        })
+    }
+
+    /// GETs a species-hub path, mapping 404 to `None`.
+    ///
+    /// Each segment is percent-encoded, so an ID containing `/`, `?` or `#` stays
+    /// a single segment instead of reaching another route.
+    async fn get_optional<T: DeserializeOwned>(&self, segments: &[&str]) -> Result<Option<T>, ApiError> {
+        let unavailable =
+            |e: reqwest::Error| ApiError::SpeciesDataUnavailable(format!("species-hub: {}", e));
+
+        let mut url = self.base_url.clone();
+        // Always succeeds: `new` rejects base URLs that can't have a path
+        if let Ok(mut path) = url.path_segments_mut() {
+            path.pop_if_empty().extend(segments);
+        }
+
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(unavailable)?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        let body = response
+            .error_for_status()
+            .map_err(unavailable)?
+            .json()
+            .await
+            .map_err(unavailable)?;
+        Ok(Some(body))
+    }
+
+    async fn fetch_species(&self, species_id: i32) -> Result<Option<SpeciesProfile>, ApiError> {
+        let profile = self
+            .get_optional(&["api", "species", &species_id.to_string()])
+            .await?;
+        if let Ok(mut cache) = self.species_cache.lock() {
+            cache.entries.insert(
+                species_id,
+                CachedSpecies {
+                    profile: profile.clone(),
+                    fetched_at: Instant::now(),
+                },
+            );
+        }
+        Ok(profile)
+    }
+
+    /// Looks up a species, returning `None` if species-hub doesn't have it.
+    ///
+    /// Fresh entries come straight from the cache. Stale entries are returned immediately
+    /// while one background task refreshes them. Anything older is fetched before returning,
+    /// falling back to the expired entry if species-hub can't be reached.
+    pub async fn species(&self, species_id: i32) -> Result<Option<SpeciesProfile>, ApiError> {
+        let cached = match self.species_cache.lock() {
+            Ok(mut cache) => match cache.entries.get(&species_id) {
+                Some(entry) => {
+                    let age = entry.fetched_at.elapsed();
+                    let profile = entry.profile.clone();
+                    if age < SPECIES_FRESH_TTL {
+                        return Ok(profile);
+                    }
+                    if age < SPECIES_STALE_TTL {
+                        if cache.refreshing.insert(species_id) {
+                            self.spawn_species_refresh(species_id);
+                        }
+                        return Ok(profile);
+                    }
+                    Some(profile)
+                }
+                None => None,
+            },
+            Err(_) => None,
+        };
+
+        match self.fetch_species(species_id).await {
+            Ok(profile) => Ok(profile),
+            Err(e) => match cached {
+                Some(profile) => {
+                    tracing::warn!(species_id, error = %e, "Serving expired species data; species-hub unavailable");
+                    Ok(profile)
+                }
+                None => Err(e),
+            },
+        }
+    }
+
+    fn spawn_species_refresh(&self, species_id: i32) {
+        let client = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = client.fetch_species(species_id).await {
+                tracing::warn!(species_id, error = %e, "Background species refresh failed");
+            }
+            if let Ok(mut cache) = client.species_cache.lock() {
+                cache.refreshing.remove(&species_id);
+            }
+        });
+    }
+
+    /// Returns the populations living in a tank, or `None` if species-hub doesn't know the tank.
+    pub async fn tank_inhabitants(&self, tank_id: &str) -> Result<Option<Vec<TankInhabitant>>, ApiError> {
+        self.get_optional(&["api", "tanks", tank_id, "inhabitants"]).await
+    }
+
+    pub async fn feeding_status(&self, tank_id: &str) -> Result<Option<TankFeedingStatus>, ApiError> {
+        self.get_optional(&["api", "tanks", tank_id, "feeding-status"]).await
+    }
+}
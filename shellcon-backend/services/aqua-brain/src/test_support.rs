@@ -0,0 +1,16 @@
+//! Fixtures shared by the unit tests.
+
+use crate::species_hub::SpeciesProfile;
+
+/// A species comfortable at 20–28°C, pH 6.5–8.0 and 5 mg/L of oxygen; tests override what they exercise.
+pub(crate) fn species(id: i32, name: &str) -> SpeciesProfile {
+    SpeciesProfile {
+        id,
+        name: name.to_string(),
+        min_temperature: 20.0,
+        max_temperature: 28.0,
+        min_ph: 6.5,
+        max_ph: 8.0,
+        min_oxygen: Some(5.0),
+    }
+}
@@ -0,0 +1,442 @@
+use serde::Serialize;
+
+use crate::{aqua_monitor::TankReading, rules::Parameter, species_hub::SpeciesProfile};
+
+/// How far ahead trends are projected when the request doesn't say.
+pub const DEFAULT_HORIZON_HOURS: f64 = 6.0;
+
+/// Longer projections from a handful of readings aren't meaningful.
+pub const MAX_HORIZON_HOURS: f64 = 72.0;
+
+/// A line through fewer readings than this is mostly noise.
+const MIN_TREND_READINGS: usize = 3;
+
+/// Residual standard deviations the latest reading may sit from the trend line before
+/// it's flagged as an anomaly.
+const ANOMALY_SIGMAS: f64 = 3.0;
+
+/// Anomaly detection needs the latest reading plus enough earlier ones to measure their spread.
+const MIN_ANOMALY_READINGS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrendDirection {
+    Rising,
+    Falling,
+    Stable,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitKind {
+    Minimum,
+    Maximum,
+}
+
+/// When a trending parameter is expected to cross the tightest species limit in its path.
+#[derive(Debug, Clone, Serialize)]
+pub struct LimitForecast {
+    pub limit: LimitKind,
+    pub value: f64,
+    pub species_id: i32,
+    pub species_name: String,
+    /// Zero when the trend line is already past the limit
+    pub hours_until: f64,
+    pub within_horizon: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ParameterTrend {
+    pub parameter: Parameter,
+    pub latest: f64,
+    /// Least-squares slope over the readings, in units per hour
+    pub slope_per_hour: f64,
+    pub direction: TrendDirection,
+    /// The trend line's value at the end of the horizon
+    pub projected: f64,
+    /// `None` when the parameter is stable or no species limits it in that direction
+    pub limit: Option<LimitForecast>,
+    /// The latest reading departs sharply from the trend of the others
+    pub anomalous: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TrendReport {
+    pub horizon_hours: f64,
+    pub reading_count: usize,
+    /// Time between the oldest and newest reading used
+    pub window_hours: f64,
+    pub parameters: Vec<ParameterTrend>,
+}
+
+/// Keeps a requested horizon within the range a short reading history can support.
+pub fn horizon_hours(requested: Option<f64>) -> f64 {
+    match requested {
+        Some(hours) if hours.is_finite() && hours > 0.0 => hours.min(MAX_HORIZON_HOURS),
+        _ => DEFAULT_HORIZON_HOURS,
+    }
+}
+
+/// Change over the horizon below which a parameter counts as stable.
+fn stable_change(parameter: Parameter) -> f64 {
+    match parameter {
+        Parameter::Temperature => 0.2,
+        Parameter::Ph => 0.05,
+        Parameter::Oxygen => 0.2,
+    }
+}
+
+pub fn label(parameter: Parameter) -> (&'static str, &'static str) {
+    match parameter {
+        Parameter::Temperature => ("Temperature", "°C"),
+        Parameter::Ph => ("pH", ""),
+        Parameter::Oxygen => ("Dissolved oxygen", " mg/L"),
+    }
+}
+
+/// The species' tolerance range for a parameter; oxygen only has a minimum.
+fn species_limits(parameter: Parameter, species: &SpeciesProfile) -> (Option<f64>, Option<f64>) {
+    match parameter {
+        Parameter::Temperature => (Some(species.min_temperature), Some(species.max_temperature)),
+        Parameter::Ph => (Some(species.min_ph), Some(species.max_ph)),
+        Parameter::Oxygen => (species.min_oxygen, None),
+    }
+}
+
+/// The limit crossed first in the given direction: the highest minimum or the lowest maximum.
+fn tightest_limit(parameter: Parameter, kind: LimitKind, species: &[SpeciesProfile]) -> Option<(f64, &SpeciesProfile)> {
+    species
+        .iter()
+        .filter_map(|profile| {
+            let (min, max) = species_limits(parameter, profile);
+            match kind {
+                LimitKind::Minimum => min,
+                LimitKind::Maximum => max,
+            }
+            .map(|value| (value, profile))
+        })
+        .reduce(|tightest, candidate| {
+            let tighter = match kind {
+                LimitKind::Minimum => candidate.0 > tightest.0,
+                LimitKind::Maximum => candidate.0 < tightest.0,
+            };
+            if tighter {
+                candidate
+            } else {
+                tightest
+            }
+        })
+}
+
+/// Fits a least-squares line to `(hours, value)` points, returning the slope and the
+/// fitted value at hour zero.
+fn fit_line(points: &[(f64, f64)]) -> Option<(f64, f64)> {
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+
+    let sxx: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    if sxx <= f64::EPSILON {
+        return None;
+    }
+    let sxy: f64 = points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+
+    let slope = sxy / sxx;
+    Some((slope, mean_y - slope * mean_x))
+}
+
+/// Standard deviation of the points around the fitted line.
+fn residual_deviation(points: &[(f64, f64)], slope: f64, intercept: f64) -> f64 {
+    let squared: f64 = points
+        .iter()
+        .map(|(x, y)| (y - (intercept + slope * x)).powi(2))
+        .sum();
+    (squared / (points.len() as f64 - 2.0)).sqrt()
+}
+
+/// Whether the latest reading (at hour zero) sits far off the line through the earlier ones.
+///
+/// The latest reading is left out of the fit, otherwise it pulls the line towards itself
+/// and inflates the spread it's measured against.
+fn is_anomalous(parameter: Parameter, points: &[(f64, f64)], latest: f64) -> bool {
+    if points.len() < MIN_ANOMALY_READINGS {
+        return false;
+    }
+    let earlier: Vec<(f64, f64)> = points.iter().copied().filter(|(hours, _)| *hours < 0.0).collect();
+    if earlier.len() < MIN_ANOMALY_READINGS - 1 {
+        return false;
+    }
+    let Some((slope, expected)) = fit_line(&earlier) else {
+        return false;
+    };
+
+    let deviation = (latest - expected).abs();
+    deviation > stable_change(parameter)
+        && deviation > ANOMALY_SIGMAS * residual_deviation(&earlier, slope, expected)
+}
+
+fn parameter_trend(
+    parameter: Parameter,
+    readings: &[TankReading],
+    latest: &TankReading,
+    species: &[SpeciesProfile],
+    horizon_hours: f64,
+) -> Option<ParameterTrend> {
+    // Hours relative to the latest reading, so the fitted value at zero is "now"
+    let points: Vec<(f64, f64)> = readings
+        .iter()
+        .map(|reading| {
+            let hours = (reading.timestamp - latest.timestamp).num_seconds() as f64 / 3600.0;
+            (hours, parameter.reading(reading))
+        })
+        .collect();
+    let (slope, fitted) = fit_line(&points)?;
+
+    let direction = if (slope * horizon_hours).abs() < stable_change(parameter) {
+        TrendDirection::Stable
+    } else if slope > 0.0 {
+        TrendDirection::Rising
+    } else {
+        TrendDirection::Falling
+    };
+
+    let limit_kind = match direction {
+        TrendDirection::Rising => Some(LimitKind::Maximum),
+        TrendDirection::Falling => Some(LimitKind::Minimum),
+        TrendDirection::Stable => None,
+    };
+    let limit = limit_kind.and_then(|kind| {
+        let (value, profile) = tightest_limit(parameter, kind, species)?;
+        let hours_until = ((value - fitted) / slope).max(0.0);
+        Some(LimitForecast {
+            limit: kind,
+            value,
+            species_id: profile.id,
+            species_name: profile.name.clone(),
+            hours_until,
+            within_horizon: hours_until <= horizon_hours,
+        })
+    });
+
+    let latest_value = parameter.reading(latest);
+    let anomalous = is_anomalous(parameter, &points, latest_value);
+
+    Some(ParameterTrend {
+        parameter,
+        latest: latest_value,
+        slope_per_hour: slope,
+        direction,
+        projected: fitted + slope * horizon_hours,
+        limit,
+        anomalous,
+    })
+}
+
+/// Fits a linear trend per parameter over the recent readings and forecasts when each
+/// parameter will cross the tightest limit of the species in scope.
+///
+/// Returns `None` when there are too few readings, or they share one timestamp.
+pub fn analyze(readings: &[TankReading], species: &[SpeciesProfile], horizon_hours: f64) -> Option<TrendReport> {
+    if readings.len() < MIN_TREND_READINGS {
+        return None;
+    }
+    let latest = readings.iter().max_by_key(|r| r.timestamp)?;
+    let oldest = readings.iter().min_by_key(|r| r.timestamp)?;
+
+    let parameters: Vec<ParameterTrend> = Parameter::ALL
+        .into_iter()
+        .filter_map(|parameter| parameter_trend(parameter, readings, latest, species, horizon_hours))
+        .collect();
+    if parameters.is_empty() {
+        return None;
+    }
+
+    Some(TrendReport {
+        horizon_hours,
+        reading_count: readings.len(),
+        window_hours: (latest.timestamp - oldest.timestamp).num_seconds() as f64 / 3600.0,
+        parameters,
+    })
+}
+
+/// Warnings for parameters still within range that are forecast to leave it within the horizon.
+///
+/// Parameters already past a limit are covered by the rule-based recommendations.
+pub fn forecast_recommendations(report: &TrendReport) -> Vec<String> {
+    report
+        .parameters
+        .iter()
+        .filter_map(|trend| {
+            let limit = trend.limit.as_ref()?;
+            if !limit.within_horizon || limit.hours_until <= 0.0 {
+                return None;
+            }
+            let (label, unit) = label(trend.parameter);
+            let (movement, crossing, bound) = match limit.limit {
+                LimitKind::Minimum => ("falling", "drop below", "minimum"),
+                LimitKind::Maximum => ("rising", "rise above", "maximum"),
+            };
+            Some(format!(
+                "{} is {} {:.2}{} per hour and will {} {}'s {} of {:.1}{} in about {:.1} hours",
+                label,
+                movement,
+                trend.slope_per_hour.abs(),
+                unit,
+                crossing,
+                limit.species_name,
+                bound,
+                limit.value,
+                unit,
+                limit.hours_until
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use chrono::{DateTime, Duration, Utc};
+
+    fn species(id: i32, name: &str, ph: (f64, f64), max_temperature: f64) -> SpeciesProfile {
+        SpeciesProfile {
+            min_temperature: 18.0,
+            max_temperature,
+            min_ph: ph.0,
+            max_ph: ph.1,
+            ..test_support::species(id, name)
+        }
+    }
+
+    /// Hourly readings ending at a fixed time, oldest first.
+    fn readings(values: &[(f64, f64, f64)]) -> Vec<TankReading> {
+        let end: DateTime<Utc> = "2025-06-01T12:00:00Z".parse().unwrap();
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, (temperature, ph, oxygen_level))| TankReading {
+                temperature: *temperature,
+                ph: *ph,
+                oxygen_level: *oxygen_level,
+                timestamp: end - Duration::hours((values.len() - 1 - i) as i64),
+            })
+            .collect()
+    }
+
+    fn trend_for(report: &TrendReport, parameter: Parameter) -> &ParameterTrend {
+        report.parameters.iter().find(|t| t.parameter == parameter).unwrap()
+    }
+
+    #[test]
+    fn fit_line_recovers_slope_and_intercept() {
+        let (slope, intercept) = fit_line(&[(-3.0, 7.3), (-2.0, 7.2), (-1.0, 7.1), (0.0, 7.0)]).unwrap();
+
+        assert!((slope + 0.1).abs() < 1e-9);
+        assert!((intercept - 7.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn fit_line_needs_distinct_times() {
+        assert!(fit_line(&[(0.0, 7.0), (0.0, 7.4)]).is_none());
+    }
+
+    #[test]
+    fn latest_reading_far_off_the_earlier_trend_is_anomalous() {
+        let earlier = [(-4.0, 25.0), (-3.0, 25.1), (-2.0, 24.95), (-1.0, 25.05)];
+        let with_latest = |latest: f64| {
+            let mut points = earlier.to_vec();
+            points.push((0.0, latest));
+            is_anomalous(Parameter::Temperature, &points, latest)
+        };
+
+        assert!(with_latest(27.0));
+        assert!(!with_latest(25.1));
+    }
+
+    #[test]
+    fn small_or_unsupported_deviations_are_not_anomalous() {
+        // A perfect line has no spread, but a jump below the stable change isn't flagged
+        let points = [(-3.0, 7.0), (-2.0, 7.0), (-1.0, 7.0), (0.0, 7.04)];
+        assert!(!is_anomalous(Parameter::Ph, &points, 7.04));
+
+        // Too few earlier readings to judge
+        let points = [(-2.0, 7.0), (-1.0, 7.0), (0.0, 9.0)];
+        assert!(!is_anomalous(Parameter::Ph, &points, 9.0));
+    }
+
+    #[test]
+    fn falling_parameter_forecasts_hours_until_the_highest_minimum() {
+        let report = analyze(
+            &readings(&[(24.0, 7.3, 7.0), (24.0, 7.2, 7.0), (24.0, 7.1, 7.0), (24.0, 7.0, 7.0)]),
+            &[species(1, "Tolerant", (6.0, 8.0), 28.0), species(2, "Fussy", (6.5, 8.0), 28.0)],
+            DEFAULT_HORIZON_HOURS,
+        )
+        .unwrap();
+
+        assert_eq!(report.reading_count, 4);
+        assert_eq!(report.window_hours, 3.0);
+        let ph = trend_for(&report, Parameter::Ph);
+        assert_eq!(ph.direction, TrendDirection::Falling);
+        assert!((ph.projected - 6.4).abs() < 1e-9);
+        let limit = ph.limit.as_ref().unwrap();
+        assert_eq!(limit.limit, LimitKind::Minimum);
+        assert_eq!(limit.species_id, 2);
+        assert!((limit.hours_until - 5.0).abs() < 1e-9);
+        assert!(limit.within_horizon);
+
+        assert_eq!(trend_for(&report, Parameter::Temperature).direction, TrendDirection::Stable);
+        assert!(trend_for(&report, Parameter::Temperature).limit.is_none());
+
+        let recommendations = forecast_recommendations(&report);
+        assert_eq!(recommendations.len(), 1);
+        assert!(recommendations[0].starts_with("pH is falling 0.10 per hour and will drop below Fussy's minimum of 6.5"));
+        assert!(recommendations[0].ends_with("in about 5.0 hours"));
+    }
+
+    #[test]
+    fn limit_already_passed_is_zero_hours_away() {
+        let report = analyze(
+            &readings(&[(26.0, 7.0, 7.0), (27.0, 7.0, 7.0), (28.0, 7.0, 7.0), (29.0, 7.0, 7.0)]),
+            &[species(1, "Warm", (6.0, 8.0), 30.0), species(2, "Cool", (6.0, 8.0), 27.0)],
+            DEFAULT_HORIZON_HOURS,
+        )
+        .unwrap();
+
+        let temperature = trend_for(&report, Parameter::Temperature);
+        assert_eq!(temperature.direction, TrendDirection::Rising);
+        let limit = temperature.limit.as_ref().unwrap();
+        assert_eq!((limit.limit, limit.species_id), (LimitKind::Maximum, 2));
+        assert_eq!(limit.hours_until, 0.0);
+        // Already past the limit, so the rules cover it rather than a forecast
+        assert!(forecast_recommendations(&report).is_empty());
+    }
+
+    #[test]
+    fn distant_limits_fall_outside_the_horizon() {
+        let report = analyze(
+            &readings(&[(24.0, 7.3, 7.0), (24.0, 7.2, 7.0), (24.0, 7.1, 7.0), (24.0, 7.0, 7.0)]),
+            &[species(1, "Tolerant", (6.0, 8.0), 28.0)],
+            DEFAULT_HORIZON_HOURS,
+        )
+        .unwrap();
+
+        let limit = trend_for(&report, Parameter::Ph).limit.as_ref().unwrap();
+        assert!((limit.hours_until - 10.0).abs() < 1e-9);
+        assert!(!limit.within_horizon);
+    }
+
+    #[test]
+    fn too_few_readings_give_no_trend() {
+        assert!(analyze(&readings(&[(24.0, 7.0, 7.0), (24.1, 7.0, 7.0)]), &[], DEFAULT_HORIZON_HOURS).is_none());
+    }
+
+    #[test]
+    fn horizon_is_clamped() {
+        assert_eq!(horizon_hours(None), DEFAULT_HORIZON_HOURS);
+        assert_eq!(horizon_hours(Some(-1.0)), DEFAULT_HORIZON_HOURS);
+        assert_eq!(horizon_hours(Some(f64::NAN)), DEFAULT_HORIZON_HOURS);
+        assert_eq!(horizon_hours(Some(12.0)), 12.0);
+        assert_eq!(horizon_hours(Some(500.0)), MAX_HORIZON_HOURS);
+    }
+}
@@ -1,4 +1,5 @@
 mod challenges;
+mod registry;
 
 use serde::{Deserialize, Serialize};
 use serde_json::json;
@@ -115,6 +116,7 @@ async fn axum(#[shuttle_shared_db::Postgres] pool: PgPool) -> shuttle_axum::Shut
     let router = Router::new()
         .layer(tower_http::trace::TraceLayer::new_for_http())
         .route("/api/tanks", get(get_all_tanks))
+        .route("/api/tanks/registry", get(registry::get_tank_registry))
         .route("/api/tanks/:tank_id/readings", get(challenges::get_tank_readings))
         .route(
             "/api/challenges/1/validate",
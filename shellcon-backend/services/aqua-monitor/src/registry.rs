@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use shuttle_axum::axum::{extract::State, response::IntoResponse, Json};
+use sqlx::Row;
+
+use crate::{ApiError, AppState};
+
+const TANK_SETTINGS_PATH: &str = "./config/tank_settings.json";
+
+#[derive(Deserialize)]
+struct TankSettingsFile {
+    #[serde(default)]
+    tanks: Vec<ConfiguredTank>,
+}
+
+#[derive(Deserialize)]
+struct ConfiguredTank {
+    id: String,
+    name: String,
+    volume_liters: f64,
+    cleaning_interval_days: Option<i32>,
+}
+
+/// A tank known to aqua-monitor, either configured in the settings file or seen in readings.
+///
+/// Tanks that only appear in readings have no name or volume.
+#[derive(Serialize)]
+pub struct RegisteredTank {
+    id: String,
+    name: Option<String>,
+    volume_liters: Option<f64>,
+    cleaning_interval_days: Option<i32>,
+    configured: bool,
+    has_readings: bool,
+}
+
+/// Returns the tank registry other services synchronise against.
+pub async fn get_tank_registry(State(state): State<AppState>) -> Result<impl IntoResponse, ApiError> {
+    // A missing or broken settings file must not look like an empty registry to consumers
+    let config = tokio::fs::read_to_string(TANK_SETTINGS_PATH)
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Failed to read tank settings: {}", e)))?;
+    let settings: TankSettingsFile = serde_json::from_str(&config)
+        .map_err(|e| ApiError::InternalError(format!("Invalid tank settings: {}", e)))?;
+
+    let rows = sqlx::query("SELECT DISTINCT tank_id FROM tank_readings")
+        .fetch_all(&state.pool)
+        .await?;
+
+    let mut tanks: BTreeMap<String, RegisteredTank> = settings
+        .tanks
+        .into_iter()
+        .map(|tank| {
+            let registered = RegisteredTank {
+                id: tank.id.clone(),
+                name: Some(tank.name),
+                volume_liters: Some(tank.volume_liters),
+                cleaning_interval_days: tank.cleaning_interval_days,
+                configured: true,
+                has_readings: false,
+            };
+            (tank.id, registered)
+        })
+        .collect();
+
+    for row in rows {
+        let tank_id: String = row.get("tank_id");
+        tanks
+            .entry(tank_id.clone())
+            .or_insert_with(|| RegisteredTank {
+                id: tank_id,
+                name: None,
+                volume_liters: None,
+                cleaning_interval_days: None,
+                configured: false,
+                has_readings: false,
+            })
+            .has_readings = true;
+    }
+
+    tracing::info!(
+        operation = "get_tank_registry",
+        tank_count = tanks.len(),
+        "Tank registry assembled"
+    );
+
+    Ok(Json(tanks.into_values().collect::<Vec<_>>()))
+}
@@ -0,0 +1,40 @@
+-- Create curated species interactions table
+-- Parameter overlap alone cannot tell us whether one species will eat another,
+-- so known predator/prey relationships are recorded here and maintained by curators.
+CREATE TABLE IF NOT EXISTS species_interactions (
+    id SERIAL PRIMARY KEY,
+    predator_id INTEGER NOT NULL REFERENCES species(id) ON DELETE CASCADE,
+    prey_id INTEGER NOT NULL REFERENCES species(id) ON DELETE CASCADE,
+    aggression_level VARCHAR(20) NOT NULL CHECK (aggression_level IN ('low', 'moderate', 'high')),
+    notes TEXT,
+    UNIQUE (predator_id, prey_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_species_interactions_prey_id ON species_interactions(prey_id);
+
+-- Insert sample data, resolved by scientific name so it does not depend on SERIAL ids
+-- A species may appear as both predator and prey (e.g. cannibalism after a molt)
+INSERT INTO species_interactions (predator_id, prey_id, aggression_level, notes)
+SELECT predator.id, prey.id, seed.aggression_level, seed.notes
+FROM (
+    VALUES
+        ('Odontodactylus scyllarus', 'Neocaridina davidi', 'high', 'Mantis shrimp will hunt and eat any small shrimp.'),
+        ('Odontodactylus scyllarus', 'Caridina multidentata', 'high', 'Mantis shrimp will hunt and eat any small shrimp.'),
+        ('Odontodactylus scyllarus', 'Palaemonetes paludosus', 'high', 'Mantis shrimp will hunt and eat any small shrimp.'),
+        ('Odontodactylus scyllarus', 'Lybia tessellata', 'high', 'Small crabs are a natural prey item for mantis shrimp.'),
+        ('Odontodactylus scyllarus', 'Alpheus bellulus', 'high', 'Pistol shrimp are regularly preyed upon by mantis shrimp.'),
+        ('Alpheus bellulus', 'Neocaridina davidi', 'high', 'Tiger pistol shrimp stun and eat dwarf shrimp with their snapping claw.'),
+        ('Alpheus bellulus', 'Caridina multidentata', 'moderate', 'Adult Amano shrimp are usually too large, but juveniles are taken.'),
+        ('Homarus gammarus', 'Palaemonetes paludosus', 'high', 'Lobsters are opportunistic predators of small shrimp.'),
+        ('Homarus gammarus', 'Neocaridina davidi', 'high', 'Lobsters are opportunistic predators of small shrimp.'),
+        ('Procambarus virginalis', 'Neocaridina davidi', 'high', 'Marble crayfish readily catch and eat dwarf shrimp.'),
+        ('Procambarus virginalis', 'Caridina multidentata', 'moderate', 'Amano shrimp are at risk, especially while molting.'),
+        ('Procambarus virginalis', 'Procambarus virginalis', 'moderate', 'Cannibalism of freshly molted individuals in crowded tanks.'),
+        ('Cambarellus patzcuarensis', 'Neocaridina davidi', 'moderate', 'Dwarf crayfish are generally peaceful but will take shrimplets.'),
+        ('Cambarellus patzcuarensis', 'Limnopilos naiyanetri', 'moderate', 'Micro crabs are small enough to be caught by dwarf crayfish.'),
+        ('Geosesarma dennerle', 'Neocaridina davidi', 'moderate', 'Vampire crabs will catch small or molting shrimp.'),
+        ('Palaemonetes paludosus', 'Neocaridina davidi', 'low', 'Ghost shrimp may pick off shrimplets but leave adults alone.')
+) AS seed(predator_scientific_name, prey_scientific_name, aggression_level, notes)
+JOIN species predator ON predator.scientific_name = seed.predator_scientific_name
+JOIN species prey ON prey.scientific_name = seed.prey_scientific_name
+ON CONFLICT (predator_id, prey_id) DO NOTHING;
@@ -0,0 +1,67 @@
+-- Create tank inhabitants table linking tanks to the species living in them
+-- A row is active while removed_at is NULL; emptied populations are kept for history
+CREATE TABLE IF NOT EXISTS tank_inhabitants (
+    id SERIAL PRIMARY KEY,
+    tank_id VARCHAR(50) NOT NULL REFERENCES tanks(id) ON DELETE CASCADE,
+    species_id INTEGER NOT NULL REFERENCES species(id) ON DELETE CASCADE,
+    count INTEGER NOT NULL CHECK (count >= 0),
+    added_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    removed_at TIMESTAMPTZ
+);
+
+-- Only one active population per species in a tank
+CREATE UNIQUE INDEX IF NOT EXISTS idx_tank_inhabitants_active
+    ON tank_inhabitants(tank_id, species_id)
+    WHERE removed_at IS NULL;
+
+-- Every change to a population is recorded here
+CREATE TABLE IF NOT EXISTS tank_population_changes (
+    id SERIAL PRIMARY KEY,
+    inhabitant_id INTEGER NOT NULL REFERENCES tank_inhabitants(id) ON DELETE CASCADE,
+    tank_id VARCHAR(50) NOT NULL,
+    species_id INTEGER NOT NULL,
+    change_type VARCHAR(20) NOT NULL CHECK (change_type IN ('added', 'removed', 'moved_in', 'moved_out')),
+    count_delta INTEGER NOT NULL,
+    count_after INTEGER NOT NULL,
+    related_tank_id VARCHAR(50),
+    note TEXT,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_tank_population_changes_tank_id ON tank_population_changes(tank_id, changed_at);
+
+-- Euryhaline shrimp for the brackish demo tanks: aqua-monitor reports Tank-B2 at ~30 ppt and
+-- 22-23°C and Tank-C3 at ~25 ppt and 18°C, which none of the freshwater species can live in
+INSERT INTO species (name, scientific_name, description, min_temperature, max_temperature, min_ph, max_ph, diet_type)
+SELECT seed.name, seed.scientific_name, seed.description, seed.min_temperature, seed.max_temperature, seed.min_ph,
+       seed.max_ph, seed.diet_type
+FROM (
+    VALUES
+        ('Rockpool Prawn', 'Palaemon elegans', 'Hardy coastal prawn that copes with the salinity swings of tide pools.',
+         10.0, 25.0, 7.5, 8.5, 'omnivore'),
+        ('Daggerblade Grass Shrimp', 'Palaemonetes pugio', 'Estuarine grass shrimp found from nearly fresh to full-strength seawater.',
+         5.0, 30.0, 6.0, 8.5, 'omnivore')
+) AS seed(name, scientific_name, description, min_temperature, max_temperature, min_ph, max_ph, diet_type)
+WHERE NOT EXISTS (
+    SELECT 1 FROM species WHERE LOWER(species.scientific_name) = LOWER(seed.scientific_name)
+);
+
+-- Insert sample data so the demo tanks are not empty
+WITH seed(tank_id, scientific_name, count) AS (
+    VALUES
+        ('Tank-A1', 'Lybia tessellata', 2),
+        ('Tank-B2', 'Palaemon elegans', 8),
+        ('Tank-B2', 'Palaemonetes pugio', 6),
+        ('Tank-C3', 'Palaemonetes pugio', 12)
+),
+inserted AS (
+    INSERT INTO tank_inhabitants (tank_id, species_id, count)
+    SELECT seed.tank_id, species.id, seed.count
+    FROM seed
+    JOIN species ON species.scientific_name = seed.scientific_name
+    JOIN tanks ON tanks.id = seed.tank_id
+    RETURNING id, tank_id, species_id, count
+)
+INSERT INTO tank_population_changes (inhabitant_id, tank_id, species_id, change_type, count_delta, count_after, note)
+SELECT id, tank_id, species_id, 'added', count, count, 'Initial stocking'
+FROM inserted;
@@ -0,0 +1,13 @@
+-- Create feeding events table recording every feeding that actually happened
+-- species_id is optional so a single portion for the whole tank can be logged once
+CREATE TABLE IF NOT EXISTS feeding_events (
+    id SERIAL PRIMARY KEY,
+    tank_id VARCHAR(50) NOT NULL REFERENCES tanks(id) ON DELETE CASCADE,
+    species_id INTEGER REFERENCES species(id) ON DELETE SET NULL,
+    food_type VARCHAR(100) NOT NULL,
+    amount_grams FLOAT NOT NULL CHECK (amount_grams > 0),
+    fed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    fed_by VARCHAR(100) NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_feeding_events_tank_id_fed_at ON feeding_events(tank_id, fed_at DESC);
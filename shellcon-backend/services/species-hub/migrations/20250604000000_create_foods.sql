@@ -0,0 +1,22 @@
+-- Create foods catalog used to validate custom diets in feeding schedules
+CREATE TABLE IF NOT EXISTS foods (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(100) NOT NULL UNIQUE,
+    diet_types TEXT[] NOT NULL,
+    protein_percent FLOAT NOT NULL CHECK (protein_percent >= 0 AND protein_percent <= 100),
+    portion_unit VARCHAR(20) NOT NULL
+);
+
+-- Insert sample data; includes every default food chosen by the feeding schedule
+INSERT INTO foods (name, diet_types, protein_percent, portion_unit)
+VALUES
+    ('bloodworms', ARRAY['carnivore', 'omnivore'], 56.0, 'cube'),
+    ('mysis shrimp', ARRAY['carnivore', 'omnivore'], 62.0, 'cube'),
+    ('brine shrimp', ARRAY['carnivore', 'omnivore', 'filter feeder'], 50.0, 'cube'),
+    ('algae wafers', ARRAY['herbivore', 'omnivore'], 32.0, 'wafer'),
+    ('blanched spinach', ARRAY['herbivore', 'omnivore'], 3.0, 'leaf'),
+    ('spirulina powder', ARRAY['herbivore', 'omnivore', 'filter feeder'], 60.0, 'pinch'),
+    ('phytoplankton', ARRAY['filter feeder'], 20.0, 'ml'),
+    ('shrimp pellets', ARRAY['herbivore', 'omnivore'], 38.0, 'pellet'),
+    ('flake food', ARRAY['omnivore', 'herbivore', 'carnivore'], 45.0, 'pinch')
+ON CONFLICT (name) DO NOTHING;
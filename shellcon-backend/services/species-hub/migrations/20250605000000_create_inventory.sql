@@ -0,0 +1,46 @@
+-- Create inventory tables for foods and water additives
+-- Items linked to a catalog food are stocked in grams and depleted by logged feedings
+CREATE TABLE IF NOT EXISTS inventory_items (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(100) NOT NULL UNIQUE,
+    category VARCHAR(20) NOT NULL CHECK (category IN ('food', 'additive')),
+    food_id INTEGER UNIQUE REFERENCES foods(id) ON DELETE SET NULL,
+    unit VARCHAR(20) NOT NULL,
+    quantity FLOAT NOT NULL DEFAULT 0 CHECK (quantity >= 0),
+    reorder_lead_days INTEGER NOT NULL DEFAULT 7 CHECK (reorder_lead_days >= 0),
+    safety_stock FLOAT NOT NULL DEFAULT 0 CHECK (safety_stock >= 0),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Every stock change: received shipments, feedings and manual adjustments
+CREATE TABLE IF NOT EXISTS inventory_movements (
+    id SERIAL PRIMARY KEY,
+    item_id INTEGER NOT NULL REFERENCES inventory_items(id) ON DELETE CASCADE,
+    movement_type VARCHAR(20) NOT NULL CHECK (movement_type IN ('shipment', 'feeding', 'adjustment')),
+    quantity_delta FLOAT NOT NULL,
+    quantity_after FLOAT NOT NULL,
+    feeding_event_id INTEGER REFERENCES feeding_events(id) ON DELETE SET NULL,
+    note TEXT,
+    occurred_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_inventory_movements_item_id ON inventory_movements(item_id, occurred_at DESC);
+
+-- Insert sample data
+INSERT INTO inventory_items (name, category, food_id, unit, quantity, reorder_lead_days, safety_stock)
+SELECT foods.name, 'food', foods.id, 'g', seed.quantity, seed.reorder_lead_days, seed.safety_stock
+FROM (
+    VALUES
+        ('bloodworms', 120.0, 5, 20.0),
+        ('algae wafers', 400.0, 7, 30.0),
+        ('phytoplankton', 250.0, 10, 25.0),
+        ('flake food', 300.0, 7, 20.0)
+) AS seed(food_name, quantity, reorder_lead_days, safety_stock)
+JOIN foods ON foods.name = seed.food_name
+ON CONFLICT (name) DO NOTHING;
+
+INSERT INTO inventory_items (name, category, unit, quantity, reorder_lead_days, safety_stock)
+VALUES
+    ('water conditioner', 'additive', 'ml', 500.0, 7, 50.0),
+    ('crustacean mineral supplement', 'additive', 'g', 200.0, 14, 20.0)
+ON CONFLICT (name) DO NOTHING;
@@ -0,0 +1,10 @@
+-- Add per-tank timezone and cleaning schedule to tanks
+-- A NULL timezone means the tank follows the facility timezone (FACILITY_TIMEZONE secret)
+ALTER TABLE tanks ADD COLUMN IF NOT EXISTS timezone VARCHAR(64);
+ALTER TABLE tanks ADD COLUMN IF NOT EXISTS cleaning_interval_days INTEGER NOT NULL DEFAULT 14 CHECK (cleaning_interval_days > 0);
+ALTER TABLE tanks ADD COLUMN IF NOT EXISTS last_cleaned_at TIMESTAMPTZ;
+
+-- Sample cleaning schedules for the demo tanks
+UPDATE tanks SET cleaning_interval_days = 14, last_cleaned_at = NOW() - INTERVAL '10 days' WHERE id = 'Tank-A1';
+UPDATE tanks SET cleaning_interval_days = 7, last_cleaned_at = NOW() - INTERVAL '3 days' WHERE id = 'Tank-B2';
+UPDATE tanks SET cleaning_interval_days = 7, last_cleaned_at = NOW() - INTERVAL '6 days' WHERE id = 'Tank-C3';
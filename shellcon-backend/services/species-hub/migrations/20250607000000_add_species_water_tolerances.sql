@@ -0,0 +1,58 @@
+-- Extend species with salinity, oxygen and hardness tolerances, water type and care level
+-- Units: salinity in ppt, dissolved oxygen in mg/L, GH in dGH, KH in dKH
+-- GH/KH are nullable because general hardness isn't a meaningful target for marine species
+ALTER TABLE species ADD COLUMN IF NOT EXISTS min_salinity FLOAT;
+ALTER TABLE species ADD COLUMN IF NOT EXISTS max_salinity FLOAT;
+ALTER TABLE species ADD COLUMN IF NOT EXISTS min_oxygen FLOAT;
+ALTER TABLE species ADD COLUMN IF NOT EXISTS min_gh FLOAT;
+ALTER TABLE species ADD COLUMN IF NOT EXISTS max_gh FLOAT;
+ALTER TABLE species ADD COLUMN IF NOT EXISTS min_kh FLOAT;
+ALTER TABLE species ADD COLUMN IF NOT EXISTS max_kh FLOAT;
+ALTER TABLE species ADD COLUMN IF NOT EXISTS water_type VARCHAR(20);
+ALTER TABLE species ADD COLUMN IF NOT EXISTS care_level VARCHAR(20);
+
+-- Populate the sample species
+UPDATE species SET
+    min_salinity = seed.min_salinity,
+    max_salinity = seed.max_salinity,
+    min_oxygen = seed.min_oxygen,
+    min_gh = seed.min_gh,
+    max_gh = seed.max_gh,
+    min_kh = seed.min_kh,
+    max_kh = seed.max_kh,
+    water_type = seed.water_type,
+    care_level = seed.care_level
+FROM (
+    VALUES
+        ('Odontodactylus scyllarus', 32.0, 36.0, 6.0, NULL::FLOAT, NULL::FLOAT, 8.0, 12.0, 'marine', 'advanced'),
+        ('Homarus gammarus', 30.0, 35.0, 7.0, NULL, NULL, 7.0, 11.0, 'marine', 'advanced'),
+        ('Geosesarma dennerle', 0.0, 0.5, 5.0, 6.0, 15.0, 3.0, 10.0, 'fresh', 'intermediate'),
+        ('Caridina multidentata', 0.0, 0.5, 5.5, 6.0, 15.0, 2.0, 8.0, 'fresh', 'beginner'),
+        ('Neocaridina davidi', 0.0, 0.5, 5.0, 6.0, 16.0, 2.0, 10.0, 'fresh', 'beginner'),
+        ('Cambarellus patzcuarensis', 0.0, 0.5, 5.0, 8.0, 18.0, 4.0, 12.0, 'fresh', 'beginner'),
+        ('Limnopilos naiyanetri', 0.0, 0.5, 6.0, 6.0, 12.0, 3.0, 8.0, 'fresh', 'advanced'),
+        ('Palaemonetes paludosus', 0.0, 2.0, 5.0, 5.0, 20.0, 3.0, 10.0, 'fresh', 'beginner'),
+        ('Atyopsis moluccensis', 0.0, 0.5, 6.5, 6.0, 15.0, 3.0, 10.0, 'fresh', 'intermediate'),
+        ('Lybia tessellata', 32.0, 36.0, 6.0, NULL, NULL, 8.0, 12.0, 'marine', 'intermediate'),
+        ('Procambarus virginalis', 0.0, 0.5, 5.0, 8.0, 20.0, 4.0, 12.0, 'fresh', 'beginner'),
+        ('Alpheus bellulus', 32.0, 36.0, 6.0, NULL, NULL, 8.0, 12.0, 'marine', 'intermediate'),
+        ('Palaemon elegans', 15.0, 38.0, 6.0, NULL, NULL, 7.0, 12.0, 'brackish', 'beginner'),
+        ('Palaemonetes pugio', 0.5, 35.0, 5.0, NULL, NULL, 3.0, 12.0, 'brackish', 'beginner')
+) AS seed(scientific_name, min_salinity, max_salinity, min_oxygen, min_gh, max_gh, min_kh, max_kh, water_type, care_level)
+WHERE species.scientific_name = seed.scientific_name;
+
+-- Anything else gets permissive freshwater defaults before the columns become required
+UPDATE species SET min_salinity = 0.0 WHERE min_salinity IS NULL;
+UPDATE species SET max_salinity = 0.5 WHERE max_salinity IS NULL;
+UPDATE species SET min_oxygen = 5.0 WHERE min_oxygen IS NULL;
+UPDATE species SET water_type = 'fresh' WHERE water_type IS NULL;
+UPDATE species SET care_level = 'intermediate' WHERE care_level IS NULL;
+
+ALTER TABLE species ALTER COLUMN min_salinity SET NOT NULL;
+ALTER TABLE species ALTER COLUMN max_salinity SET NOT NULL;
+ALTER TABLE species ALTER COLUMN min_oxygen SET NOT NULL;
+ALTER TABLE species ALTER COLUMN water_type SET NOT NULL;
+ALTER TABLE species ALTER COLUMN care_level SET NOT NULL;
+
+ALTER TABLE species ADD CONSTRAINT species_water_type_check CHECK (water_type IN ('fresh', 'brackish', 'marine'));
+ALTER TABLE species ADD CONSTRAINT species_care_level_check CHECK (care_level IN ('beginner', 'intermediate', 'advanced'));
@@ -0,0 +1,57 @@
+-- Typical molt intervals for adult animals of each species
+-- A molt is expected typical_interval_days after the last one, give or take window_days
+CREATE TABLE IF NOT EXISTS species_molt_intervals (
+    species_id INTEGER PRIMARY KEY REFERENCES species(id) ON DELETE CASCADE,
+    typical_interval_days INTEGER NOT NULL CHECK (typical_interval_days > 0),
+    window_days INTEGER NOT NULL DEFAULT 7 CHECK (window_days >= 0),
+    notes TEXT
+);
+
+-- Molts observed in a tank population
+CREATE TABLE IF NOT EXISTS molt_events (
+    id SERIAL PRIMARY KEY,
+    inhabitant_id INTEGER NOT NULL REFERENCES tank_inhabitants(id) ON DELETE CASCADE,
+    observed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    outcome VARCHAR(20) NOT NULL CHECK (outcome IN ('complete', 'failed')),
+    notes TEXT,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_molt_events_inhabitant_id ON molt_events(inhabitant_id, observed_at DESC);
+
+-- Insert sample data
+INSERT INTO species_molt_intervals (species_id, typical_interval_days, window_days, notes)
+SELECT species.id, seed.typical_interval_days, seed.window_days, seed.notes
+FROM (
+    VALUES
+        ('Odontodactylus scyllarus', 90, 21, 'Adults molt every few months and hide for several days afterwards.'),
+        ('Homarus gammarus', 365, 60, 'Adult lobsters molt roughly once a year.'),
+        ('Geosesarma dennerle', 45, 10, 'Molts on land; provide hiding places above the waterline.'),
+        ('Caridina multidentata', 35, 7, NULL),
+        ('Neocaridina davidi', 30, 7, 'Juveniles molt weekly; adults roughly monthly.'),
+        ('Cambarellus patzcuarensis', 30, 7, NULL),
+        ('Limnopilos naiyanetri', 28, 7, NULL),
+        ('Palaemonetes paludosus', 30, 7, NULL),
+        ('Atyopsis moluccensis', 35, 7, NULL),
+        ('Lybia tessellata', 45, 10, 'Anemones are dropped during the molt and picked up again afterwards.'),
+        ('Procambarus virginalis', 30, 7, 'Freshly molted individuals are at risk of cannibalism.'),
+        ('Alpheus bellulus', 40, 10, NULL),
+        ('Palaemon elegans', 21, 5, 'Molts more often in warmer water.'),
+        ('Palaemonetes pugio', 14, 4, NULL)
+) AS seed(scientific_name, typical_interval_days, window_days, notes)
+JOIN species ON species.scientific_name = seed.scientific_name
+ON CONFLICT (species_id) DO NOTHING;
+
+INSERT INTO molt_events (inhabitant_id, observed_at, outcome, notes)
+SELECT i.id, NOW() - seed.days_ago * INTERVAL '1 day', seed.outcome, seed.notes
+FROM (
+    VALUES
+        ('Tank-A1', 'Lybia tessellata', 20, 'complete', NULL),
+        ('Tank-B2', 'Palaemon elegans', 18, 'complete', NULL),
+        ('Tank-B2', 'Palaemonetes pugio', 12, 'complete', 'Moult found near the rockwork.'),
+        ('Tank-C3', 'Palaemonetes pugio', 13, 'complete', NULL),
+        ('Tank-C3', 'Palaemonetes pugio', 3, 'failed', 'One shrimp stuck in its old shell; check KH and salinity.')
+) AS seed(tank_id, scientific_name, days_ago, outcome, notes)
+JOIN species s ON s.scientific_name = seed.scientific_name
+JOIN tank_inhabitants i ON i.tank_id = seed.tank_id AND i.species_id = s.id AND i.removed_at IS NULL
+WHERE NOT EXISTS (SELECT 1 FROM molt_events);
@@ -0,0 +1,55 @@
+-- Breeding records: each brood belongs to the parent population it came from
+CREATE TABLE IF NOT EXISTS broods (
+    id SERIAL PRIMARY KEY,
+    inhabitant_id INTEGER NOT NULL REFERENCES tank_inhabitants(id) ON DELETE CASCADE,
+    -- Size of the parent group when the brood was recorded
+    parent_count INTEGER NOT NULL CHECK (parent_count > 0),
+    brood_date TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    initial_count INTEGER NOT NULL CHECK (initial_count > 0),
+    notes TEXT,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_broods_inhabitant_id ON broods(inhabitant_id, brood_date);
+
+-- Survivor counts at fixed checkpoints (days after the brood date)
+CREATE TABLE IF NOT EXISTS brood_survival_checks (
+    id SERIAL PRIMARY KEY,
+    brood_id INTEGER NOT NULL REFERENCES broods(id) ON DELETE CASCADE,
+    day INTEGER NOT NULL CHECK (day IN (7, 30, 60, 90)),
+    survivors INTEGER NOT NULL CHECK (survivors >= 0),
+    checked_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (brood_id, day)
+);
+
+-- Insert sample data
+INSERT INTO broods (inhabitant_id, parent_count, brood_date, initial_count, notes)
+SELECT i.id, seed.parent_count, NOW() - seed.days_ago * INTERVAL '1 day', seed.initial_count, seed.notes
+FROM (
+    VALUES
+        ('Tank-C3', 'Palaemonetes pugio', 3, 95, 32, 'First berried females since stocking.'),
+        ('Tank-C3', 'Palaemonetes pugio', 4, 50, 28, NULL),
+        ('Tank-B2', 'Palaemon elegans', 1, 21, 40, 'Female carried eggs for about three weeks.')
+) AS seed(tank_id, scientific_name, parent_count, days_ago, initial_count, notes)
+JOIN species s ON s.scientific_name = seed.scientific_name
+JOIN tank_inhabitants i ON i.tank_id = seed.tank_id AND i.species_id = s.id AND i.removed_at IS NULL
+WHERE NOT EXISTS (SELECT 1 FROM broods);
+
+-- Checks are matched to their brood by tank, species and brood date; NOW() is fixed for the
+-- whole migration transaction, so the dates line up with the broods inserted above
+INSERT INTO brood_survival_checks (brood_id, day, survivors, checked_at)
+SELECT b.id, seed.day, seed.survivors, b.brood_date + seed.day * INTERVAL '1 day'
+FROM (
+    VALUES
+        ('Tank-C3', 'Palaemonetes pugio', 95, 7, 29),
+        ('Tank-C3', 'Palaemonetes pugio', 95, 30, 24),
+        ('Tank-C3', 'Palaemonetes pugio', 95, 60, 21),
+        ('Tank-C3', 'Palaemonetes pugio', 95, 90, 20),
+        ('Tank-C3', 'Palaemonetes pugio', 50, 7, 25),
+        ('Tank-C3', 'Palaemonetes pugio', 50, 30, 19),
+        ('Tank-B2', 'Palaemon elegans', 21, 7, 33)
+) AS seed(tank_id, scientific_name, days_ago, day, survivors)
+JOIN species s ON s.scientific_name = seed.scientific_name
+JOIN tank_inhabitants i ON i.tank_id = seed.tank_id AND i.species_id = s.id
+JOIN broods b ON b.inhabitant_id = i.id AND b.brood_date = NOW() - seed.days_ago * INTERVAL '1 day'
+ON CONFLICT (brood_id, day) DO NOTHING;
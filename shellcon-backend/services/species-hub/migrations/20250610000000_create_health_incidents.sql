@@ -0,0 +1,63 @@
+-- Medications and whether they are known to harm invertebrates
+CREATE TABLE IF NOT EXISTS medications (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(100) NOT NULL UNIQUE,
+    active_ingredient VARCHAR(100),
+    toxic_to_invertebrates BOOLEAN NOT NULL DEFAULT FALSE,
+    toxicity_notes TEXT
+);
+
+-- Health incidents affecting a species in a tank; open until an outcome is recorded
+CREATE TABLE IF NOT EXISTS health_incidents (
+    id SERIAL PRIMARY KEY,
+    tank_id VARCHAR(50) NOT NULL REFERENCES tanks(id) ON DELETE CASCADE,
+    species_id INTEGER NOT NULL REFERENCES species(id) ON DELETE CASCADE,
+    status VARCHAR(20) NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'closed')),
+    symptoms TEXT NOT NULL,
+    diagnosis TEXT,
+    affected_count INTEGER CHECK (affected_count > 0),
+    outcome VARCHAR(30) CHECK (outcome IN ('recovered', 'partially_recovered', 'died', 'euthanized')),
+    notes TEXT,
+    opened_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    closed_at TIMESTAMPTZ,
+    CHECK ((status = 'open' AND outcome IS NULL AND closed_at IS NULL)
+        OR (status = 'closed' AND outcome IS NOT NULL AND closed_at IS NOT NULL))
+);
+
+CREATE INDEX IF NOT EXISTS idx_health_incidents_tank_id ON health_incidents(tank_id, status);
+
+-- Treatments given for an incident
+CREATE TABLE IF NOT EXISTS incident_treatments (
+    id SERIAL PRIMARY KEY,
+    incident_id INTEGER NOT NULL REFERENCES health_incidents(id) ON DELETE CASCADE,
+    medication_id INTEGER NOT NULL REFERENCES medications(id),
+    dosage_amount FLOAT NOT NULL CHECK (dosage_amount > 0),
+    dosage_unit VARCHAR(20) NOT NULL,
+    administered_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    notes TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_incident_treatments_incident_id ON incident_treatments(incident_id);
+
+-- Insert sample data
+INSERT INTO medications (name, active_ingredient, toxic_to_invertebrates, toxicity_notes)
+VALUES
+    ('copper sulfate', 'copper', TRUE, 'Copper is lethal to shrimp, crabs and crayfish even at therapeutic doses.'),
+    ('malachite green', 'malachite green', TRUE, 'Toxic to invertebrates; often combined with formalin.'),
+    ('formalin', 'formaldehyde', TRUE, 'Toxic to invertebrates and strips oxygen from the water.'),
+    ('methylene blue', 'methylene blue', TRUE, 'Harms invertebrates and the biological filter; use only in a separate hospital tank.'),
+    ('fenbendazole', 'fenbendazole', TRUE, 'Kills snails and is frequently fatal to shrimp.'),
+    ('potassium permanganate', 'potassium permanganate', TRUE, 'Strong oxidiser; lethal to invertebrates in the display tank.'),
+    ('praziquantel', 'praziquantel', FALSE, 'Generally tolerated by shrimp and crabs at standard doses.'),
+    ('indian almond leaf', 'tannins', FALSE, 'Mild antibacterial; safe for invertebrates.'),
+    ('aquarium salt', 'sodium chloride', FALSE, 'Freshwater invertebrates tolerate short, low-dose baths only.')
+ON CONFLICT (name) DO NOTHING;
+
+INSERT INTO health_incidents (tank_id, species_id, symptoms, diagnosis, affected_count, opened_at)
+SELECT seed.tank_id, s.id, seed.symptoms, seed.diagnosis, seed.affected_count, NOW() - seed.days_ago * INTERVAL '1 day'
+FROM (
+    VALUES
+        ('Tank-C3', 'Palaemonetes pugio', 'White ring around the carapace, lethargy', 'Failed molt risk from low KH', 3, 2)
+) AS seed(tank_id, scientific_name, symptoms, diagnosis, affected_count, days_ago)
+JOIN species s ON s.scientific_name = seed.scientific_name
+WHERE NOT EXISTS (SELECT 1 FROM health_incidents);
@@ -0,0 +1,2 @@
+-- Scientific names identify species across catalog imports and exports, regardless of case
+CREATE UNIQUE INDEX IF NOT EXISTS idx_species_scientific_name_unique ON species (LOWER(scientific_name));
@@ -0,0 +1,75 @@
+-- Taxonomy hierarchy: order > family > genus > species
+CREATE TABLE IF NOT EXISTS taxonomy_orders (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(100) NOT NULL UNIQUE,
+    common_name VARCHAR(100)
+);
+
+CREATE TABLE IF NOT EXISTS taxonomy_families (
+    id SERIAL PRIMARY KEY,
+    order_id INTEGER NOT NULL REFERENCES taxonomy_orders(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL UNIQUE,
+    common_name VARCHAR(100)
+);
+
+CREATE TABLE IF NOT EXISTS taxonomy_genera (
+    id SERIAL PRIMARY KEY,
+    family_id INTEGER NOT NULL REFERENCES taxonomy_families(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL UNIQUE
+);
+
+CREATE INDEX IF NOT EXISTS idx_taxonomy_families_order_id ON taxonomy_families(order_id);
+CREATE INDEX IF NOT EXISTS idx_taxonomy_genera_family_id ON taxonomy_genera(family_id);
+
+ALTER TABLE species ADD COLUMN IF NOT EXISTS genus_id INTEGER REFERENCES taxonomy_genera(id) ON DELETE SET NULL;
+CREATE INDEX IF NOT EXISTS idx_species_genus_id ON species(genus_id);
+
+-- Insert sample data
+INSERT INTO taxonomy_orders (name, common_name)
+VALUES
+    ('Decapoda', 'Decapods'),
+    ('Stomatopoda', 'Mantis shrimps')
+ON CONFLICT (name) DO NOTHING;
+
+INSERT INTO taxonomy_families (order_id, name, common_name)
+SELECT o.id, seed.name, seed.common_name
+FROM (
+    VALUES
+        ('Stomatopoda', 'Odontodactylidae', NULL),
+        ('Decapoda', 'Nephropidae', 'Clawed lobsters'),
+        ('Decapoda', 'Sesarmidae', NULL),
+        ('Decapoda', 'Atyidae', 'Atyid shrimps'),
+        ('Decapoda', 'Cambaridae', 'Crayfish'),
+        ('Decapoda', 'Hymenosomatidae', 'Spider crabs'),
+        ('Decapoda', 'Palaemonidae', 'Palaemonid shrimps'),
+        ('Decapoda', 'Xanthidae', 'Mud crabs'),
+        ('Decapoda', 'Alpheidae', 'Snapping shrimps')
+) AS seed(order_name, name, common_name)
+JOIN taxonomy_orders o ON o.name = seed.order_name
+ON CONFLICT (name) DO NOTHING;
+
+INSERT INTO taxonomy_genera (family_id, name)
+SELECT f.id, seed.name
+FROM (
+    VALUES
+        ('Odontodactylidae', 'Odontodactylus'),
+        ('Nephropidae', 'Homarus'),
+        ('Sesarmidae', 'Geosesarma'),
+        ('Atyidae', 'Caridina'),
+        ('Atyidae', 'Neocaridina'),
+        ('Atyidae', 'Atyopsis'),
+        ('Cambaridae', 'Cambarellus'),
+        ('Cambaridae', 'Procambarus'),
+        ('Hymenosomatidae', 'Limnopilos'),
+        ('Palaemonidae', 'Palaemon'),
+        ('Palaemonidae', 'Palaemonetes'),
+        ('Xanthidae', 'Lybia'),
+        ('Alpheidae', 'Alpheus')
+) AS seed(family_name, name)
+JOIN taxonomy_families f ON f.name = seed.family_name
+ON CONFLICT (name) DO NOTHING;
+
+-- The genus is the first word of the scientific name
+UPDATE species SET genus_id = g.id
+FROM taxonomy_genera g
+WHERE species.genus_id IS NULL AND g.name = split_part(species.scientific_name, ' ', 1);
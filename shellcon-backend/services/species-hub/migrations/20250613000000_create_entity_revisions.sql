@@ -0,0 +1,25 @@
+-- Versioned history of species and tanks
+-- Each change stores a full snapshot of the row, written in the same transaction as the change
+CREATE TABLE IF NOT EXISTS entity_revisions (
+    id SERIAL PRIMARY KEY,
+    entity_type VARCHAR(20) NOT NULL CHECK (entity_type IN ('species', 'tank')),
+    entity_id VARCHAR(50) NOT NULL,
+    revision INTEGER NOT NULL CHECK (revision > 0),
+    operation VARCHAR(20) NOT NULL CHECK (operation IN ('create', 'update', 'rollback')),
+    snapshot JSONB NOT NULL,
+    changed_by VARCHAR(100) NOT NULL,
+    reason TEXT,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (entity_type, entity_id, revision)
+);
+
+-- Existing rows start with revision 1
+INSERT INTO entity_revisions (entity_type, entity_id, revision, operation, snapshot, changed_by, reason)
+SELECT 'species', s.id::TEXT, 1, 'create', to_jsonb(s), 'migration', 'Initial catalog'
+FROM species s
+ON CONFLICT (entity_type, entity_id, revision) DO NOTHING;
+
+INSERT INTO entity_revisions (entity_type, entity_id, revision, operation, snapshot, changed_by, reason)
+SELECT 'tank', t.id, 1, 'create', to_jsonb(t), 'migration', 'Initial tank registry'
+FROM tanks t
+ON CONFLICT (entity_type, entity_id, revision) DO NOTHING;
@@ -0,0 +1,21 @@
+-- Version counter for the species catalog, used for ETag/Last-Modified and response caching
+-- A single row, bumped by a statement trigger on every write to species
+CREATE TABLE IF NOT EXISTS catalog_version (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    version BIGINT NOT NULL DEFAULT 1,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO catalog_version (id) VALUES (TRUE) ON CONFLICT (id) DO NOTHING;
+
+CREATE OR REPLACE FUNCTION bump_catalog_version() RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE catalog_version SET version = version + 1, updated_at = NOW();
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS species_catalog_version ON species;
+CREATE TRIGGER species_catalog_version
+    AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON species
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_catalog_version();
@@ -0,0 +1,32 @@
+-- Adult size and waste output of each species, used to estimate tank bioload
+-- A population's load is count * adult_size_cm * bioload_factor; a factor of 1.0 is a typical fish
+-- of the same length, so most invertebrates are well below 1.0
+CREATE TABLE IF NOT EXISTS species_bioload_profiles (
+    species_id INTEGER PRIMARY KEY REFERENCES species(id) ON DELETE CASCADE,
+    adult_size_cm FLOAT NOT NULL CHECK (adult_size_cm > 0),
+    bioload_factor FLOAT NOT NULL CHECK (bioload_factor > 0),
+    notes TEXT
+);
+
+-- Insert sample data
+INSERT INTO species_bioload_profiles (species_id, adult_size_cm, bioload_factor, notes)
+SELECT species.id, seed.adult_size_cm, seed.bioload_factor, seed.notes
+FROM (
+    VALUES
+        ('Odontodactylus scyllarus', 15.0, 1.0, 'Messy carnivore; leftover shell and flesh add to the load.'),
+        ('Homarus gammarus', 50.0, 1.0, 'Needs a very large system once grown.'),
+        ('Geosesarma dennerle', 2.5, 0.3, 'Carapace width; spends much of its time on land.'),
+        ('Caridina multidentata', 5.0, 0.2, NULL),
+        ('Neocaridina davidi', 3.0, 0.1, 'Very light load; colonies can be kept densely.'),
+        ('Cambarellus patzcuarensis', 4.0, 0.5, NULL),
+        ('Limnopilos naiyanetri', 1.0, 0.1, 'Carapace width.'),
+        ('Palaemonetes paludosus', 4.0, 0.15, NULL),
+        ('Atyopsis moluccensis', 8.0, 0.2, 'Filter feeder; relies on suspended particles rather than added food.'),
+        ('Lybia tessellata', 2.5, 0.3, 'Carapace width.'),
+        ('Procambarus virginalis', 10.0, 0.6, 'Clones itself; populations grow quickly.'),
+        ('Alpheus bellulus', 5.0, 0.4, NULL),
+        ('Palaemon elegans', 6.0, 0.2, NULL),
+        ('Palaemonetes pugio', 4.0, 0.15, NULL)
+) AS seed(scientific_name, adult_size_cm, bioload_factor, notes)
+JOIN species ON species.scientific_name = seed.scientific_name
+ON CONFLICT (species_id) DO NOTHING;
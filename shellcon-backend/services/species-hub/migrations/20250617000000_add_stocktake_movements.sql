@@ -0,0 +1,4 @@
+-- Stocktake corrections are recorded separately from manual usage so they don't count as consumption
+ALTER TABLE inventory_movements DROP CONSTRAINT IF EXISTS inventory_movements_movement_type_check;
+ALTER TABLE inventory_movements ADD CONSTRAINT inventory_movements_movement_type_check
+    CHECK (movement_type IN ('shipment', 'feeding', 'adjustment', 'stocktake'));
@@ -0,0 +1,6 @@
+-- Whether an order's species are invertebrates, so medication warnings only list the
+-- populations a treatment can harm. Every order seeded so far is a crustacean order;
+-- new orders must say which they are
+ALTER TABLE taxonomy_orders ADD COLUMN IF NOT EXISTS invertebrate BOOLEAN;
+UPDATE taxonomy_orders SET invertebrate = TRUE WHERE invertebrate IS NULL;
+ALTER TABLE taxonomy_orders ALTER COLUMN invertebrate SET NOT NULL;
@@ -0,0 +1,224 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::ApiError;
+
+/// Default aqua-monitor address when running all services locally (see README ports).
+pub const DEFAULT_AQUA_MONITOR_URL: &str = "http://localhost:8000";
+
+/// Setting `AQUA_MONITOR_URL` to this value swaps the HTTP client for canned demo readings.
+pub const STUB_AQUA_MONITOR_URL: &str = "stub";
+
+/// A single tank reading as returned by aqua-monitor's readings endpoint.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TankReading {
+    pub temperature: f64,
+    pub ph: f64,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+struct TankReadingsResponse {
+    readings: Vec<TankReading>,
+}
+
+/// A tank in aqua-monitor's registry; tanks only seen in readings have no name or volume.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MonitoredTank {
+    pub id: String,
+    pub name: Option<String>,
+    pub volume_liters: Option<f64>,
+    pub cleaning_interval_days: Option<i32>,
+    pub configured: bool,
+}
+
+/// Appends path segments to the base URL, percent-encoding each one so a tank ID
+/// containing `/`, `?` or `#` can't reach a different aqua-monitor route.
+fn endpoint(base_url: &reqwest::Url, segments: &[&str]) -> reqwest::Url {
+    let mut url = base_url.clone();
+    // Always succeeds: `http` rejects base URLs that can't have a path
+    if let Ok(mut path) = url.path_segments_mut() {
+        path.pop_if_empty().extend(segments);
+    }
+    url
+}
+
+struct StubData {
+    readings: HashMap<String, Vec<TankReading>>,
+    registry: Vec<MonitoredTank>,
+}
+
+#[derive(Clone)]
+enum Backend {
+    Http {
+        client: reqwest::Client,
+        base_url: reqwest::Url,
+    },
+    Stub(Arc<StubData>),
+}
+
+/// Client for the aqua-monitor service.
+///
+/// The HTTP client is created once and shared through `AppState`. The stub
+/// backend serves the same demo readings that aqua-monitor seeds its database
+/// with, so species-hub can be exercised without the other service running.
+#[derive(Clone)]
+pub struct AquaMonitorClient {
+    backend: Backend,
+}
+
+impl AquaMonitorClient {
+    pub fn http(base_url: &str) -> Result<Self, String> {
+        let base_url = reqwest::Url::parse(base_url)
+            .ok()
+            .filter(|url| !url.cannot_be_a_base())
+            .ok_or_else(|| format!("invalid aqua-monitor URL: {:?}", base_url))?;
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+            .map_err(|e| format!("failed to build aqua-monitor HTTP client: {}", e))?;
+
+        Ok(Self {
+            backend: Backend::Http {
+                client,
+                base_url,
+            },
+        })
+    }
+
+    pub fn stub() -> Self {
+        let now = Utc::now();
+        let series = |values: &[(f64, f64)]| -> Vec<TankReading> {
+            // Newest first, matching aqua-monitor's ORDER BY timestamp DESC
+            values
+                .iter()
+                .enumerate()
+                .map(|(i, (temperature, ph))| TankReading {
+                    temperature: *temperature,
+                    ph: *ph,
+                    timestamp: now - chrono::Duration::minutes(15 * i as i64),
+                })
+                .collect()
+        };
+
+        let mut readings = HashMap::new();
+        readings.insert(
+            "Tank-A1".to_string(),
+            series(&[(25.8, 7.4), (25.6, 7.5), (25.5, 7.6), (25.3, 7.7), (25.2, 7.8)]),
+        );
+        readings.insert(
+            "Tank-B2".to_string(),
+            series(&[(22.9, 7.7), (22.8, 7.8), (22.7, 7.9), (22.6, 8.0), (22.5, 8.1)]),
+        );
+        readings.insert(
+            "Tank-C3".to_string(),
+            series(&[(18.5, 6.5), (18.4, 6.6), (18.3, 6.7), (18.2, 6.8), (18.1, 6.9)]),
+        );
+
+        // Mirrors aqua-monitor's tank_settings.json plus the tanks its readings are seeded for
+        let configured = |id: &str, name: &str, volume_liters: f64, cleaning_interval_days: i32| MonitoredTank {
+            id: id.to_string(),
+            name: Some(name.to_string()),
+            volume_liters: Some(volume_liters),
+            cleaning_interval_days: Some(cleaning_interval_days),
+            configured: true,
+        };
+        let mut registry = vec![
+            configured("tank1", "Main Tank", 500.0, 14),
+            configured("tank2", "Quarantine Tank", 200.0, 7),
+        ];
+        let mut reading_tanks: Vec<&String> = readings.keys().collect();
+        reading_tanks.sort();
+        registry.extend(reading_tanks.into_iter().map(|id| MonitoredTank {
+            id: id.clone(),
+            name: None,
+            volume_liters: None,
+            cleaning_interval_days: None,
+            configured: false,
+        }));
+
+        Self {
+            backend: Backend::Stub(Arc::new(StubData { readings, registry })),
+        }
+    }
+
+    /// Builds a client from the configured `AQUA_MONITOR_URL`, falling back to the local default.
+    pub fn from_config(url: Option<String>) -> Result<Self, String> {
+        match url.as_deref() {
+            Some(STUB_AQUA_MONITOR_URL) => Ok(Self::stub()),
+            Some(url) => Self::http(url),
+            None => Self::http(DEFAULT_AQUA_MONITOR_URL),
+        }
+    }
+
+    pub fn is_stub(&self) -> bool {
+        matches!(self.backend, Backend::Stub(_))
+    }
+
+    /// Fetches the most recent readings for a tank, newest first.
+    pub async fn recent_readings(&self, tank_id: &str) -> Result<Vec<TankReading>, ApiError> {
+        match &self.backend {
+            Backend::Http { client, base_url } => {
+                let response = client
+                    .get(endpoint(base_url, &["api", "tanks", tank_id, "readings"]))
+                    .send()
+                    .await?;
+
+                if response.status() == reqwest::StatusCode::NOT_FOUND {
+                    return Err(ApiError::TankNotFound(format!(
+                        "No readings for tank ID: {}",
+                        tank_id
+                    )));
+                }
+
+                let body: TankReadingsResponse = response.error_for_status()?.json().await?;
+                Ok(body.readings)
+            }
+            Backend::Stub(data) => data.readings.get(tank_id).cloned().ok_or_else(|| {
+                ApiError::TankNotFound(format!("No readings for tank ID: {}", tank_id))
+            }),
+        }
+    }
+
+    /// Fetches aqua-monitor's tank registry.
+    pub async fn tank_registry(&self) -> Result<Vec<MonitoredTank>, ApiError> {
+        match &self.backend {
+            Backend::Http { client, base_url } => Ok(client
+                .get(endpoint(base_url, &["api", "tanks", "registry"]))
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?),
+            Backend::Stub(data) => Ok(data.registry.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tank_ids_are_encoded_as_a_single_path_segment() {
+        let base_url = reqwest::Url::parse("http://localhost:8000/").unwrap();
+        assert_eq!(
+            endpoint(&base_url, &["api", "tanks", "a/b?c#d", "readings"]).as_str(),
+            "http://localhost:8000/api/tanks/a%2Fb%3Fc%23d/readings"
+        );
+
+        let prefixed = reqwest::Url::parse("http://monitor.internal/aqua").unwrap();
+        assert_eq!(
+            endpoint(&prefixed, &["api", "tanks", "registry"]).as_str(),
+            "http://monitor.internal/aqua/api/tanks/registry"
+        );
+    }
+
+    #[test]
+    fn base_urls_without_a_path_are_rejected() {
+        assert!(AquaMonitorClient::http("mailto:aqua@example.com").is_err());
+        assert!(AquaMonitorClient::http("not a url").is_err());
+    }
+}
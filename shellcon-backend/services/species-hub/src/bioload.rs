@@ -0,0 +1,454 @@
+use serde::{Deserialize, Serialize};
+use shuttle_axum::axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Json,
+};
+
+use crate::{
+    feeding::fetch_tank,
+    interactions::{self, CompatibilityReport, CompatibilityVerdict},
+    ApiError, AppState, Species,
+};
+
+/// Bioload units a litre of water can carry: one centimetre of typical fish per litre.
+const CAPACITY_UNITS_PER_LITER: f64 = 1.0;
+
+/// Above this share of capacity a tank is considered near its limit.
+const NEAR_CAPACITY_PERCENT: f64 = 80.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BioloadStatus {
+    Comfortable,
+    NearCapacity,
+    Overstocked,
+}
+
+impl BioloadStatus {
+    fn from_percent(percent: f64) -> Self {
+        if percent > 100.0 {
+            BioloadStatus::Overstocked
+        } else if percent > NEAR_CAPACITY_PERCENT {
+            BioloadStatus::NearCapacity
+        } else {
+            BioloadStatus::Comfortable
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BioloadStatus::Comfortable => "comfortable",
+            BioloadStatus::NearCapacity => "near capacity",
+            BioloadStatus::Overstocked => "overstocked",
+        }
+    }
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+pub struct BioloadProfile {
+    pub species_id: i32,
+    pub species_name: String,
+    pub adult_size_cm: f64,
+    pub bioload_factor: f64,
+    pub notes: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct BioloadProfilePayload {
+    species_id: i32,
+    adult_size_cm: f64,
+    bioload_factor: f64,
+    notes: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct BioloadQuery {
+    /// Species of a hypothetical addition, e.g. `?add_species_id=4&add_count=10`
+    add_species_id: Option<i32>,
+    add_count: Option<i32>,
+}
+
+/// A population's share of the tank's bioload.
+#[derive(Serialize)]
+pub struct BioloadContribution {
+    pub species_id: i32,
+    pub species_name: String,
+    pub count: i32,
+    pub adult_size_cm: f64,
+    pub bioload_factor: f64,
+    pub load_units: f64,
+}
+
+#[derive(Serialize)]
+pub struct BioloadLevel {
+    pub load_units: f64,
+    pub percent_of_capacity: f64,
+    pub status: BioloadStatus,
+}
+
+#[derive(Serialize)]
+pub struct HypotheticalAddition {
+    pub species_id: i32,
+    pub species_name: String,
+    pub count: i32,
+    pub added_load_units: f64,
+    pub projected: BioloadLevel,
+    /// Only the reasons involving the added species; existing conflicts don't block it
+    pub compatibility: CompatibilityReport,
+    /// False whenever an inhabitant has no bioload profile, since the real load is unknown
+    pub can_add: bool,
+    pub summary: String,
+}
+
+#[derive(Serialize)]
+pub struct BioloadReport {
+    pub tank_id: String,
+    pub volume_liters: f64,
+    pub capacity_units: f64,
+    pub current: BioloadLevel,
+    pub contributions: Vec<BioloadContribution>,
+    /// Inhabitants without a bioload profile, left out of the totals
+    pub unprofiled_species: Vec<String>,
+    pub hypothetical: Option<HypotheticalAddition>,
+}
+
+#[derive(sqlx::FromRow)]
+struct InhabitantLoadRow {
+    species_id: i32,
+    species_name: String,
+    count: i32,
+    adult_size_cm: Option<f64>,
+    bioload_factor: Option<f64>,
+}
+
+const PROFILE_SELECT: &str = "SELECT p.species_id, s.name AS species_name, p.adult_size_cm, p.bioload_factor, p.notes \
+     FROM species_bioload_profiles p JOIN species s ON s.id = p.species_id";
+
+fn load_units(count: i32, adult_size_cm: f64, bioload_factor: f64) -> f64 {
+    count as f64 * adult_size_cm * bioload_factor
+}
+
+/// An addition fits only if the whole tank's load is known, it stays within capacity
+/// and the species isn't incompatible with the current inhabitants.
+fn addition_allowed(
+    projected: &BioloadLevel,
+    verdict: CompatibilityVerdict,
+    unprofiled_species: &[String],
+) -> bool {
+    unprofiled_species.is_empty()
+        && projected.status != BioloadStatus::Overstocked
+        && verdict != CompatibilityVerdict::Incompatible
+}
+
+fn level(load_units: f64, capacity_units: f64) -> BioloadLevel {
+    let percent_of_capacity = load_units / capacity_units * 100.0;
+    BioloadLevel {
+        load_units,
+        percent_of_capacity,
+        status: BioloadStatus::from_percent(percent_of_capacity),
+    }
+}
+
+/// What a tank already carries, for checking a proposed addition against.
+struct TankLoad<'a> {
+    tank_name: &'a str,
+    resident_ids: &'a [i32],
+    unprofiled_species: &'a [String],
+    load_units: f64,
+    capacity_units: f64,
+}
+
+/// Checks a proposed addition against both the remaining capacity and the current inhabitants.
+async fn assess_addition(
+    state: &AppState,
+    tank: &TankLoad<'_>,
+    species_id: i32,
+    count: i32,
+) -> Result<HypotheticalAddition, ApiError> {
+    let unprofiled_species = tank.unprofiled_species;
+    if count <= 0 {
+        return Err(ApiError::InvalidInput("add_count must be positive".to_string()));
+    }
+
+    let profile = sqlx::query_as::<_, BioloadProfile>(&format!("{} WHERE p.species_id = $1", PROFILE_SELECT))
+        .bind(species_id)
+        .fetch_optional(&state.pool)
+        .await?;
+    let profile = match profile {
+        Some(profile) => profile,
+        None => {
+            let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM species WHERE id = $1)")
+                .bind(species_id)
+                .fetch_one(&state.pool)
+                .await?;
+            return Err(if exists {
+                ApiError::InvalidInput(format!("Species with ID {} has no bioload profile", species_id))
+            } else {
+                ApiError::SpeciesNotFound(format!("Species with ID {} not found", species_id))
+            });
+        }
+    };
+
+    let mut group_ids = tank.resident_ids.to_vec();
+    if !group_ids.contains(&species_id) {
+        group_ids.push(species_id);
+    }
+    let group = sqlx::query_as::<_, Species>("SELECT * FROM species WHERE id = ANY($1) ORDER BY id")
+        .bind(&group_ids)
+        .fetch_all(&state.pool)
+        .await?;
+    let interactions = interactions::fetch_interactions_among(&state.pool, &group_ids).await?;
+
+    let mut compatibility = interactions::assess_compatibility(&group, &interactions);
+    compatibility.reasons.retain(|r| r.species_ids.contains(&species_id));
+    compatibility.verdict = compatibility
+        .reasons
+        .iter()
+        .map(|r| r.verdict)
+        .max()
+        .unwrap_or(CompatibilityVerdict::Compatible);
+
+    let added_load_units = load_units(count, profile.adult_size_cm, profile.bioload_factor);
+    let projected = level(tank.load_units + added_load_units, tank.capacity_units);
+    let can_add = addition_allowed(&projected, compatibility.verdict, unprofiled_species);
+
+    let compatibility_note = match compatibility.verdict {
+        CompatibilityVerdict::Compatible => "compatible with the current inhabitants",
+        CompatibilityVerdict::Caution => "compatible with caution; see the compatibility reasons",
+        CompatibilityVerdict::Incompatible => "incompatible with the current inhabitants",
+    };
+    let mut summary = format!(
+        "{}: adding {} {} to {} brings bioload to {:.1}% of capacity ({}); {}",
+        if can_add { "Yes" } else { "No" },
+        count,
+        profile.species_name,
+        tank.tank_name,
+        projected.percent_of_capacity,
+        projected.status.as_str(),
+        compatibility_note,
+    );
+    if !unprofiled_species.is_empty() {
+        summary = format!(
+            "{}. The real load is higher than shown: {} {} no bioload profile",
+            summary,
+            unprofiled_species.join(", "),
+            if unprofiled_species.len() == 1 { "has" } else { "have" },
+        );
+    }
+
+    Ok(HypotheticalAddition {
+        species_id,
+        species_name: profile.species_name,
+        count,
+        added_load_units,
+        projected,
+        compatibility,
+        can_add,
+        summary,
+    })
+}
+
+/// Computes a tank's bioload from its inhabitants as a percentage of what its volume can carry.
+///
+/// With `add_species_id` and `add_count` it also answers whether that addition fits:
+/// the projected load must stay within capacity and the species must not be
+/// incompatible with anything already in the tank. While any inhabitant lacks a
+/// bioload profile the answer is no, since the tank's real load is unknown.
+pub async fn get_tank_bioload(
+    Path(tank_id): Path<String>,
+    Query(query): Query<BioloadQuery>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let tank = fetch_tank(&state.pool, &tank_id).await?;
+    if tank.volume <= 0.0 {
+        return Err(ApiError::InvalidInput(format!("Tank {} has no volume recorded", tank_id)));
+    }
+    let capacity_units = tank.volume * CAPACITY_UNITS_PER_LITER;
+
+    let rows = sqlx::query_as::<_, InhabitantLoadRow>(
+        "SELECT i.species_id, s.name AS species_name, i.count, p.adult_size_cm, p.bioload_factor \
+         FROM tank_inhabitants i \
+         JOIN species s ON s.id = i.species_id \
+         LEFT JOIN species_bioload_profiles p ON p.species_id = i.species_id \
+         WHERE i.tank_id = $1 AND i.removed_at IS NULL \
+         ORDER BY s.name",
+    )
+    .bind(&tank_id)
+    .fetch_all(&state.pool)
+    .await?;
+
+    let resident_ids: Vec<i32> = rows.iter().map(|r| r.species_id).collect();
+    let mut contributions = Vec::new();
+    let mut unprofiled_species = Vec::new();
+    for row in rows {
+        match (row.adult_size_cm, row.bioload_factor) {
+            (Some(adult_size_cm), Some(bioload_factor)) => contributions.push(BioloadContribution {
+                species_id: row.species_id,
+                species_name: row.species_name,
+                count: row.count,
+                adult_size_cm,
+                bioload_factor,
+                load_units: load_units(row.count, adult_size_cm, bioload_factor),
+            }),
+            _ => unprofiled_species.push(row.species_name),
+        }
+    }
+    let current_load: f64 = contributions.iter().map(|c| c.load_units).sum();
+
+    let hypothetical = match (query.add_species_id, query.add_count) {
+        (Some(species_id), count) => Some(
+            assess_addition(
+                &state,
+                &TankLoad {
+                    tank_name: &tank.name,
+                    resident_ids: &resident_ids,
+                    unprofiled_species: &unprofiled_species,
+                    load_units: current_load,
+                    capacity_units,
+                },
+                species_id,
+                count.unwrap_or(1),
+            )
+            .await?,
+        ),
+        (None, Some(_)) => {
+            return Err(ApiError::InvalidInput(
+                "add_count requires add_species_id".to_string(),
+            ))
+        }
+        (None, None) => None,
+    };
+
+    let current = level(current_load, capacity_units);
+    tracing::info!(
+        tank_id = %tank_id,
+        operation = "tank_bioload",
+        percent_of_capacity = current.percent_of_capacity,
+        status = current.status.as_str(),
+        hypothetical = hypothetical.is_some(),
+        can_add = hypothetical.as_ref().map(|h| h.can_add),
+        "Tank bioload calculated"
+    );
+
+    Ok(Json(BioloadReport {
+        tank_id,
+        volume_liters: tank.volume,
+        capacity_units,
+        current,
+        contributions,
+        unprofiled_species,
+        hypothetical,
+    }))
+}
+
+pub async fn list_bioload_profiles(State(state): State<AppState>) -> Result<impl IntoResponse, ApiError> {
+    let profiles = sqlx::query_as::<_, BioloadProfile>(&format!("{} ORDER BY s.name", PROFILE_SELECT))
+        .fetch_all(&state.pool)
+        .await?;
+
+    Ok(Json(profiles))
+}
+
+/// Creates or replaces the adult size and bioload factor for a species.
+pub async fn upsert_bioload_profile(
+    State(state): State<AppState>,
+    Json(payload): Json<BioloadProfilePayload>,
+) -> Result<impl IntoResponse, ApiError> {
+    if payload.adult_size_cm <= 0.0 || payload.bioload_factor <= 0.0 {
+        return Err(ApiError::InvalidInput(
+            "adult_size_cm and bioload_factor must be positive".to_string(),
+        ));
+    }
+
+    let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM species WHERE id = $1)")
+        .bind(payload.species_id)
+        .fetch_one(&state.pool)
+        .await?;
+    if !exists {
+        return Err(ApiError::SpeciesNotFound(format!(
+            "Species with ID {} not found",
+            payload.species_id
+        )));
+    }
+
+    let profile = sqlx::query_as::<_, BioloadProfile>(
+        "WITH saved AS ( \
+             INSERT INTO species_bioload_profiles (species_id, adult_size_cm, bioload_factor, notes) \
+             VALUES ($1, $2, $3, $4) \
+             ON CONFLICT (species_id) DO UPDATE SET \
+                 adult_size_cm = EXCLUDED.adult_size_cm, \
+                 bioload_factor = EXCLUDED.bioload_factor, \
+                 notes = EXCLUDED.notes \
+             RETURNING * \
+         ) \
+         SELECT saved.species_id, s.name AS species_name, saved.adult_size_cm, saved.bioload_factor, saved.notes \
+         FROM saved JOIN species s ON s.id = saved.species_id",
+    )
+    .bind(payload.species_id)
+    .bind(payload.adult_size_cm)
+    .bind(payload.bioload_factor)
+    .bind(&payload.notes)
+    .fetch_one(&state.pool)
+    .await?;
+
+    tracing::info!(
+        species_id = payload.species_id,
+        adult_size_cm = payload.adult_size_cm,
+        bioload_factor = payload.bioload_factor,
+        operation = "upsert_bioload_profile",
+        "Bioload profile saved"
+    );
+
+    Ok(Json(profile))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_scales_with_count_size_and_factor() {
+        assert_eq!(load_units(10, 3.0, 0.5), 15.0);
+        assert_eq!(load_units(2, 15.0, 1.0), 30.0);
+        assert_eq!(load_units(0, 15.0, 1.0), 0.0);
+    }
+
+    #[test]
+    fn status_follows_the_share_of_capacity() {
+        let cases = [
+            (0.0, BioloadStatus::Comfortable),
+            (80.0, BioloadStatus::Comfortable),
+            (80.1, BioloadStatus::NearCapacity),
+            (100.0, BioloadStatus::NearCapacity),
+            (100.1, BioloadStatus::Overstocked),
+        ];
+        for (percent, status) in cases {
+            assert_eq!(BioloadStatus::from_percent(percent), status, "{}%", percent);
+        }
+    }
+
+    #[test]
+    fn level_is_a_percentage_of_capacity() {
+        let half = level(10.0, 20.0);
+        assert_eq!(half.load_units, 10.0);
+        assert_eq!(half.percent_of_capacity, 50.0);
+        assert_eq!(half.status, BioloadStatus::Comfortable);
+
+        assert_eq!(level(18.0, 20.0).status, BioloadStatus::NearCapacity);
+        assert_eq!(level(25.0, 20.0).status, BioloadStatus::Overstocked);
+    }
+
+    #[test]
+    fn additions_need_every_inhabitant_profiled() {
+        let comfortable = level(5.0, 20.0);
+        let overstocked = level(25.0, 20.0);
+        let unprofiled = vec!["Cherry Shrimp".to_string()];
+
+        assert!(addition_allowed(&comfortable, CompatibilityVerdict::Compatible, &[]));
+        assert!(addition_allowed(&comfortable, CompatibilityVerdict::Caution, &[]));
+        assert!(!addition_allowed(&comfortable, CompatibilityVerdict::Incompatible, &[]));
+        assert!(!addition_allowed(&overstocked, CompatibilityVerdict::Compatible, &[]));
+        assert!(!addition_allowed(&comfortable, CompatibilityVerdict::Compatible, &unprofiled));
+    }
+}
@@ -0,0 +1,427 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use shuttle_axum::axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use sqlx::PgConnection;
+
+use crate::{inhabitants::ensure_tank_exists, ApiError, AppState};
+
+/// Days after the brood date at which survivors are counted; matches the CHECK constraint.
+pub const SURVIVAL_CHECKPOINT_DAYS: [i32; 4] = [7, 30, 60, 90];
+
+#[derive(Serialize, sqlx::FromRow)]
+pub struct SurvivalCheck {
+    pub brood_id: i32,
+    pub day: i32,
+    pub survivors: i32,
+    pub checked_at: DateTime<Utc>,
+}
+
+#[derive(sqlx::FromRow)]
+struct BroodRow {
+    id: i32,
+    inhabitant_id: i32,
+    tank_id: String,
+    species_id: i32,
+    species_name: String,
+    parent_count: i32,
+    brood_date: DateTime<Utc>,
+    initial_count: i32,
+    notes: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct Brood {
+    pub id: i32,
+    pub inhabitant_id: i32,
+    pub tank_id: String,
+    pub species_id: i32,
+    pub species_name: String,
+    pub parent_count: i32,
+    pub brood_date: DateTime<Utc>,
+    pub initial_count: i32,
+    /// Survivors at the latest recorded checkpoint, or the initial count if none is recorded
+    pub current_survivors: i32,
+    pub survival: Vec<SurvivalCheck>,
+    pub next_checkpoint_day: Option<i32>,
+    pub next_checkpoint_at: Option<DateTime<Utc>>,
+    pub notes: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct CheckpointSurvival {
+    pub day: i32,
+    pub broods_checked: usize,
+    /// Survivors as a share of the initial count, across the broods checked at this day
+    pub survival_rate: f64,
+}
+
+#[derive(Serialize)]
+pub struct ReproductionStats {
+    pub species_id: i32,
+    pub species_name: String,
+    pub broods: usize,
+    pub parent_groups: usize,
+    pub total_offspring: i64,
+    pub average_brood_size: f64,
+    pub offspring_per_parent: f64,
+    /// Mean gap between consecutive broods of the same parent group
+    pub average_days_between_broods: Option<f64>,
+    pub last_brood_at: Option<DateTime<Utc>>,
+    pub survival: Vec<CheckpointSurvival>,
+}
+
+#[derive(Deserialize)]
+pub struct RecordBroodRequest {
+    brood_date: Option<DateTime<Utc>>,
+    count: i32,
+    /// Defaults to the current size of the parent group
+    parent_count: Option<i32>,
+    notes: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct RecordSurvivalRequest {
+    day: i32,
+    survivors: i32,
+    checked_at: Option<DateTime<Utc>>,
+}
+
+const BROOD_SELECT: &str = "SELECT b.id, b.inhabitant_id, i.tank_id, i.species_id, s.name AS species_name, \
+     b.parent_count, b.brood_date, b.initial_count, b.notes \
+     FROM broods b \
+     JOIN tank_inhabitants i ON i.id = b.inhabitant_id \
+     JOIN species s ON s.id = i.species_id";
+
+/// Attaches survival checks to brood rows and works out the next checkpoint for each.
+fn assemble_broods(rows: Vec<BroodRow>, checks: Vec<SurvivalCheck>) -> Vec<Brood> {
+    let mut checks_by_brood: HashMap<i32, Vec<SurvivalCheck>> = HashMap::new();
+    for check in checks {
+        checks_by_brood.entry(check.brood_id).or_default().push(check);
+    }
+
+    rows.into_iter()
+        .map(|row| {
+            let mut survival = checks_by_brood.remove(&row.id).unwrap_or_default();
+            survival.sort_by_key(|check| check.day);
+
+            let current_survivors = survival.last().map_or(row.initial_count, |check| check.survivors);
+            let next_checkpoint_day = SURVIVAL_CHECKPOINT_DAYS
+                .iter()
+                .copied()
+                .find(|day| !survival.iter().any(|check| check.day == *day));
+
+            Brood {
+                id: row.id,
+                inhabitant_id: row.inhabitant_id,
+                tank_id: row.tank_id,
+                species_id: row.species_id,
+                species_name: row.species_name,
+                parent_count: row.parent_count,
+                brood_date: row.brood_date,
+                initial_count: row.initial_count,
+                current_survivors,
+                survival,
+                next_checkpoint_day,
+                next_checkpoint_at: next_checkpoint_day.map(|day| row.brood_date + Duration::days(day as i64)),
+                notes: row.notes,
+            }
+        })
+        .collect()
+}
+
+/// Which broods to load.
+enum BroodFilter<'a> {
+    All,
+    Brood(i32),
+    Tank(&'a str),
+    Species(i32),
+}
+
+async fn fetch_broods(conn: &mut PgConnection, filter: BroodFilter<'_>) -> Result<Vec<Brood>, sqlx::Error> {
+    let condition = match filter {
+        BroodFilter::All => "",
+        BroodFilter::Brood(_) => "WHERE b.id = $1",
+        BroodFilter::Tank(_) => "WHERE i.tank_id = $1",
+        BroodFilter::Species(_) => "WHERE i.species_id = $1",
+    };
+    let sql = format!("{} {} ORDER BY b.brood_date DESC, b.id DESC", BROOD_SELECT, condition);
+    let query = sqlx::query_as::<_, BroodRow>(&sql);
+    let query = match filter {
+        BroodFilter::All => query,
+        BroodFilter::Brood(id) | BroodFilter::Species(id) => query.bind(id),
+        BroodFilter::Tank(tank_id) => query.bind(tank_id),
+    };
+    let rows = query.fetch_all(&mut *conn).await?;
+
+    let brood_ids: Vec<i32> = rows.iter().map(|row| row.id).collect();
+    let checks = sqlx::query_as::<_, SurvivalCheck>(
+        "SELECT brood_id, day, survivors, checked_at FROM brood_survival_checks WHERE brood_id = ANY($1)",
+    )
+    .bind(&brood_ids)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(assemble_broods(rows, checks))
+}
+
+/// Aggregates brood records into per-species reproduction statistics.
+fn reproduction_stats(broods: &[Brood]) -> Vec<ReproductionStats> {
+    let mut by_species: BTreeMap<i32, Vec<&Brood>> = BTreeMap::new();
+    for brood in broods {
+        by_species.entry(brood.species_id).or_default().push(brood);
+    }
+
+    by_species
+        .into_values()
+        .map(|broods| {
+            let total_offspring: i64 = broods.iter().map(|b| b.initial_count as i64).sum();
+            let total_parents: i64 = broods.iter().map(|b| b.parent_count as i64).sum();
+
+            // Gaps between consecutive broods of each parent group
+            let mut dates_by_group: HashMap<i32, Vec<DateTime<Utc>>> = HashMap::new();
+            for brood in &broods {
+                dates_by_group.entry(brood.inhabitant_id).or_default().push(brood.brood_date);
+            }
+            let parent_groups = dates_by_group.len();
+            let gaps: Vec<f64> = dates_by_group
+                .into_values()
+                .flat_map(|mut dates| {
+                    dates.sort();
+                    dates
+                        .windows(2)
+                        .map(|pair| (pair[1] - pair[0]).num_hours() as f64 / 24.0)
+                        .collect::<Vec<_>>()
+                })
+                .collect();
+
+            let survival = SURVIVAL_CHECKPOINT_DAYS
+                .iter()
+                .filter_map(|&day| {
+                    let checked: Vec<(i32, i32)> = broods
+                        .iter()
+                        .filter_map(|b| {
+                            b.survival
+                                .iter()
+                                .find(|check| check.day == day)
+                                .map(|check| (check.survivors, b.initial_count))
+                        })
+                        .collect();
+                    if checked.is_empty() {
+                        return None;
+                    }
+                    let survivors: i32 = checked.iter().map(|(survivors, _)| survivors).sum();
+                    let initial: i32 = checked.iter().map(|(_, initial)| initial).sum();
+                    Some(CheckpointSurvival {
+                        day,
+                        broods_checked: checked.len(),
+                        survival_rate: survivors as f64 / initial as f64,
+                    })
+                })
+                .collect();
+
+            ReproductionStats {
+                species_id: broods[0].species_id,
+                species_name: broods[0].species_name.clone(),
+                broods: broods.len(),
+                parent_groups,
+                total_offspring,
+                average_brood_size: total_offspring as f64 / broods.len() as f64,
+                offspring_per_parent: total_offspring as f64 / total_parents as f64,
+                average_days_between_broods: if gaps.is_empty() {
+                    None
+                } else {
+                    Some(gaps.iter().sum::<f64>() / gaps.len() as f64)
+                },
+                last_brood_at: broods.iter().map(|b| b.brood_date).max(),
+                survival,
+            }
+        })
+        .collect()
+}
+
+/// Records a brood produced by a tank's population of a species.
+pub async fn record_brood(
+    Path((tank_id, species_id)): Path<(String, i32)>,
+    State(state): State<AppState>,
+    Json(request): Json<RecordBroodRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    if request.count <= 0 {
+        return Err(ApiError::InvalidInput(
+            "Brood count must be a positive number of offspring".to_string(),
+        ));
+    }
+    let brood_date = request.brood_date.unwrap_or_else(Utc::now);
+    if brood_date > Utc::now() {
+        return Err(ApiError::InvalidInput(
+            "brood_date cannot be in the future".to_string(),
+        ));
+    }
+
+    let mut tx = state.pool.begin().await?;
+    ensure_tank_exists(&mut tx, &tank_id).await?;
+
+    let (inhabitant_id, group_size): (i32, i32) = sqlx::query_as(
+        "SELECT id, count FROM tank_inhabitants WHERE tank_id = $1 AND species_id = $2 AND removed_at IS NULL",
+    )
+    .bind(&tank_id)
+    .bind(species_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| {
+        ApiError::NotFound(format!("No species {} living in tank {}", species_id, tank_id))
+    })?;
+
+    let parent_count = request.parent_count.unwrap_or(group_size);
+    if parent_count <= 0 || parent_count > group_size {
+        return Err(ApiError::InvalidInput(format!(
+            "parent_count must be between 1 and the group size ({})",
+            group_size
+        )));
+    }
+
+    let id: i32 = sqlx::query_scalar(
+        "INSERT INTO broods (inhabitant_id, parent_count, brood_date, initial_count, notes) \
+         VALUES ($1, $2, $3, $4, $5) RETURNING id",
+    )
+    .bind(inhabitant_id)
+    .bind(parent_count)
+    .bind(brood_date)
+    .bind(request.count)
+    .bind(&request.notes)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let brood = fetch_broods(&mut tx, BroodFilter::Brood(id))
+        .await?
+        .pop()
+        .ok_or_else(|| ApiError::InternalError(format!("Brood {} not found after insert", id)))?;
+    tx.commit().await?;
+
+    tracing::info!(
+        tank_id = %tank_id,
+        species_id = species_id,
+        brood_id = id,
+        offspring = request.count,
+        operation = "record_brood",
+        "Brood recorded"
+    );
+
+    Ok((StatusCode::CREATED, Json(brood)))
+}
+
+/// Records the number of survivors of a brood at one of the fixed checkpoints.
+pub async fn record_survival(
+    Path(brood_id): Path<i32>,
+    State(state): State<AppState>,
+    Json(request): Json<RecordSurvivalRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    if !SURVIVAL_CHECKPOINT_DAYS.contains(&request.day) {
+        return Err(ApiError::InvalidInput(format!(
+            "day must be one of {:?}",
+            SURVIVAL_CHECKPOINT_DAYS
+        )));
+    }
+
+    let mut tx = state.pool.begin().await?;
+
+    let initial_count: i32 = sqlx::query_scalar("SELECT initial_count FROM broods WHERE id = $1 FOR UPDATE")
+        .bind(brood_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Brood with ID {} not found", brood_id)))?;
+
+    if request.survivors < 0 || request.survivors > initial_count {
+        return Err(ApiError::InvalidInput(format!(
+            "survivors must be between 0 and the brood size ({})",
+            initial_count
+        )));
+    }
+
+    sqlx::query(
+        "INSERT INTO brood_survival_checks (brood_id, day, survivors, checked_at) \
+         VALUES ($1, $2, $3, COALESCE($4, NOW())) \
+         ON CONFLICT (brood_id, day) \
+         DO UPDATE SET survivors = EXCLUDED.survivors, checked_at = EXCLUDED.checked_at",
+    )
+    .bind(brood_id)
+    .bind(request.day)
+    .bind(request.survivors)
+    .bind(request.checked_at)
+    .execute(&mut *tx)
+    .await?;
+
+    let brood = fetch_broods(&mut tx, BroodFilter::Brood(brood_id))
+        .await?
+        .pop()
+        .ok_or_else(|| ApiError::NotFound(format!("Brood with ID {} not found", brood_id)))?;
+    tx.commit().await?;
+
+    tracing::info!(
+        brood_id = brood_id,
+        day = request.day,
+        survivors = request.survivors,
+        operation = "record_brood_survival",
+        "Brood survival recorded"
+    );
+
+    Ok(Json(brood))
+}
+
+/// Lists the broods produced in a tank, newest first.
+pub async fn list_tank_broods(
+    Path(tank_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut conn = state.pool.acquire().await?;
+    ensure_tank_exists(&mut conn, &tank_id).await?;
+
+    let broods = fetch_broods(&mut conn, BroodFilter::Tank(&tank_id)).await?;
+    Ok(Json(broods))
+}
+
+/// Reproduction statistics for every species with recorded broods.
+pub async fn get_reproduction_stats(State(state): State<AppState>) -> Result<impl IntoResponse, ApiError> {
+    let mut conn = state.pool.acquire().await?;
+    let broods = fetch_broods(&mut conn, BroodFilter::All).await?;
+
+    Ok(Json(reproduction_stats(&broods)))
+}
+
+/// Reproduction statistics for one species.
+pub async fn get_species_reproduction_stats(
+    Path(species_id): Path<i32>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut conn = state.pool.acquire().await?;
+
+    let species_name: String = sqlx::query_scalar("SELECT name FROM species WHERE id = $1")
+        .bind(species_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| ApiError::SpeciesNotFound(format!("Species with ID {} not found", species_id)))?;
+
+    let broods = fetch_broods(&mut conn, BroodFilter::Species(species_id)).await?;
+    let stats = reproduction_stats(&broods)
+        .pop()
+        .unwrap_or_else(|| ReproductionStats {
+            species_id,
+            species_name,
+            broods: 0,
+            parent_groups: 0,
+            total_offspring: 0,
+            average_brood_size: 0.0,
+            offspring_per_parent: 0.0,
+            average_days_between_broods: None,
+            last_brood_at: None,
+            survival: Vec::new(),
+        });
+
+    Ok(Json(stats))
+}
@@ -0,0 +1,476 @@
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone, Utc};
+use chrono_tz::{OffsetComponents, OffsetName, Tz};
+use shuttle_axum::axum::{
+    extract::{Path, State},
+    http::header,
+    response::IntoResponse,
+};
+
+use crate::{
+    feeding::{build_tank_feeding_schedule, fetch_tank, tank_timezone, TankFeedingSchedule},
+    ApiError, AppState, Tank,
+};
+
+const PRODUCT_ID: &str = "-//ShellCon//species-hub//EN";
+const UID_DOMAIN: &str = "species-hub.shellcon";
+const FEEDING_DURATION: &str = "PT15M";
+
+/// Years of offset changes spelled out in each VTIMEZONE, starting from the current year.
+/// Clients keep applying the last observance after that.
+const TIMEZONE_YEARS: i32 = 5;
+
+/// Minimal iCalendar (RFC 5545) writer: escapes text values, folds long lines and uses CRLF.
+struct Calendar {
+    out: String,
+    // Every TZID referenced by an event needs a matching VTIMEZONE
+    timezones: Vec<Tz>,
+}
+
+impl Calendar {
+    fn new(name: &str) -> Self {
+        let mut calendar = Self {
+            out: String::new(),
+            timezones: Vec::new(),
+        };
+        calendar.line("BEGIN:VCALENDAR");
+        calendar.line("VERSION:2.0");
+        calendar.line(&format!("PRODID:{}", PRODUCT_ID));
+        calendar.line("CALSCALE:GREGORIAN");
+        calendar.line("METHOD:PUBLISH");
+        calendar.line(&format!("X-WR-CALNAME:{}", escape_text(name)));
+        calendar
+    }
+
+    fn line(&mut self, content: &str) {
+        // Fold at 75 octets without splitting a UTF-8 character
+        let mut width = 0;
+        for ch in content.chars() {
+            let len = ch.len_utf8();
+            if width + len > 75 {
+                self.out.push_str("\r\n ");
+                width = 1;
+            }
+            self.out.push(ch);
+            width += len;
+        }
+        self.out.push_str("\r\n");
+    }
+
+    /// Returns the TZID for a timezone, registering it for a VTIMEZONE component.
+    fn tzid(&mut self, timezone: Tz) -> &'static str {
+        if !self.timezones.contains(&timezone) {
+            self.timezones.push(timezone);
+        }
+        timezone.name()
+    }
+
+    fn finish(mut self) -> String {
+        let from = Utc::now().year();
+        for timezone in std::mem::take(&mut self.timezones) {
+            add_timezone(&mut self, timezone, from, from + TIMEZONE_YEARS);
+        }
+        self.line("END:VCALENDAR");
+        self.out
+    }
+}
+
+/// A period during which a timezone keeps one UTC offset.
+struct Observance {
+    /// Local wall-clock time the period starts, in the offset it replaces
+    onset: NaiveDateTime,
+    offset_from: FixedOffset,
+    offset_to: FixedOffset,
+    name: String,
+    daylight: bool,
+}
+
+impl Observance {
+    fn at(timezone: Tz, instant: DateTime<Utc>, offset_from: FixedOffset) -> Self {
+        let offset = timezone.offset_from_utc_datetime(&instant.naive_utc());
+        let offset_to = offset.fix();
+        Observance {
+            onset: instant.naive_utc() + Duration::seconds(offset_from.local_minus_utc() as i64),
+            offset_from,
+            offset_to,
+            name: offset
+                .abbreviation()
+                .map(str::to_string)
+                .unwrap_or_else(|| format_utc_offset(offset_to)),
+            daylight: !offset.dst_offset().is_zero(),
+        }
+    }
+}
+
+fn format_utc_offset(offset: FixedOffset) -> String {
+    let seconds = offset.local_minus_utc();
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let minutes = seconds.abs() / 60;
+    format!("{}{:02}{:02}", sign, minutes / 60, minutes % 60)
+}
+
+fn utc_offset_at(timezone: Tz, instant: DateTime<Utc>) -> FixedOffset {
+    timezone.offset_from_utc_datetime(&instant.naive_utc()).fix()
+}
+
+/// The offset in effect at the start of `from_year`, then every change up to the end of `to_year`.
+fn timezone_observances(timezone: Tz, from_year: i32, to_year: i32) -> Vec<Observance> {
+    let year_start = |year: i32| {
+        NaiveDate::from_ymd_opt(year, 1, 1)
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .map(|start| start.and_utc())
+    };
+    let (Some(start), Some(end)) = (year_start(from_year), year_start(to_year + 1)) else {
+        return Vec::new();
+    };
+
+    let initial = utc_offset_at(timezone, start);
+    let mut observances = vec![Observance {
+        // Before any event; only the offset matters
+        onset: NaiveDate::from_ymd_opt(1970, 1, 1)
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .unwrap_or_default(),
+        offset_from: initial,
+        ..Observance::at(timezone, start, initial)
+    }];
+
+    // Offsets change at most a few times a year, so step by day and narrow each change
+    // down to the second it happens
+    let mut previous = start;
+    let mut previous_offset = initial;
+    while previous < end {
+        let next = previous + Duration::days(1);
+        let next_offset = utc_offset_at(timezone, next);
+        if next_offset != previous_offset {
+            let (mut before, mut after) = (previous, next);
+            while after - before > Duration::seconds(1) {
+                let middle = before + (after - before) / 2;
+                if utc_offset_at(timezone, middle) == previous_offset {
+                    before = middle;
+                } else {
+                    after = middle;
+                }
+            }
+            observances.push(Observance::at(timezone, after, previous_offset));
+        }
+        previous = next;
+        previous_offset = next_offset;
+    }
+    observances
+}
+
+/// Writes a VTIMEZONE with one STANDARD or DAYLIGHT component per observance.
+fn add_timezone(calendar: &mut Calendar, timezone: Tz, from_year: i32, to_year: i32) {
+    calendar.line("BEGIN:VTIMEZONE");
+    calendar.line(&format!("TZID:{}", timezone.name()));
+    for observance in timezone_observances(timezone, from_year, to_year) {
+        let kind = if observance.daylight { "DAYLIGHT" } else { "STANDARD" };
+        calendar.line(&format!("BEGIN:{}", kind));
+        calendar.line(&format!("DTSTART:{}", observance.onset.format("%Y%m%dT%H%M%S")));
+        calendar.line(&format!("TZOFFSETFROM:{}", format_utc_offset(observance.offset_from)));
+        calendar.line(&format!("TZOFFSETTO:{}", format_utc_offset(observance.offset_to)));
+        calendar.line(&format!("TZNAME:{}", escape_text(&observance.name)));
+        calendar.line(&format!("END:{}", kind));
+    }
+    calendar.line("END:VTIMEZONE");
+}
+
+/// Escapes a TEXT value. Line breaks of any style become `\n`; a bare `\r` would
+/// otherwise end the content line early.
+fn escape_text(value: &str) -> String {
+    value
+        .replace("\r\n", "\n")
+        .replace('\r', "\n")
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Daily recurring events for each feeding slot, in the tank's local timezone.
+fn add_feeding_events(calendar: &mut Calendar, tank: &Tank, schedule: &TankFeedingSchedule, timezone: Tz) {
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let today = Utc::now().with_timezone(&timezone).date_naive();
+
+    for slot in &schedule.feedings {
+        let Ok(time) = NaiveTime::parse_from_str(&slot.time, "%H:%M") else {
+            continue;
+        };
+        let description = slot
+            .foods
+            .iter()
+            .map(|food| format!("{}: {:.2} g", food.food_type, food.amount_grams))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        calendar.line("BEGIN:VEVENT");
+        calendar.line(&format!(
+            "UID:feeding-{}-{}@{}",
+            tank.id,
+            time.format("%H%M"),
+            UID_DOMAIN
+        ));
+        calendar.line(&format!("DTSTAMP:{}", stamp));
+        let tzid = calendar.tzid(timezone);
+        calendar.line(&format!(
+            "DTSTART;TZID={}:{}",
+            tzid,
+            today.and_time(time).format("%Y%m%dT%H%M%S")
+        ));
+        calendar.line(&format!("DURATION:{}", FEEDING_DURATION));
+        calendar.line("RRULE:FREQ=DAILY");
+        calendar.line(&format!(
+            "SUMMARY:{}",
+            escape_text(&format!("Feed {} ({})", tank.name, tank.id))
+        ));
+        calendar.line(&format!("DESCRIPTION:{}", escape_text(&description)));
+        calendar.line("CATEGORIES:FEEDING");
+        calendar.line("END:VEVENT");
+    }
+}
+
+/// An all-day event on the next cleaning due date, repeating every cleaning interval.
+fn add_cleaning_event(calendar: &mut Calendar, tank: &Tank, timezone: Tz) {
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let today = Utc::now().with_timezone(&timezone).date_naive();
+    let interval = tank.cleaning_interval_days.max(1);
+
+    // Overdue cleanings are shown as due today
+    let due = tank
+        .last_cleaned_at
+        .map(|cleaned| cleaned.with_timezone(&timezone).date_naive() + Duration::days(interval as i64))
+        .map_or(today, |due| due.max(today));
+    let description = match tank.last_cleaned_at {
+        Some(cleaned) => format!(
+            "Clean every {} days. Last cleaned {}.",
+            interval,
+            cleaned.with_timezone(&timezone).format("%Y-%m-%d")
+        ),
+        None => format!("Clean every {} days. No cleaning recorded yet.", interval),
+    };
+
+    calendar.line("BEGIN:VEVENT");
+    calendar.line(&format!("UID:cleaning-{}@{}", tank.id, UID_DOMAIN));
+    calendar.line(&format!("DTSTAMP:{}", stamp));
+    calendar.line(&format!("DTSTART;VALUE=DATE:{}", due.format("%Y%m%d")));
+    calendar.line(&format!("RRULE:FREQ=DAILY;INTERVAL={}", interval));
+    calendar.line(&format!(
+        "SUMMARY:{}",
+        escape_text(&format!("Clean {} ({})", tank.name, tank.id))
+    ));
+    calendar.line(&format!("DESCRIPTION:{}", escape_text(&description)));
+    calendar.line("CATEGORIES:CLEANING");
+    calendar.line("END:VEVENT");
+}
+
+async fn add_tank(calendar: &mut Calendar, state: &AppState, tank: &Tank) -> Result<(), ApiError> {
+    let timezone = tank_timezone(tank, state.facility_timezone);
+    let schedule = build_tank_feeding_schedule(state, tank).await?;
+    add_feeding_events(calendar, tank, &schedule, timezone);
+    add_cleaning_event(calendar, tank, timezone);
+    Ok(())
+}
+
+fn calendar_response(body: String, filename: &str) -> impl IntoResponse {
+    (
+        [
+            (header::CONTENT_TYPE, "text/calendar; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("inline; filename=\"{}\"", filename),
+            ),
+        ],
+        body,
+    )
+}
+
+/// iCalendar feed of feeding times and cleaning due dates for every tank.
+pub async fn get_facility_calendar(State(state): State<AppState>) -> Result<impl IntoResponse, ApiError> {
+    let tanks = sqlx::query_as::<_, Tank>("SELECT * FROM tanks ORDER BY id")
+        .fetch_all(&state.pool)
+        .await?;
+
+    let mut calendar = Calendar::new("Aquarium maintenance");
+    for tank in &tanks {
+        add_tank(&mut calendar, &state, tank).await?;
+    }
+
+    tracing::info!(
+        tanks_exported = tanks.len(),
+        operation = "export_maintenance_calendar",
+        "Maintenance calendar exported"
+    );
+
+    Ok(calendar_response(calendar.finish(), "aquarium-maintenance.ics"))
+}
+
+/// iCalendar feed of feeding times and cleaning due dates for one tank.
+pub async fn get_tank_calendar(
+    Path(tank_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let tank = fetch_tank(&state.pool, &tank_id).await?;
+
+    let mut calendar = Calendar::new(&format!("{} maintenance", tank.name));
+    add_tank(&mut calendar, &state, &tank).await?;
+
+    Ok(calendar_response(
+        calendar.finish(),
+        &format!("{}-maintenance.ics", tank.id),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feeding::{FeedingSlot, FoodPortion};
+
+    fn tank(id: &str, cleaning_interval_days: i32, last_cleaned_at: Option<DateTime<Utc>>) -> Tank {
+        Tank {
+            id: id.to_string(),
+            name: format!("Shrimp, {}", id),
+            tank_type: "planted".to_string(),
+            volume: 60.0,
+            description: None,
+            timezone: None,
+            cleaning_interval_days,
+            last_cleaned_at,
+        }
+    }
+
+    fn schedule(tank: &Tank, timezone: Tz) -> TankFeedingSchedule {
+        TankFeedingSchedule {
+            tank_id: tank.id.clone(),
+            tank_name: tank.name.clone(),
+            tank_type: tank.tank_type.clone(),
+            timezone: timezone.name().to_string(),
+            feeding_times: vec!["08:00".to_string()],
+            feedings: vec![FeedingSlot {
+                time: "08:00".to_string(),
+                foods: vec![
+                    FoodPortion {
+                        food_type: "algae wafers".to_string(),
+                        amount_grams: 0.5,
+                    },
+                    FoodPortion {
+                        food_type: "flake food".to_string(),
+                        amount_grams: 0.25,
+                    },
+                ],
+                amount_grams: 0.75,
+            }],
+            foods: Vec::new(),
+            daily_amount_grams: 0.75,
+            species: Vec::new(),
+        }
+    }
+
+    fn export(tanks: &[(Tank, Tz)]) -> String {
+        let mut calendar = Calendar::new("Aquarium maintenance");
+        for (tank, timezone) in tanks {
+            add_feeding_events(&mut calendar, tank, &schedule(tank, *timezone), *timezone);
+            add_cleaning_event(&mut calendar, tank, *timezone);
+        }
+        calendar.finish()
+    }
+
+    #[test]
+    fn long_lines_are_folded_with_crlf() {
+        let mut calendar = Calendar::new("Folding");
+        calendar.line(&format!("DESCRIPTION:{}", "é".repeat(60)));
+        let ics = calendar.finish();
+
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(!ics.replace("\r\n", "").contains('\n'));
+        for line in ics.split("\r\n") {
+            assert!(line.len() <= 75, "line of {} octets: {}", line.len(), line);
+        }
+        assert!(ics.contains("\r\n é"));
+    }
+
+    #[test]
+    fn text_values_are_escaped() {
+        assert_eq!(escape_text("a,b;c\\d\ne"), "a\\,b\\;c\\\\d\\ne");
+        assert_eq!(escape_text("Windows\r\nline\rbreaks"), "Windows\\nline\\nbreaks");
+    }
+
+    #[test]
+    fn feeding_events_recur_daily_in_the_tank_timezone() {
+        let ics = export(&[(tank("Tank-A1", 7, None), chrono_tz::Europe::Berlin)]);
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.contains("UID:feeding-Tank-A1-0800@species-hub.shellcon\r\n"));
+        assert!(ics.contains("DTSTART;TZID=Europe/Berlin:"));
+        assert!(ics.contains("T080000\r\nDURATION:PT15M\r\nRRULE:FREQ=DAILY\r\n"));
+        assert!(ics.contains("SUMMARY:Feed Shrimp\\, Tank-A1 (Tank-A1)\r\n"));
+        assert!(ics.contains("DESCRIPTION:algae wafers: 0.50 g\\nflake food: 0.25 g\r\n"));
+    }
+
+    #[test]
+    fn every_tzid_has_one_vtimezone() {
+        let ics = export(&[
+            (tank("Tank-A1", 7, None), chrono_tz::Europe::Berlin),
+            (tank("Tank-B2", 7, None), chrono_tz::Europe::Berlin),
+            (tank("Tank-C3", 7, None), chrono_tz::America::New_York),
+        ]);
+
+        for timezone in ["Europe/Berlin", "America/New_York"] {
+            assert!(ics.contains(&format!("DTSTART;TZID={}:", timezone)));
+            assert_eq!(ics.matches(&format!("\r\nTZID:{}\r\n", timezone)).count(), 1);
+        }
+        assert_eq!(ics.matches("BEGIN:VTIMEZONE").count(), 2);
+        // Timezones come after the events that use them, before the calendar closes
+        assert!(ics.rfind("END:VEVENT").unwrap() < ics.find("BEGIN:VTIMEZONE").unwrap());
+    }
+
+    #[test]
+    fn observances_cover_each_offset_change() {
+        let observances = timezone_observances(chrono_tz::Europe::Berlin, 2025, 2025);
+        let summary: Vec<(String, String, String, &str, bool)> = observances
+            .iter()
+            .map(|o| {
+                (
+                    o.onset.format("%Y%m%dT%H%M%S").to_string(),
+                    format_utc_offset(o.offset_from),
+                    format_utc_offset(o.offset_to),
+                    o.name.as_str(),
+                    o.daylight,
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                ("19700101T000000".to_string(), "+0100".to_string(), "+0100".to_string(), "CET", false),
+                ("20250330T020000".to_string(), "+0100".to_string(), "+0200".to_string(), "CEST", true),
+                ("20251026T030000".to_string(), "+0200".to_string(), "+0100".to_string(), "CET", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn utc_has_a_single_standard_observance() {
+        let observances = timezone_observances(Tz::UTC, 2025, 2029);
+
+        assert_eq!(observances.len(), 1);
+        assert!(!observances[0].daylight);
+        assert_eq!(format_utc_offset(observances[0].offset_to), "+0000");
+    }
+
+    #[test]
+    fn offsets_are_formatted_with_sign_hours_and_minutes() {
+        let offset = |seconds: i32| FixedOffset::east_opt(seconds).unwrap();
+        assert_eq!(format_utc_offset(offset(5 * 3600 + 30 * 60)), "+0530");
+        assert_eq!(format_utc_offset(offset(-(3 * 3600 + 30 * 60))), "-0330");
+    }
+
+    #[test]
+    fn overdue_cleaning_is_due_today() {
+        let overdue = tank("Tank-A1", 7, Some(Utc::now() - Duration::days(30)));
+        let ics = export(&[(overdue, Tz::UTC)]);
+
+        let today = Utc::now().date_naive().format("%Y%m%d");
+        assert!(ics.contains(&format!("DTSTART;VALUE=DATE:{}\r\nRRULE:FREQ=DAILY;INTERVAL=7\r\n", today)));
+        assert!(ics.contains("CATEGORIES:CLEANING"));
+    }
+}
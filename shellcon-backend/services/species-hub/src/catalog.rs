@@ -0,0 +1,602 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use shuttle_axum::axum::{
+    extract::{Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use tracing::Instrument;
+
+use crate::{
+    history::{self, HistoryEntity, RevisionOperation},
+    taxonomy, ApiError, AppState, Species,
+};
+
+const WATER_TYPES: [&str; 3] = ["fresh", "brackish", "marine"];
+const CARE_LEVELS: [&str; 3] = ["beginner", "intermediate", "advanced"];
+/// Diets the feeding schedule knows how to ration for.
+const DIET_TYPES: [&str; 4] = ["carnivore", "herbivore", "filter feeder", "omnivore"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CatalogFormat {
+    Json,
+    Csv,
+}
+
+impl CatalogFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CatalogFormat::Json => "json",
+            CatalogFormat::Csv => "csv",
+        }
+    }
+}
+
+/// One species as it appears in an import or export file.
+///
+/// Database ids are left out so files can move between environments;
+/// `scientific_name` identifies the species instead.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeciesRecord {
+    pub name: String,
+    pub scientific_name: String,
+    pub description: String,
+    pub min_temperature: f64,
+    pub max_temperature: f64,
+    pub min_ph: f64,
+    pub max_ph: f64,
+    pub diet_type: String,
+    pub min_salinity: f64,
+    pub max_salinity: f64,
+    pub min_oxygen: f64,
+    pub min_gh: Option<f64>,
+    pub max_gh: Option<f64>,
+    pub min_kh: Option<f64>,
+    pub max_kh: Option<f64>,
+    pub water_type: String,
+    pub care_level: String,
+}
+
+impl From<Species> for SpeciesRecord {
+    fn from(species: Species) -> Self {
+        Self {
+            name: species.name,
+            scientific_name: species.scientific_name,
+            description: species.description,
+            min_temperature: species.min_temperature,
+            max_temperature: species.max_temperature,
+            min_ph: species.min_ph,
+            max_ph: species.max_ph,
+            diet_type: species.diet_type,
+            min_salinity: species.min_salinity,
+            max_salinity: species.max_salinity,
+            min_oxygen: species.min_oxygen,
+            min_gh: species.min_gh,
+            max_gh: species.max_gh,
+            min_kh: species.min_kh,
+            max_kh: species.max_kh,
+            water_type: species.water_type,
+            care_level: species.care_level,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportAction {
+    Create,
+    Update,
+    Unchanged,
+}
+
+#[derive(Serialize)]
+pub struct RowChange {
+    pub row: usize,
+    pub scientific_name: String,
+    pub action: ImportAction,
+    pub changed_fields: Vec<&'static str>,
+}
+
+#[derive(Serialize)]
+pub struct RowError {
+    /// 1-based position of the record in the file (excluding the CSV header)
+    pub row: usize,
+    pub scientific_name: Option<String>,
+    pub errors: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct ImportReport {
+    pub format: CatalogFormat,
+    pub dry_run: bool,
+    /// False when validation errors stopped the import or when running dry
+    pub applied: bool,
+    pub rows: usize,
+    pub created: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub changes: Vec<RowChange>,
+    pub errors: Vec<RowError>,
+}
+
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    format: Option<CatalogFormat>,
+}
+
+#[derive(Deserialize)]
+pub struct ImportQuery {
+    format: Option<CatalogFormat>,
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// Parses every record, keeping per-row parse failures instead of stopping at the first one.
+fn parse_records(format: CatalogFormat, body: &str) -> Result<Vec<Result<SpeciesRecord, String>>, ApiError> {
+    match format {
+        CatalogFormat::Json => {
+            let values: Vec<serde_json::Value> = serde_json::from_str(body).map_err(|e| {
+                ApiError::InvalidInput(format!("Expected a JSON array of species: {}", e))
+            })?;
+            Ok(values
+                .into_iter()
+                .map(|value| serde_json::from_value::<SpeciesRecord>(value).map_err(|e| e.to_string()))
+                .collect())
+        }
+        CatalogFormat::Csv => {
+            let mut reader = csv::ReaderBuilder::new()
+                .trim(csv::Trim::All)
+                .from_reader(body.as_bytes());
+            reader
+                .headers()
+                .map_err(|e| ApiError::InvalidInput(format!("Unreadable CSV header: {}", e)))?;
+            Ok(reader
+                .deserialize::<SpeciesRecord>()
+                .map(|record| record.map_err(|e| e.to_string()))
+                .collect())
+        }
+    }
+}
+
+fn check_range(errors: &mut Vec<String>, field: &str, min: f64, max: f64, bounds: (f64, f64)) {
+    if !min.is_finite() || !max.is_finite() {
+        errors.push(format!("{} range must be numeric", field));
+    } else if min > max {
+        errors.push(format!("min_{} ({}) is greater than max_{} ({})", field, min, field, max));
+    } else if min < bounds.0 || max > bounds.1 {
+        errors.push(format!("{} range must lie within {}–{}", field, bounds.0, bounds.1));
+    }
+}
+
+fn check_optional_range(errors: &mut Vec<String>, field: &str, min: Option<f64>, max: Option<f64>) {
+    match (min, max) {
+        (Some(min), Some(max)) => check_range(errors, field, min, max, (0.0, 50.0)),
+        (None, None) => {}
+        _ => errors.push(format!("min_{0} and max_{0} must be given together", field)),
+    }
+}
+
+/// Checks a record against the same rules the database and the rest of the service rely on.
+fn validate_record(record: &SpeciesRecord) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    for (field, value) in [
+        ("name", &record.name),
+        ("scientific_name", &record.scientific_name),
+        ("description", &record.description),
+        ("diet_type", &record.diet_type),
+    ] {
+        if value.trim().is_empty() {
+            errors.push(format!("{} is required", field));
+        }
+    }
+    if record.name.chars().count() > 100 || record.scientific_name.chars().count() > 100 {
+        errors.push("name and scientific_name are limited to 100 characters".to_string());
+    }
+
+    check_range(&mut errors, "temperature", record.min_temperature, record.max_temperature, (-5.0, 45.0));
+    check_range(&mut errors, "ph", record.min_ph, record.max_ph, (0.0, 14.0));
+    check_range(&mut errors, "salinity", record.min_salinity, record.max_salinity, (0.0, 60.0));
+    check_optional_range(&mut errors, "gh", record.min_gh, record.max_gh);
+    check_optional_range(&mut errors, "kh", record.min_kh, record.max_kh);
+    if !record.min_oxygen.is_finite() || record.min_oxygen < 0.0 {
+        errors.push("min_oxygen must be a non-negative number".to_string());
+    }
+
+    if !WATER_TYPES.contains(&record.water_type.as_str()) {
+        errors.push(format!("water_type must be one of {:?}", WATER_TYPES));
+    }
+    if !CARE_LEVELS.contains(&record.care_level.as_str()) {
+        errors.push(format!("care_level must be one of {:?}", CARE_LEVELS));
+    }
+    if !record.diet_type.trim().is_empty() && !DIET_TYPES.contains(&record.diet_type.as_str()) {
+        errors.push(format!("diet_type must be one of {:?}", DIET_TYPES));
+    }
+
+    errors
+}
+
+/// Lists the fields an import would change on an existing species.
+fn changed_fields(existing: &Species, record: &SpeciesRecord) -> Vec<&'static str> {
+    let mut changed = Vec::new();
+    let mut compare = |field: &'static str, different: bool| {
+        if different {
+            changed.push(field);
+        }
+    };
+
+    compare("name", existing.name != record.name);
+    compare("scientific_name", existing.scientific_name != record.scientific_name);
+    compare("description", existing.description != record.description);
+    compare("min_temperature", existing.min_temperature != record.min_temperature);
+    compare("max_temperature", existing.max_temperature != record.max_temperature);
+    compare("min_ph", existing.min_ph != record.min_ph);
+    compare("max_ph", existing.max_ph != record.max_ph);
+    compare("diet_type", existing.diet_type != record.diet_type);
+    compare("min_salinity", existing.min_salinity != record.min_salinity);
+    compare("max_salinity", existing.max_salinity != record.max_salinity);
+    compare("min_oxygen", existing.min_oxygen != record.min_oxygen);
+    compare("min_gh", existing.min_gh != record.min_gh);
+    compare("max_gh", existing.max_gh != record.max_gh);
+    compare("min_kh", existing.min_kh != record.min_kh);
+    compare("max_kh", existing.max_kh != record.max_kh);
+    compare("water_type", existing.water_type != record.water_type);
+    compare("care_level", existing.care_level != record.care_level);
+
+    changed
+}
+
+/// Picks the file format from the query string, then the Content-Type header, defaulting to JSON.
+fn request_format(query: Option<CatalogFormat>, headers: &HeaderMap) -> CatalogFormat {
+    query.unwrap_or_else(|| {
+        let content_type = headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        if content_type.starts_with("text/csv") {
+            CatalogFormat::Csv
+        } else {
+            CatalogFormat::Json
+        }
+    })
+}
+
+/// Exports the whole species catalog as JSON or CSV, ordered by scientific name.
+pub async fn export_species(
+    Query(query): Query<ExportQuery>,
+    State(state): State<AppState>,
+) -> Result<Response, ApiError> {
+    let format = query.format.unwrap_or(CatalogFormat::Json);
+    let records: Vec<SpeciesRecord> = sqlx::query_as::<_, Species>("SELECT * FROM species ORDER BY scientific_name")
+        .fetch_all(&state.pool)
+        .await?
+        .into_iter()
+        .map(SpeciesRecord::from)
+        .collect();
+
+    tracing::info!(
+        species_exported = records.len(),
+        format = format.as_str(),
+        operation = "export_species_catalog",
+        "Species catalog exported"
+    );
+
+    match format {
+        CatalogFormat::Json => Ok(Json(records).into_response()),
+        CatalogFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            for record in &records {
+                writer
+                    .serialize(record)
+                    .map_err(|e| ApiError::InternalError(format!("CSV export failed: {}", e)))?;
+            }
+            let body = writer
+                .into_inner()
+                .map_err(|e| ApiError::InternalError(format!("CSV export failed: {}", e)))?;
+
+            Ok((
+                [
+                    (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+                    (header::CONTENT_DISPOSITION, "attachment; filename=\"species.csv\""),
+                ],
+                body,
+            )
+                .into_response())
+        }
+    }
+}
+
+/// Imports species from JSON or CSV, upserting on scientific name in a single transaction.
+///
+/// Every row is validated before anything is written; if any row fails, nothing is
+/// imported and the response lists the errors per row. With `dry_run=true` the report
+/// describes the changes without applying them.
+pub async fn import_species(
+    Query(query): Query<ImportQuery>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<impl IntoResponse, ApiError> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    let span = tracing::info_span!("species_catalog_import", %request_id);
+    async move {
+        let format = request_format(query.format, &headers);
+        let changed_by = history::changed_by(&headers);
+        let parsed = parse_records(format, &body)?;
+
+        let mut records = Vec::new();
+        let mut errors = Vec::new();
+        let mut seen = HashSet::new();
+        for (index, parsed) in parsed.into_iter().enumerate() {
+            let row = index + 1;
+            match parsed {
+                Ok(mut record) => {
+                    record.scientific_name = record.scientific_name.trim().to_string();
+                    record.name = record.name.trim().to_string();
+                    let mut row_errors = validate_record(&record);
+                    if !seen.insert(record.scientific_name.to_lowercase()) {
+                        row_errors.push(format!(
+                            "{} appears more than once in the file",
+                            record.scientific_name
+                        ));
+                    }
+                    if row_errors.is_empty() {
+                        records.push((row, record));
+                    } else {
+                        errors.push(RowError {
+                            row,
+                            scientific_name: Some(record.scientific_name),
+                            errors: row_errors,
+                        });
+                    }
+                }
+                Err(message) => errors.push(RowError {
+                    row,
+                    scientific_name: None,
+                    errors: vec![message],
+                }),
+            }
+        }
+
+        let mut tx = state.pool.begin().await?;
+
+        // Scientific names match case-insensitively, like the unique index on them
+        let names: Vec<String> = records
+            .iter()
+            .map(|(_, record)| record.scientific_name.to_lowercase())
+            .collect();
+        let existing: HashMap<String, Species> =
+            sqlx::query_as::<_, Species>("SELECT * FROM species WHERE LOWER(scientific_name) = ANY($1) FOR UPDATE")
+                .bind(&names)
+                .fetch_all(&mut *tx)
+                .await?
+                .into_iter()
+                .map(|species| (species.scientific_name.to_lowercase(), species))
+                .collect();
+
+        let changes: Vec<RowChange> = records
+            .iter()
+            .map(|(row, record)| {
+                let (action, changed_fields) = match existing.get(&record.scientific_name.to_lowercase()) {
+                    None => (ImportAction::Create, Vec::new()),
+                    Some(species) => {
+                        let fields = changed_fields(species, record);
+                        if fields.is_empty() {
+                            (ImportAction::Unchanged, fields)
+                        } else {
+                            (ImportAction::Update, fields)
+                        }
+                    }
+                };
+                RowChange {
+                    row: *row,
+                    scientific_name: record.scientific_name.clone(),
+                    action,
+                    changed_fields,
+                }
+            })
+            .collect();
+
+        let apply = errors.is_empty() && !query.dry_run;
+        if apply {
+            for ((_, record), change) in records.iter().zip(&changes) {
+                if change.action == ImportAction::Unchanged {
+                    continue;
+                }
+                let species_id: i32 = sqlx::query_scalar(
+                    "INSERT INTO species (name, scientific_name, description, min_temperature, max_temperature, \
+                     min_ph, max_ph, diet_type, min_salinity, max_salinity, min_oxygen, min_gh, max_gh, min_kh, max_kh, \
+                     water_type, care_level) \
+                     VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17) \
+                     ON CONFLICT ((LOWER(scientific_name))) DO UPDATE SET \
+                     name = EXCLUDED.name, scientific_name = EXCLUDED.scientific_name, description = EXCLUDED.description, \
+                     min_temperature = EXCLUDED.min_temperature, max_temperature = EXCLUDED.max_temperature, \
+                     min_ph = EXCLUDED.min_ph, max_ph = EXCLUDED.max_ph, diet_type = EXCLUDED.diet_type, \
+                     min_salinity = EXCLUDED.min_salinity, max_salinity = EXCLUDED.max_salinity, \
+                     min_oxygen = EXCLUDED.min_oxygen, min_gh = EXCLUDED.min_gh, max_gh = EXCLUDED.max_gh, \
+                     min_kh = EXCLUDED.min_kh, max_kh = EXCLUDED.max_kh, \
+                     water_type = EXCLUDED.water_type, care_level = EXCLUDED.care_level \
+                     RETURNING id",
+                )
+                .bind(&record.name)
+                .bind(&record.scientific_name)
+                .bind(&record.description)
+                .bind(record.min_temperature)
+                .bind(record.max_temperature)
+                .bind(record.min_ph)
+                .bind(record.max_ph)
+                .bind(&record.diet_type)
+                .bind(record.min_salinity)
+                .bind(record.max_salinity)
+                .bind(record.min_oxygen)
+                .bind(record.min_gh)
+                .bind(record.max_gh)
+                .bind(record.min_kh)
+                .bind(record.max_kh)
+                .bind(&record.water_type)
+                .bind(&record.care_level)
+                .fetch_one(&mut *tx)
+                .await?;
+
+                let operation = if change.action == ImportAction::Create {
+                    RevisionOperation::Create
+                } else {
+                    RevisionOperation::Update
+                };
+                history::record_revision(
+                    &mut tx,
+                    HistoryEntity::Species,
+                    &species_id.to_string(),
+                    operation,
+                    &changed_by,
+                    Some("Catalog import"),
+                )
+                .await?;
+            }
+            taxonomy::link_genera_by_name(&mut tx, &changed_by).await?;
+            tx.commit().await?;
+            state.catalog_cache.invalidate();
+        } else {
+            tx.rollback().await?;
+        }
+
+        let count = |action: ImportAction| changes.iter().filter(|c| c.action == action).count();
+        let report = ImportReport {
+            format,
+            dry_run: query.dry_run,
+            applied: apply,
+            rows: records.len() + errors.len(),
+            created: count(ImportAction::Create),
+            updated: count(ImportAction::Update),
+            unchanged: count(ImportAction::Unchanged),
+            changes,
+            errors,
+        };
+
+        tracing::info!(
+            request_id = %request_id,
+            format = format.as_str(),
+            dry_run = query.dry_run,
+            rows = report.rows,
+            created = report.created,
+            updated = report.updated,
+            unchanged = report.unchanged,
+            invalid_rows = report.errors.len(),
+            operation = "import_species_catalog",
+            operation_status = if report.applied { "applied" } else if report.errors.is_empty() { "dry_run" } else { "rejected" },
+            "Species catalog import processed"
+        );
+
+        let status = if report.errors.is_empty() {
+            StatusCode::OK
+        } else {
+            StatusCode::UNPROCESSABLE_ENTITY
+        };
+        Ok::<_, ApiError>((status, Json(report)))
+    }
+    .instrument(span)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A case name, how it changes a valid record, and the error it should produce
+    type Case = (&'static str, fn(&mut SpeciesRecord), Option<&'static str>);
+
+    fn record() -> SpeciesRecord {
+        SpeciesRecord {
+            name: "Daggerblade Grass Shrimp".to_string(),
+            scientific_name: "Palaemonetes pugio".to_string(),
+            description: "Estuarine shrimp".to_string(),
+            min_temperature: 10.0,
+            max_temperature: 30.0,
+            min_ph: 7.0,
+            max_ph: 8.5,
+            diet_type: "omnivore".to_string(),
+            min_salinity: 5.0,
+            max_salinity: 30.0,
+            min_oxygen: 4.0,
+            min_gh: None,
+            max_gh: None,
+            min_kh: Some(6.0),
+            max_kh: Some(12.0),
+            water_type: "brackish".to_string(),
+            care_level: "beginner".to_string(),
+        }
+    }
+
+    #[test]
+    fn validation_rules() {
+        let cases: Vec<Case> = vec![
+            ("valid record", |_| {}, None),
+            ("blank name", |r| r.name = "  ".to_string(), Some("name is required")),
+            ("blank diet", |r| r.diet_type = String::new(), Some("diet_type is required")),
+            ("100 accented characters", |r| r.name = "é".repeat(100), None),
+            ("101 characters", |r| r.scientific_name = "a".repeat(101), Some("limited to 100 characters")),
+            ("inverted range", |r| r.min_ph = 9.0, Some("min_ph (9) is greater than max_ph (8.5)")),
+            ("range out of bounds", |r| r.max_temperature = 50.0, Some("temperature range must lie within")),
+            ("non-numeric range", |r| r.min_salinity = f64::NAN, Some("salinity range must be numeric")),
+            ("half a hardness range", |r| r.max_kh = None, Some("min_kh and max_kh must be given together")),
+            ("negative oxygen", |r| r.min_oxygen = -1.0, Some("min_oxygen must be a non-negative number")),
+            ("unknown water type", |r| r.water_type = "salt".to_string(), Some("water_type must be one of")),
+            ("unknown care level", |r| r.care_level = "expert".to_string(), Some("care_level must be one of")),
+            ("unknown diet", |r| r.diet_type = "detritivore".to_string(), Some("diet_type must be one of")),
+        ];
+
+        for (case, change, expected) in cases {
+            let mut record = record();
+            change(&mut record);
+            let errors = validate_record(&record);
+
+            match expected {
+                None => assert!(errors.is_empty(), "{}: {:?}", case, errors),
+                Some(expected) => {
+                    assert_eq!(errors.len(), 1, "{}: {:?}", case, errors);
+                    assert!(errors[0].contains(expected), "{}: {:?}", case, errors);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn json_rows_fail_individually() {
+        let valid = serde_json::to_value(record()).unwrap();
+        let body = serde_json::json!([valid, { "name": "Missing everything" }]).to_string();
+
+        let parsed = parse_records(CatalogFormat::Json, &body).unwrap();
+
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].as_ref().unwrap().scientific_name, "Palaemonetes pugio");
+        assert!(parsed[1].is_err());
+    }
+
+    #[test]
+    fn csv_rows_are_trimmed_and_fail_individually() {
+        let body = "name,scientific_name,description,min_temperature,max_temperature,min_ph,max_ph,diet_type,\
+                    min_salinity,max_salinity,min_oxygen,min_gh,max_gh,min_kh,max_kh,water_type,care_level\n\
+                    Grass Shrimp , Palaemonetes pugio ,Estuarine shrimp,10,30,7,8.5,omnivore,5,30,4,,,6,12,brackish,beginner\n\
+                    Grass Shrimp,Palaemonetes pugio,Estuarine shrimp,warm,30,7,8.5,omnivore,5,30,4,,,6,12,brackish,beginner\n";
+
+        let parsed = parse_records(CatalogFormat::Csv, body).unwrap();
+
+        assert_eq!(parsed.len(), 2);
+        let first = parsed[0].as_ref().unwrap();
+        assert_eq!(first.scientific_name, "Palaemonetes pugio");
+        assert_eq!(first.min_gh, None);
+        assert_eq!(first.max_kh, Some(12.0));
+        assert!(parsed[1].is_err());
+    }
+
+    #[test]
+    fn bodies_that_are_not_a_list_are_rejected() {
+        assert!(matches!(
+            parse_records(CatalogFormat::Json, "{\"name\": \"Grass Shrimp\"}"),
+            Err(ApiError::InvalidInput(_))
+        ));
+    }
+}
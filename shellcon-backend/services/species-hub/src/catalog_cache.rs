@@ -0,0 +1,177 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use shuttle_axum::axum::{
+    body::{Body, Bytes},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::Response,
+};
+use sqlx::PgPool;
+
+use crate::{ApiError, SpeciesQuery};
+
+/// Distinct search responses kept per catalog version before the cache starts over.
+const MAX_CACHED_RESPONSES: usize = 256;
+
+/// Current version of the species catalog, bumped by a trigger on every write to species.
+#[derive(Debug, Clone, Copy, sqlx::FromRow)]
+pub struct CatalogVersion {
+    pub version: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl CatalogVersion {
+    pub fn etag(&self) -> String {
+        format!("\"catalog-{}\"", self.version)
+    }
+
+    /// `Last-Modified` value in the HTTP date format.
+    pub fn last_modified(&self) -> String {
+        self.updated_at.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+    }
+
+    /// Whether the client's cached copy is still current.
+    ///
+    /// `If-None-Match` takes precedence over `If-Modified-Since`, as in RFC 9110.
+    pub fn matches(&self, headers: &HeaderMap) -> bool {
+        if let Some(if_none_match) = headers.get(header::IF_NONE_MATCH).and_then(|v| v.to_str().ok()) {
+            let etag = self.etag();
+            return if_none_match
+                .split(',')
+                .map(|tag| tag.trim().trim_start_matches("W/"))
+                .any(|tag| tag == "*" || tag == etag);
+        }
+
+        headers
+            .get(header::IF_MODIFIED_SINCE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| DateTime::parse_from_rfc2822(v).ok())
+            // HTTP dates have whole-second precision
+            .is_some_and(|since| self.updated_at.timestamp() <= since.timestamp())
+    }
+
+    fn apply_headers(&self, response: &mut Response) {
+        let headers = response.headers_mut();
+        if let Ok(etag) = HeaderValue::from_str(&self.etag()) {
+            headers.insert(header::ETAG, etag);
+        }
+        if let Ok(last_modified) = HeaderValue::from_str(&self.last_modified()) {
+            headers.insert(header::LAST_MODIFIED, last_modified);
+        }
+        // Clients may keep the catalog but must revalidate before using it
+        headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+    }
+
+    /// An empty 304 response carrying the validators.
+    pub fn not_modified(&self) -> Response {
+        let mut response = Response::new(Body::empty());
+        *response.status_mut() = StatusCode::NOT_MODIFIED;
+        self.apply_headers(&mut response);
+        response
+    }
+
+    /// A 200 JSON response with an already serialized body.
+    pub fn respond(&self, body: Bytes) -> Response {
+        let mut response = Response::new(Body::from(body));
+        response
+            .headers_mut()
+            .insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        self.apply_headers(&mut response);
+        response
+    }
+}
+
+pub async fn fetch_version(pool: &PgPool) -> Result<CatalogVersion, sqlx::Error> {
+    sqlx::query_as::<_, CatalogVersion>("SELECT version, updated_at FROM catalog_version")
+        .fetch_one(pool)
+        .await
+}
+
+pub fn serialize<T: Serialize>(value: &T) -> Result<Bytes, ApiError> {
+    serde_json::to_vec(value)
+        .map(Bytes::from)
+        .map_err(|e| ApiError::InternalError(format!("Failed to serialize species response: {}", e)))
+}
+
+/// Cache key for a species search, covering every filter that changes the result.
+pub fn search_key(params: &SpeciesQuery) -> String {
+    let field = |value: &Option<String>| value.as_deref().unwrap_or("").to_string();
+    format!(
+        "name={}&scientific_name={}&order={}&family={}&genus={}",
+        field(&params.name),
+        field(&params.scientific_name),
+        field(&params.order),
+        field(&params.family),
+        field(&params.genus)
+    )
+}
+
+#[derive(Default)]
+struct CacheState {
+    version: i64,
+    searches: HashMap<String, Bytes>,
+    profiles: HashMap<i32, Bytes>,
+}
+
+impl CacheState {
+    /// Drops entries from an older catalog version, e.g. after a write from another instance.
+    fn sync(&mut self, version: i64) {
+        if self.version != version {
+            self.searches.clear();
+            self.profiles.clear();
+            self.version = version;
+        }
+    }
+}
+
+/// In-process cache of serialized species responses, keyed by catalog version.
+///
+/// Writers call `invalidate` after committing; responses are also checked against the
+/// version counter so writes made elsewhere are never served stale.
+#[derive(Clone, Default)]
+pub struct CatalogCache {
+    inner: Arc<RwLock<CacheState>>,
+}
+
+impl CatalogCache {
+    pub fn search(&self, version: i64, key: &str) -> Option<Bytes> {
+        let state = self.inner.read().ok()?;
+        (state.version == version).then(|| state.searches.get(key).cloned()).flatten()
+    }
+
+    pub fn store_search(&self, version: i64, key: String, body: Bytes) {
+        if let Ok(mut state) = self.inner.write() {
+            state.sync(version);
+            if state.searches.len() >= MAX_CACHED_RESPONSES {
+                state.searches.clear();
+            }
+            state.searches.insert(key, body);
+        }
+    }
+
+    pub fn profile(&self, version: i64, id: i32) -> Option<Bytes> {
+        let state = self.inner.read().ok()?;
+        (state.version == version).then(|| state.profiles.get(&id).cloned()).flatten()
+    }
+
+    pub fn store_profile(&self, version: i64, id: i32, body: Bytes) {
+        if let Ok(mut state) = self.inner.write() {
+            state.sync(version);
+            if state.profiles.len() >= MAX_CACHED_RESPONSES {
+                state.profiles.clear();
+            }
+            state.profiles.insert(id, body);
+        }
+    }
+
+    pub fn invalidate(&self) {
+        if let Ok(mut state) = self.inner.write() {
+            *state = CacheState::default();
+        }
+        tracing::info!(operation = "catalog_cache_invalidate", "Species catalog cache invalidated");
+    }
+}
@@ -6,8 +6,8 @@ use shuttle_axum::axum::{
 use sqlx::{postgres::PgRow, Row};
 
 use crate::{
-    ApiError, AppState, FeedingSchedule, FeedingScheduleParams,
-    Species, SpeciesQuery,
+    feeding, foods, taxonomy, ApiError, AppState, FeedingConditions, FeedingFactor, FeedingSchedule,
+    FeedingScheduleParams, Species, SpeciesQuery, Tank,
 };
 
 // ⚠️ CHALLENGE #2: DATABASE QUERY OPTIMIZATION ⚠️
@@ -31,6 +31,7 @@ pub async fn get_species(
         operation = "species_catalog_search",
         search_by_name = params.name.is_some(),
         search_by_scientific_name = params.scientific_name.is_some(),
+        search_by_taxonomy = taxonomy::has_taxon_filter(&params),
         "Starting species catalog search"
     );
 
@@ -53,7 +54,10 @@ pub async fn get_species(
 
     // Using case-sensitive query with LIKE (non-optimized)
     // This requires a full table scan and doesn't utilize indexes effectively
-    let species = if let Some(name) = &params.name {
+    let species = if taxonomy::has_taxon_filter(&params) {
+        // Taxonomy filters join through the hierarchy tables
+        taxonomy::fetch_species_in_taxon(&state.pool, &params).await?
+    } else if let Some(name) = &params.name {
         // Use runtime query with LIKE for case-sensitive search
         sqlx::query("SELECT * FROM species WHERE name LIKE $1")
             .bind(format!("%{}%", name))
@@ -67,6 +71,15 @@ pub async fn get_species(
                 min_ph: row.get("min_ph"),
                 max_ph: row.get("max_ph"),
                 diet_type: row.get("diet_type"),
+                min_salinity: row.get("min_salinity"),
+                max_salinity: row.get("max_salinity"),
+                min_oxygen: row.get("min_oxygen"),
+                min_gh: row.get("min_gh"),
+                max_gh: row.get("max_gh"),
+                min_kh: row.get("min_kh"),
+                max_kh: row.get("max_kh"),
+                water_type: row.get("water_type"),
+                care_level: row.get("care_level"),
             })
             .fetch_all(&state.pool)
             .await
@@ -93,6 +106,15 @@ pub async fn get_species(
                 min_ph: row.get("min_ph"),
                 max_ph: row.get("max_ph"),
                 diet_type: row.get("diet_type"),
+                min_salinity: row.get("min_salinity"),
+                max_salinity: row.get("max_salinity"),
+                min_oxygen: row.get("min_oxygen"),
+                min_gh: row.get("min_gh"),
+                max_gh: row.get("max_gh"),
+                min_kh: row.get("min_kh"),
+                max_kh: row.get("max_kh"),
+                water_type: row.get("water_type"),
+                care_level: row.get("care_level"),
             })
             .fetch_all(&state.pool)
             .await
@@ -118,6 +140,15 @@ pub async fn get_species(
                 min_ph: row.get("min_ph"),
                 max_ph: row.get("max_ph"),
                 diet_type: row.get("diet_type"),
+                min_salinity: row.get("min_salinity"),
+                max_salinity: row.get("max_salinity"),
+                min_oxygen: row.get("min_oxygen"),
+                min_gh: row.get("min_gh"),
+                max_gh: row.get("max_gh"),
+                min_kh: row.get("min_kh"),
+                max_kh: row.get("max_kh"),
+                water_type: row.get("water_type"),
+                care_level: row.get("care_level"),
             })
             .fetch_all(&state.pool)
             .await
@@ -163,7 +194,7 @@ pub async fn get_species(
 // This function needs improved error handling for robustness
 pub async fn get_feeding_schedule(
     Path(species_id): Path<i32>,
-    Query(params): Query<FeedingScheduleParams>,
+    Query(mut params): Query<FeedingScheduleParams>,
     State(state): State<AppState>,
 ) -> Result<impl IntoResponse, ApiError> {
     // Add request ID for correlation
@@ -198,6 +229,15 @@ pub async fn get_feeding_schedule(
             min_ph: row.get("min_ph"),
             max_ph: row.get("max_ph"),
             diet_type: row.get("diet_type"),
+            min_salinity: row.get("min_salinity"),
+            max_salinity: row.get("max_salinity"),
+            min_oxygen: row.get("min_oxygen"),
+            min_gh: row.get("min_gh"),
+            max_gh: row.get("max_gh"),
+            min_kh: row.get("min_kh"),
+            max_kh: row.get("max_kh"),
+            water_type: row.get("water_type"),
+            care_level: row.get("care_level"),
         })
         .fetch_one(&state.pool)
         .await
@@ -221,9 +261,26 @@ pub async fn get_feeding_schedule(
             }
         })?;
 
-    // If tank_id is provided, fetch tank information
-    let tank_type = if let Some(tank_id) = &params.tank_id {
-        let tank = sqlx::query("SELECT tank_type FROM tanks WHERE id = $1")
+    // A custom diet must be a catalog food suited to the species' diet type
+    if let Some(custom_diet) = &params.custom_diet {
+        let food = foods::validate_custom_diet(&state.pool, &species, custom_diet)
+            .await
+            .map_err(|e| {
+                tracing::warn!(
+                    request_id = %request_id,
+                    species_id = species.id,
+                    custom_diet = %custom_diet,
+                    error.message = %e,
+                    "Rejected custom diet for feeding schedule",
+                );
+                e
+            })?;
+        params.custom_diet = Some(food.name);
+    }
+
+    // If tank_id is provided, gather the tank's volume, type, population and water temperature
+    let conditions = if let Some(tank_id) = &params.tank_id {
+        let tank = sqlx::query_as::<_, Tank>("SELECT * FROM tanks WHERE id = $1")
             .bind(tank_id)
             .fetch_optional(&state.pool)
             .await
@@ -237,14 +294,23 @@ pub async fn get_feeding_schedule(
                 ApiError::Database(e)
             })?;
 
-        // If tank exists, use its type; otherwise, use default
-        tank.map(|t| t.get("tank_type"))
+        // If tank exists, use its conditions; otherwise, use defaults
+        match tank {
+            Some(tank) => feeding::tank_feeding_conditions(&state, &tank, species.id).await?,
+            None => FeedingConditions {
+                timezone: Some(state.facility_timezone),
+                ..Default::default()
+            },
+        }
     } else {
-        None
+        FeedingConditions {
+            timezone: Some(state.facility_timezone),
+            ..Default::default()
+        }
     };
 
-    // Calculate feeding schedule based on species and tank_type
-    let schedule = calculate_feeding_schedule(&species, &params, tank_type);
+    // Calculate feeding schedule based on species and tank conditions
+    let schedule = calculate_feeding_schedule(&species, &params, &conditions);
 
     // Calculate elapsed time for logging
     let elapsed = start.elapsed().as_millis();
@@ -264,11 +330,24 @@ pub async fn get_feeding_schedule(
     Ok(Json(schedule))
 }
 
+// Typical daily ration for one adult animal, by diet type
+fn daily_ration_per_animal_grams(diet_type: &str) -> f64 {
+    match diet_type {
+        "carnivore" => 0.06,
+        "herbivore" => 0.03,
+        "filter feeder" => 0.02,
+        _ => 0.04,
+    }
+}
+
+// Uneaten food above this load per liter of water starts to foul the tank
+pub(crate) const MAX_DAILY_GRAMS_PER_LITER: f64 = 0.02;
+
 // Helper function moved from main.rs
 pub fn calculate_feeding_schedule(
     species: &Species,
     params: &FeedingScheduleParams,
-    tank_type: Option<String>,
+    conditions: &FeedingConditions,
 ) -> FeedingSchedule {
     // Use custom diet if provided
     let food_type = if let Some(diet) = &params.custom_diet {
@@ -290,19 +369,118 @@ pub fn calculate_feeding_schedule(
         _ => vec!["09:00".to_string(), "17:00".to_string()],
     };
 
-    // Adjust amount based on species parameters
-    let base_amount = (species.min_temperature + species.max_temperature) / 10.0;
-    let amount_grams = match tank_type.as_deref() {
-        Some("reef") => base_amount * 0.8,   // Less food in reef tanks
-        Some("planted") => base_amount * 1.1, // More food in planted tanks
-        Some("brackish") => base_amount * 0.9, // Slightly less in brackish
-        _ => base_amount,                      // Default amount
+    let ration = daily_ration_per_animal_grams(&species.diet_type);
+    let mut factors = Vec::new();
+
+    // Population: every animal needs its own ration
+    let population = conditions.population.unwrap_or(1).max(0);
+    factors.push(FeedingFactor {
+        factor: "population",
+        multiplier: population as f64,
+        reason: match conditions.population {
+            Some(count) => format!(
+                "{} animals at {:.2} g/day each ({} diet)",
+                count, ration, species.diet_type
+            ),
+            None => format!(
+                "Population unknown, sized for a single animal at {:.2} g/day ({} diet)",
+                ration, species.diet_type
+            ),
+        },
+    });
+
+    // Tank type: nutrient-sensitive setups get less, planted tanks can absorb more
+    let (tank_type_multiplier, tank_type_reason) = match conditions.tank_type.as_deref() {
+        Some("reef") => (0.8, "Reef tanks are nutrient-sensitive; feeding reduced"),
+        Some("nano") => (0.85, "Nano tanks accumulate waste quickly; feeding reduced"),
+        Some("planted") => (1.1, "Plants absorb extra nutrients; feeding increased"),
+        Some("brackish") => (0.9, "Brackish water breaks food down slowly; feeding slightly reduced"),
+        Some(_) => (1.0, "No adjustment for this tank type"),
+        None => (1.0, "Tank type unknown; no adjustment"),
     };
+    factors.push(FeedingFactor {
+        factor: "tank_type",
+        multiplier: tank_type_multiplier,
+        reason: tank_type_reason.to_string(),
+    });
+
+    // Water temperature: crustacean metabolism (and appetite) rises across the tolerated range
+    let (temperature_multiplier, temperature_reason) = match conditions.water_temperature {
+        Some(t) if t < species.min_temperature => (
+            0.5,
+            format!(
+                "Water at {:.1}°C is below the {:.1}°C minimum; metabolism slows, feeding halved",
+                t, species.min_temperature
+            ),
+        ),
+        Some(t) if t > species.max_temperature => (
+            0.7,
+            format!(
+                "Water at {:.1}°C is above the {:.1}°C maximum; feeding reduced to protect oxygen levels",
+                t, species.max_temperature
+            ),
+        ),
+        Some(t) => {
+            let range = (species.max_temperature - species.min_temperature).max(f64::EPSILON);
+            let position = (t - species.min_temperature) / range;
+            (
+                0.8 + 0.4 * position,
+                format!(
+                    "Water at {:.1}°C sits {:.0}% into the {:.1}–{:.1}°C range",
+                    t,
+                    position * 100.0,
+                    species.min_temperature,
+                    species.max_temperature
+                ),
+            )
+        }
+        None => (1.0, "No current temperature reading; no adjustment".to_string()),
+    };
+    factors.push(FeedingFactor {
+        factor: "water_temperature",
+        multiplier: temperature_multiplier,
+        reason: temperature_reason,
+    });
+
+    let uncapped = ration * population as f64 * tank_type_multiplier * temperature_multiplier;
+
+    // Volume: cap the daily amount so uneaten food doesn't overwhelm the water
+    let (volume_multiplier, volume_reason) = match conditions.volume_liters {
+        Some(volume) => {
+            let cap = volume * MAX_DAILY_GRAMS_PER_LITER;
+            if uncapped > cap {
+                (
+                    cap / uncapped,
+                    format!(
+                        "Capped at {:.2} g/day for {:.0} L of water ({:.2} g/L)",
+                        cap, volume, MAX_DAILY_GRAMS_PER_LITER
+                    ),
+                )
+            } else {
+                (
+                    1.0,
+                    format!("Within the {:.2} g/day limit for {:.0} L of water", cap, volume),
+                )
+            }
+        }
+        None => (1.0, "Tank volume unknown; no cap applied".to_string()),
+    };
+    factors.push(FeedingFactor {
+        factor: "tank_volume",
+        multiplier: volume_multiplier,
+        reason: volume_reason,
+    });
+
+    let daily_amount_grams = uncapped * volume_multiplier;
+    let amount_grams = daily_amount_grams / feeding_times.len() as f64;
 
     FeedingSchedule {
         species_id: species.id,
         feeding_times,
+        timezone: conditions.timezone.unwrap_or(chrono_tz::Tz::UTC).name().to_string(),
         food_type,
         amount_grams,
+        daily_amount_grams,
+        factors,
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,469 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use shuttle_axum::axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Json,
+};
+use sqlx::PgPool;
+use tracing::Instrument;
+
+use crate::{
+    challenges::{calculate_feeding_schedule, MAX_DAILY_GRAMS_PER_LITER},
+    inhabitants::fetch_active_inhabitants,
+    ApiError, AppState, FeedingConditions, FeedingFactor, FeedingSchedule, FeedingScheduleParams,
+    Species, Tank,
+};
+
+#[derive(Deserialize)]
+pub struct TankFeedingQuery {
+    tank_id: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct FoodPortion {
+    pub food_type: String,
+    pub amount_grams: f64,
+}
+
+/// Everything that goes into the tank at one time of day.
+#[derive(Serialize)]
+pub struct FeedingSlot {
+    pub time: String,
+    pub foods: Vec<FoodPortion>,
+    pub amount_grams: f64,
+}
+
+/// Daily totals for one food type across every species that eats it.
+#[derive(Serialize)]
+pub struct FoodSummary {
+    pub food_type: String,
+    pub species_ids: Vec<i32>,
+    pub feeding_times: Vec<String>,
+    pub daily_amount_grams: f64,
+}
+
+#[derive(Serialize)]
+pub struct TankFeedingSchedule {
+    pub tank_id: String,
+    pub tank_name: String,
+    pub tank_type: String,
+    /// IANA timezone the local "HH:MM" feeding times are expressed in
+    pub timezone: String,
+    pub feeding_times: Vec<String>,
+    pub feedings: Vec<FeedingSlot>,
+    pub foods: Vec<FoodSummary>,
+    pub daily_amount_grams: f64,
+    pub species: Vec<FeedingSchedule>,
+}
+
+/// Merges per-species schedules into a single tank timeline.
+///
+/// Feeding times are combined into one sorted list, portions that share a time and
+/// food type are added together, and each food type gets a daily total. The volume
+/// cap applies to the tank as a whole, so when the species together exceed it every
+/// portion is scaled down by the same share.
+pub fn merge_feeding_schedules(
+    tank: &Tank,
+    timezone: Tz,
+    mut schedules: Vec<FeedingSchedule>,
+) -> TankFeedingSchedule {
+    let total: f64 = schedules.iter().map(|s| s.daily_amount_grams).sum();
+    let cap = tank.volume * MAX_DAILY_GRAMS_PER_LITER;
+    if total > cap {
+        let share = cap / total;
+        for schedule in &mut schedules {
+            schedule.daily_amount_grams *= share;
+            schedule.amount_grams *= share;
+            schedule.factors.push(FeedingFactor {
+                factor: "tank_share",
+                multiplier: share,
+                reason: format!(
+                    "All species together need {:.2} g/day; scaled down to share the {:.2} g/day limit for {:.0} L of water",
+                    total, cap, tank.volume
+                ),
+            });
+        }
+    }
+
+    let mut slots: BTreeMap<&str, BTreeMap<&str, f64>> = BTreeMap::new();
+    let mut foods: BTreeMap<&str, (BTreeSet<i32>, BTreeSet<&str>, f64)> = BTreeMap::new();
+
+    for schedule in &schedules {
+        for time in &schedule.feeding_times {
+            *slots
+                .entry(time.as_str())
+                .or_default()
+                .entry(schedule.food_type.as_str())
+                .or_default() += schedule.amount_grams;
+        }
+
+        let (species_ids, times, daily_amount) = foods.entry(schedule.food_type.as_str()).or_default();
+        species_ids.insert(schedule.species_id);
+        times.extend(schedule.feeding_times.iter().map(String::as_str));
+        *daily_amount += schedule.daily_amount_grams;
+    }
+
+    let feedings: Vec<FeedingSlot> = slots
+        .into_iter()
+        .map(|(time, portions)| {
+            let foods: Vec<FoodPortion> = portions
+                .into_iter()
+                .map(|(food_type, amount_grams)| FoodPortion {
+                    food_type: food_type.to_string(),
+                    amount_grams,
+                })
+                .collect();
+            FeedingSlot {
+                time: time.to_string(),
+                amount_grams: foods.iter().map(|f| f.amount_grams).sum(),
+                foods,
+            }
+        })
+        .collect();
+
+    let foods: Vec<FoodSummary> = foods
+        .into_iter()
+        .map(|(food_type, (species_ids, times, daily_amount_grams))| FoodSummary {
+            food_type: food_type.to_string(),
+            species_ids: species_ids.into_iter().collect(),
+            feeding_times: times.into_iter().map(str::to_string).collect(),
+            daily_amount_grams,
+        })
+        .collect();
+
+    TankFeedingSchedule {
+        tank_id: tank.id.clone(),
+        tank_name: tank.name.clone(),
+        tank_type: tank.tank_type.clone(),
+        timezone: timezone.name().to_string(),
+        feeding_times: feedings.iter().map(|slot| slot.time.clone()).collect(),
+        daily_amount_grams: foods.iter().map(|f| f.daily_amount_grams).sum(),
+        feedings,
+        foods,
+        species: schedules,
+    }
+}
+
+/// The timezone a tank's feeding times are expressed in.
+///
+/// Tanks without their own timezone, or with one that no longer parses, follow the facility.
+pub fn tank_timezone(tank: &Tank, facility_timezone: Tz) -> Tz {
+    match tank.timezone.as_deref().map(str::parse::<Tz>) {
+        Some(Ok(timezone)) => timezone,
+        Some(Err(e)) => {
+            tracing::warn!(
+                tank_id = %tank.id,
+                timezone = ?tank.timezone,
+                error = %e,
+                "Invalid tank timezone; using facility timezone"
+            );
+            facility_timezone
+        }
+        None => facility_timezone,
+    }
+}
+
+/// Latest water temperature for a tank from aqua-monitor.
+///
+/// Feeding schedules are still useful without a live reading, so failures are
+/// logged and treated as "unknown" rather than failing the request.
+pub async fn current_water_temperature(state: &AppState, tank_id: &str) -> Option<f64> {
+    match state.aqua_monitor.recent_readings(tank_id).await {
+        Ok(readings) => readings
+            .iter()
+            .max_by_key(|r| r.timestamp)
+            .map(|r| r.temperature),
+        Err(e) => {
+            tracing::warn!(
+                tank_id = %tank_id,
+                error = %e,
+                "Could not fetch water temperature from aqua-monitor; feeding amounts won't be temperature-adjusted"
+            );
+            None
+        }
+    }
+}
+
+/// Collects the conditions that scale a species' feeding amount in a given tank.
+pub async fn tank_feeding_conditions(
+    state: &AppState,
+    tank: &Tank,
+    species_id: i32,
+) -> Result<FeedingConditions, ApiError> {
+    let population: Option<i32> = sqlx::query_scalar(
+        "SELECT count FROM tank_inhabitants WHERE tank_id = $1 AND species_id = $2 AND removed_at IS NULL",
+    )
+    .bind(&tank.id)
+    .bind(species_id)
+    .fetch_optional(&state.pool)
+    .await?;
+
+    Ok(FeedingConditions {
+        timezone: Some(tank_timezone(tank, state.facility_timezone)),
+        tank_type: Some(tank.tank_type.clone()),
+        volume_liters: Some(tank.volume),
+        population,
+        water_temperature: current_water_temperature(state, &tank.id).await,
+    })
+}
+
+/// Calculates the schedule of every inhabitant of a tank and merges them.
+pub async fn build_tank_feeding_schedule(
+    state: &AppState,
+    tank: &Tank,
+) -> Result<TankFeedingSchedule, ApiError> {
+    let inhabitants = fetch_active_inhabitants(&state.pool, &tank.id).await?;
+    let species_ids: Vec<i32> = inhabitants.iter().map(|i| i.species_id).collect();
+
+    let species = sqlx::query_as::<_, Species>("SELECT * FROM species WHERE id = ANY($1) ORDER BY id")
+        .bind(&species_ids)
+        .fetch_all(&state.pool)
+        .await?;
+
+    // One reading serves every species in the tank
+    let water_temperature = current_water_temperature(state, &tank.id).await;
+    let timezone = tank_timezone(tank, state.facility_timezone);
+
+    let params = FeedingScheduleParams {
+        tank_id: Some(tank.id.clone()),
+        ..Default::default()
+    };
+    let schedules = species
+        .iter()
+        .map(|s| {
+            let conditions = FeedingConditions {
+                timezone: Some(timezone),
+                tank_type: Some(tank.tank_type.clone()),
+                volume_liters: Some(tank.volume),
+                population: inhabitants
+                    .iter()
+                    .find(|i| i.species_id == s.id)
+                    .map(|i| i.count),
+                water_temperature,
+            };
+            calculate_feeding_schedule(s, &params, &conditions)
+        })
+        .collect();
+
+    Ok(merge_feeding_schedules(tank, timezone, schedules))
+}
+
+pub async fn fetch_tank(pool: &PgPool, tank_id: &str) -> Result<Tank, ApiError> {
+    sqlx::query_as::<_, Tank>("SELECT * FROM tanks WHERE id = $1")
+        .bind(tank_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| ApiError::TankNotFound(tank_id.to_string()))
+}
+
+/// Returns the merged feeding schedule for every tank, or a single tank via `?tank_id=`.
+pub async fn get_feeding_schedules(
+    Query(params): Query<TankFeedingQuery>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    let start = std::time::Instant::now();
+
+    let span = tracing::info_span!("tank_feeding_schedule_generation", %request_id);
+    async move {
+        let tanks = match &params.tank_id {
+            Some(tank_id) => vec![fetch_tank(&state.pool, tank_id).await?],
+            None => {
+                sqlx::query_as::<_, Tank>("SELECT * FROM tanks ORDER BY id")
+                    .fetch_all(&state.pool)
+                    .await?
+            }
+        };
+
+        let mut schedules = Vec::with_capacity(tanks.len());
+        for tank in &tanks {
+            schedules.push(build_tank_feeding_schedule(&state, tank).await?);
+        }
+
+        tracing::info!(
+            request_id = %request_id,
+            tanks_scheduled = schedules.len(),
+            schedule_calc_time_ms = start.elapsed().as_millis() as f64,
+            operation_status = "success",
+            "Tank feeding schedule generation completed"
+        );
+
+        Ok::<_, ApiError>(Json(schedules))
+    }
+    .instrument(span)
+    .await
+}
+
+/// Returns the merged feeding schedule for a single tank.
+pub async fn get_tank_feeding_schedule(
+    Path(tank_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let tank = fetch_tank(&state.pool, &tank_id).await?;
+    let schedule = build_tank_feeding_schedule(&state, &tank).await?;
+
+    tracing::info!(
+        tank_id = %tank_id,
+        species_count = schedule.species.len(),
+        feeding_times_per_day = schedule.feeding_times.len(),
+        daily_amount_grams = schedule.daily_amount_grams,
+        operation_status = "success",
+        "Tank feeding schedule generation completed"
+    );
+
+    Ok(Json(schedule))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn species(diet_type: &str) -> Species {
+        Species {
+            diet_type: diet_type.to_string(),
+            max_temperature: 30.0,
+            ..test_support::species(7, "Cherry Shrimp")
+        }
+    }
+
+    fn multipliers(schedule: &FeedingSchedule) -> Vec<(&'static str, f64)> {
+        schedule.factors.iter().map(|f| (f.factor, f.multiplier)).collect()
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "expected {}, got {}", expected, actual);
+    }
+
+    #[test]
+    fn unknown_conditions_leave_a_single_ration() {
+        let schedule = calculate_feeding_schedule(
+            &species("carnivore"),
+            &FeedingScheduleParams::default(),
+            &FeedingConditions::default(),
+        );
+
+        assert_eq!(schedule.food_type, "bloodworms");
+        assert_eq!(schedule.feeding_times, vec!["08:00", "20:00"]);
+        assert_eq!(schedule.timezone, "UTC");
+        assert_eq!(
+            multipliers(&schedule),
+            vec![("population", 1.0), ("tank_type", 1.0), ("water_temperature", 1.0), ("tank_volume", 1.0)]
+        );
+        assert_close(schedule.daily_amount_grams, 0.06);
+        assert_close(schedule.amount_grams, 0.03);
+    }
+
+    #[test]
+    fn population_tank_type_and_temperature_scale_the_ration() {
+        let conditions = FeedingConditions {
+            timezone: Some(chrono_tz::Europe::Berlin),
+            tank_type: Some("planted".to_string()),
+            volume_liters: Some(100.0),
+            population: Some(10),
+            // Three quarters into the 20–30°C range
+            water_temperature: Some(27.5),
+        };
+        let schedule = calculate_feeding_schedule(&species("omnivore"), &FeedingScheduleParams::default(), &conditions);
+
+        assert_eq!(schedule.food_type, "flake food");
+        assert_eq!(schedule.timezone, "Europe/Berlin");
+        let factors = multipliers(&schedule);
+        assert_eq!(factors[0], ("population", 10.0));
+        assert_eq!(factors[1], ("tank_type", 1.1));
+        assert_close(factors[2].1, 1.1);
+        assert_eq!(factors[3], ("tank_volume", 1.0));
+        assert_close(schedule.daily_amount_grams, 0.04 * 10.0 * 1.1 * 1.1);
+        assert_close(schedule.amount_grams, schedule.daily_amount_grams / 2.0);
+    }
+
+    #[test]
+    fn water_outside_the_range_cuts_feeding() {
+        let params = FeedingScheduleParams::default();
+        let at = |temperature: f64| {
+            let conditions = FeedingConditions {
+                water_temperature: Some(temperature),
+                ..FeedingConditions::default()
+            };
+            let schedule = calculate_feeding_schedule(&species("herbivore"), &params, &conditions);
+            schedule.factors[2].multiplier
+        };
+
+        assert_eq!(at(15.0), 0.5);
+        assert_eq!(at(32.0), 0.7);
+        assert_close(at(20.0), 0.8);
+        assert_close(at(30.0), 1.2);
+    }
+
+    #[test]
+    fn tank_volume_caps_the_daily_amount() {
+        let conditions = FeedingConditions {
+            volume_liters: Some(50.0),
+            population: Some(100),
+            ..FeedingConditions::default()
+        };
+        let schedule = calculate_feeding_schedule(&species("herbivore"), &FeedingScheduleParams::default(), &conditions);
+
+        // 100 animals at 0.03 g/day is 3 g, capped at 0.02 g/L for 50 L
+        assert_close(schedule.factors[3].multiplier, 1.0 / 3.0);
+        assert_close(schedule.daily_amount_grams, 1.0);
+        assert_eq!(schedule.feeding_times.len(), 3);
+        assert_close(schedule.amount_grams, 1.0 / 3.0);
+    }
+
+    #[test]
+    fn species_share_the_tank_volume_cap() {
+        let tank = Tank {
+            id: "Tank-N1".to_string(),
+            name: "Nano".to_string(),
+            tank_type: "nano".to_string(),
+            volume: 20.0,
+            description: None,
+            timezone: None,
+            cleaning_interval_days: 7,
+            last_cleaned_at: None,
+        };
+        let schedule_for = |id: i32, diet_type: &str, population: i32| {
+            let conditions = FeedingConditions {
+                volume_liters: Some(tank.volume),
+                population: Some(population),
+                ..FeedingConditions::default()
+            };
+            let mut species = species(diet_type);
+            species.id = id;
+            calculate_feeding_schedule(&species, &FeedingScheduleParams::default(), &conditions)
+        };
+        // Each stays under the 0.4 g/day cap for 20 L on its own: 0.3 g and 0.24 g
+        let schedules = vec![schedule_for(1, "herbivore", 10), schedule_for(2, "carnivore", 4)];
+        assert!(schedules.iter().all(|s| s.daily_amount_grams <= 0.4));
+
+        let merged = merge_feeding_schedules(&tank, chrono_tz::Tz::UTC, schedules);
+
+        assert!(merged.daily_amount_grams <= tank.volume * MAX_DAILY_GRAMS_PER_LITER + 1e-9);
+        assert_close(merged.daily_amount_grams, 0.4);
+        assert_close(merged.foods.iter().map(|f| f.daily_amount_grams).sum(), 0.4);
+        // Both portions shrink by the same share, keeping their 0.3 : 0.24 ratio
+        let share = 0.4 / 0.54;
+        assert_close(merged.species[0].daily_amount_grams, 0.3 * share);
+        assert_close(merged.species[1].daily_amount_grams, 0.24 * share);
+        assert_close(merged.species[0].amount_grams, 0.1 * share);
+        let last = merged.species[1].factors.last().unwrap();
+        assert_eq!(last.factor, "tank_share");
+        assert_close(last.multiplier, share);
+    }
+
+    #[test]
+    fn custom_diet_replaces_the_default_food() {
+        let params = FeedingScheduleParams {
+            custom_diet: Some("blanched spinach".to_string()),
+            ..FeedingScheduleParams::default()
+        };
+        let schedule = calculate_feeding_schedule(&species("herbivore"), &params, &FeedingConditions::default());
+
+        assert_eq!(schedule.food_type, "blanched spinach");
+        assert_eq!(schedule.feeding_times, vec!["08:00", "13:00", "18:00"]);
+    }
+}
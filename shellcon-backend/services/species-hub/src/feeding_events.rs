@@ -0,0 +1,428 @@
+use chrono::{DateTime, Duration, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use shuttle_axum::axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+
+use crate::{
+    feeding::{build_tank_feeding_schedule, fetch_tank, TankFeedingSchedule},
+    inhabitants::ensure_tank_exists,
+    inventory,
+    ApiError, AppState, Tank,
+};
+
+// A feeding this long after its slot without a logged event is overdue
+const OVERDUE_GRACE_MINUTES: i64 = 60;
+// Feedings logged this early still count towards the next slot
+const EARLY_FEEDING_MINUTES: i64 = 60;
+// More than this share of the scheduled daily amount within 24 hours is overfeeding
+const OVERFED_RATIO: f64 = 1.25;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedingStatus {
+    Normal,
+    Overdue,
+    Overfed,
+    Unknown,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+pub struct FeedingEvent {
+    pub id: i32,
+    pub tank_id: String,
+    pub species_id: Option<i32>,
+    pub food_type: String,
+    pub amount_grams: f64,
+    pub fed_at: DateTime<Utc>,
+    pub fed_by: String,
+}
+
+#[derive(Deserialize)]
+pub struct LogFeedingRequest {
+    species_id: Option<i32>,
+    food_type: String,
+    amount_grams: f64,
+    fed_at: Option<DateTime<Utc>>,
+    fed_by: String,
+}
+
+#[derive(Deserialize)]
+pub struct FeedingEventQuery {
+    since: Option<DateTime<Utc>>,
+    limit: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct TankFeedingStatus {
+    pub tank_id: String,
+    pub status: FeedingStatus,
+    pub reasons: Vec<String>,
+    pub last_fed_at: Option<DateTime<Utc>>,
+    pub last_due_at: Option<DateTime<Utc>>,
+    pub next_due_at: Option<DateTime<Utc>>,
+    pub fed_grams_last_24h: f64,
+    pub scheduled_daily_grams: f64,
+}
+
+/// Expands local "HH:MM" feeding times into the most recent slot at or before `now` and the next one after it.
+///
+/// Slots are built on the tank's local calendar so they stay at the same wall-clock
+/// time across DST changes; a time skipped by a DST jump is dropped for that day.
+fn surrounding_slots(
+    feeding_times: &[String],
+    timezone: Tz,
+    now: DateTime<Utc>,
+) -> (Option<DateTime<Utc>>, Option<DateTime<Utc>>) {
+    let times: Vec<NaiveTime> = feeding_times
+        .iter()
+        .filter_map(|t| NaiveTime::parse_from_str(t, "%H:%M").ok())
+        .collect();
+
+    let today = now.with_timezone(&timezone).date_naive();
+    let slots: Vec<DateTime<Utc>> = [today - Duration::days(1), today, today + Duration::days(1)]
+        .into_iter()
+        .flat_map(|day| times.iter().map(move |time| day.and_time(*time)))
+        .filter_map(|local| timezone.from_local_datetime(&local).earliest())
+        .map(|slot| slot.with_timezone(&Utc))
+        .collect();
+
+    let last_due = slots.iter().filter(|slot| **slot <= now).max().copied();
+    let next_due = slots.iter().filter(|slot| **slot > now).min().copied();
+    (last_due, next_due)
+}
+
+/// Compares logged feedings against a tank's generated schedule.
+pub fn assess_feeding_status(
+    schedule: &TankFeedingSchedule,
+    last_fed_at: Option<DateTime<Utc>>,
+    fed_grams_last_24h: f64,
+    now: DateTime<Utc>,
+) -> TankFeedingStatus {
+    let timezone = schedule.timezone.parse::<Tz>().unwrap_or(Tz::UTC);
+    let (last_due_at, next_due_at) = surrounding_slots(&schedule.feeding_times, timezone, now);
+    let mut reasons = Vec::new();
+
+    let status = if schedule.feeding_times.is_empty() {
+        reasons.push("Tank has no inhabitants with a feeding schedule".to_string());
+        FeedingStatus::Unknown
+    } else {
+        let overfed = schedule.daily_amount_grams > 0.0
+            && fed_grams_last_24h > schedule.daily_amount_grams * OVERFED_RATIO;
+        if overfed {
+            reasons.push(format!(
+                "{:.2} g fed in the last 24 hours against a scheduled {:.2} g/day",
+                fed_grams_last_24h, schedule.daily_amount_grams
+            ));
+        }
+
+        let overdue = match last_due_at {
+            Some(due) if now - due > Duration::minutes(OVERDUE_GRACE_MINUTES) => {
+                let fed_for_slot = last_fed_at
+                    .is_some_and(|fed| fed >= due - Duration::minutes(EARLY_FEEDING_MINUTES));
+                !fed_for_slot
+            }
+            _ => false,
+        };
+        if overdue {
+            reasons.push(match (last_due_at, last_fed_at) {
+                (Some(due), Some(fed)) => format!(
+                    "Feeding due at {} was missed; last feeding logged at {}",
+                    due.with_timezone(&timezone).format("%H:%M %Z"),
+                    fed.with_timezone(&timezone).to_rfc3339()
+                ),
+                (Some(due), None) => format!(
+                    "Feeding due at {} was missed; no feedings have been logged",
+                    due.with_timezone(&timezone).format("%H:%M %Z")
+                ),
+                _ => "Feeding is overdue".to_string(),
+            });
+        }
+
+        if overfed {
+            FeedingStatus::Overfed
+        } else if overdue {
+            FeedingStatus::Overdue
+        } else {
+            FeedingStatus::Normal
+        }
+    };
+
+    TankFeedingStatus {
+        tank_id: schedule.tank_id.clone(),
+        status,
+        reasons,
+        last_fed_at,
+        last_due_at,
+        next_due_at,
+        fed_grams_last_24h,
+        scheduled_daily_grams: schedule.daily_amount_grams,
+    }
+}
+
+async fn tank_feeding_status(state: &AppState, tank: &Tank) -> Result<TankFeedingStatus, ApiError> {
+    let schedule = build_tank_feeding_schedule(state, tank).await?;
+    let now = Utc::now();
+
+    let (last_fed_at, fed_grams_last_24h): (Option<DateTime<Utc>>, Option<f64>) = sqlx::query_as(
+        "SELECT MAX(fed_at), SUM(amount_grams) FILTER (WHERE fed_at > $2) \
+         FROM feeding_events WHERE tank_id = $1 AND fed_at <= $3",
+    )
+    .bind(&tank.id)
+    .bind(now - Duration::hours(24))
+    .bind(now)
+    .fetch_one(&state.pool)
+    .await?;
+
+    Ok(assess_feeding_status(
+        &schedule,
+        last_fed_at,
+        fed_grams_last_24h.unwrap_or(0.0),
+        now,
+    ))
+}
+
+/// Records a feeding that has taken place.
+pub async fn log_feeding(
+    Path(tank_id): Path<String>,
+    State(state): State<AppState>,
+    Json(request): Json<LogFeedingRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    if request.amount_grams <= 0.0 || !request.amount_grams.is_finite() {
+        return Err(ApiError::InvalidInput(
+            "amount_grams must be a positive number".to_string(),
+        ));
+    }
+    if request.food_type.trim().is_empty() {
+        return Err(ApiError::InvalidInput("food_type is required".to_string()));
+    }
+    if request.fed_by.trim().is_empty() {
+        return Err(ApiError::InvalidInput("fed_by is required".to_string()));
+    }
+
+    // The feeding and the matching stock depletion are recorded together
+    let mut tx = state.pool.begin().await?;
+    ensure_tank_exists(&mut tx, &tank_id).await?;
+
+    if let Some(species_id) = request.species_id {
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM species WHERE id = $1)")
+            .bind(species_id)
+            .fetch_one(&mut *tx)
+            .await?;
+        if !exists {
+            return Err(ApiError::SpeciesNotFound(format!(
+                "Species with ID {} not found",
+                species_id
+            )));
+        }
+    }
+
+    let event = sqlx::query_as::<_, FeedingEvent>(
+        "INSERT INTO feeding_events (tank_id, species_id, food_type, amount_grams, fed_at, fed_by) \
+         VALUES ($1, $2, $3, $4, COALESCE($5, NOW()), $6) \
+         RETURNING *",
+    )
+    .bind(&tank_id)
+    .bind(request.species_id)
+    .bind(request.food_type.trim())
+    .bind(request.amount_grams)
+    .bind(request.fed_at)
+    .bind(request.fed_by.trim())
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let depletion = inventory::deplete_for_feeding(&mut tx, &event).await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        tank_id = %tank_id,
+        feeding_event_id = event.id,
+        food_type = %event.food_type,
+        amount_grams = event.amount_grams,
+        fed_by = %event.fed_by,
+        inventory_depleted = depletion.is_some(),
+        operation = "log_feeding",
+        "Feeding logged"
+    );
+
+    Ok((StatusCode::CREATED, Json(event)))
+}
+
+/// Lists logged feedings for a tank, newest first.
+pub async fn list_feedings(
+    Path(tank_id): Path<String>,
+    Query(params): Query<FeedingEventQuery>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut conn = state.pool.acquire().await?;
+    ensure_tank_exists(&mut conn, &tank_id).await?;
+
+    let limit = params.limit.unwrap_or(50).clamp(1, 500);
+    let events = sqlx::query_as::<_, FeedingEvent>(
+        "SELECT * FROM feeding_events \
+         WHERE tank_id = $1 AND ($2::timestamptz IS NULL OR fed_at >= $2) \
+         ORDER BY fed_at DESC LIMIT $3",
+    )
+    .bind(&tank_id)
+    .bind(params.since)
+    .bind(limit)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(Json(events))
+}
+
+/// Reports whether a tank's feedings are on schedule.
+pub async fn get_tank_feeding_status(
+    Path(tank_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let tank = fetch_tank(&state.pool, &tank_id).await?;
+    let status = tank_feeding_status(&state, &tank).await?;
+
+    tracing::info!(
+        tank_id = %tank_id,
+        feeding_status = ?status.status,
+        operation = "get_tank_feeding_status",
+        "Feeding status assessed"
+    );
+
+    Ok(Json(status))
+}
+
+/// Reports feeding status for every tank.
+pub async fn get_feeding_status(State(state): State<AppState>) -> Result<impl IntoResponse, ApiError> {
+    let tanks = sqlx::query_as::<_, Tank>("SELECT * FROM tanks ORDER BY id")
+        .fetch_all(&state.pool)
+        .await?;
+
+    let mut statuses = Vec::with_capacity(tanks.len());
+    for tank in &tanks {
+        statuses.push(tank_feeding_status(&state, tank).await?);
+    }
+
+    Ok(Json(statuses))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::Europe::Berlin;
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        value.parse().unwrap()
+    }
+
+    fn times(values: &[&str]) -> Vec<String> {
+        values.iter().map(|t| t.to_string()).collect()
+    }
+
+    #[test]
+    fn slots_keep_wall_clock_time_across_spring_forward() {
+        // Berlin moves from UTC+1 to UTC+2 at 02:00 on 30 March 2025
+        let (last, next) = surrounding_slots(&times(&["08:00"]), Berlin, utc("2025-03-30T12:00:00Z"));
+
+        assert_eq!(last, Some(utc("2025-03-30T06:00:00Z")));
+        assert_eq!(next, Some(utc("2025-03-31T06:00:00Z")));
+
+        let (last, _) = surrounding_slots(&times(&["08:00"]), Berlin, utc("2025-03-30T06:30:00Z"));
+        assert_eq!(last, Some(utc("2025-03-30T06:00:00Z")));
+        let (last, _) = surrounding_slots(&times(&["08:00"]), Berlin, utc("2025-03-30T05:30:00Z"));
+        assert_eq!(last, Some(utc("2025-03-29T07:00:00Z")));
+    }
+
+    #[test]
+    fn time_skipped_by_spring_forward_is_dropped_for_that_day() {
+        let (last, next) = surrounding_slots(&times(&["02:30"]), Berlin, utc("2025-03-30T12:00:00Z"));
+
+        assert_eq!(last, Some(utc("2025-03-29T01:30:00Z")));
+        assert_eq!(next, Some(utc("2025-03-31T00:30:00Z")));
+    }
+
+    #[test]
+    fn repeated_time_at_fall_back_uses_the_first_occurrence() {
+        // 02:30 happens twice on 26 October 2025: 00:30 UTC in CEST, then 01:30 UTC in CET
+        let (last, next) = surrounding_slots(&times(&["02:30"]), Berlin, utc("2025-10-26T01:00:00Z"));
+
+        assert_eq!(last, Some(utc("2025-10-26T00:30:00Z")));
+        assert_eq!(next, Some(utc("2025-10-27T01:30:00Z")));
+    }
+
+    #[test]
+    fn unparseable_times_are_ignored() {
+        let (last, next) = surrounding_slots(&times(&["noon", "12:00"]), Tz::UTC, utc("2025-06-01T13:00:00Z"));
+
+        assert_eq!(last, Some(utc("2025-06-01T12:00:00Z")));
+        assert_eq!(next, Some(utc("2025-06-02T12:00:00Z")));
+        assert_eq!(surrounding_slots(&[], Tz::UTC, utc("2025-06-01T13:00:00Z")), (None, None));
+    }
+
+    fn schedule(feeding_times: &[&str]) -> TankFeedingSchedule {
+        TankFeedingSchedule {
+            tank_id: "Tank-A1".to_string(),
+            tank_name: "Reef Display".to_string(),
+            tank_type: "reef".to_string(),
+            timezone: "UTC".to_string(),
+            feeding_times: times(feeding_times),
+            feedings: Vec::new(),
+            foods: Vec::new(),
+            daily_amount_grams: 1.0,
+            species: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn feeding_status_against_the_schedule() {
+        // Fed at 08:00 and 20:00 UTC, 1 g/day; an hour's grace after a slot, and feedings
+        // up to an hour early count towards it
+        let cases = [
+            ("fed on time", "10:00", Some("08:05"), 0.5, FeedingStatus::Normal),
+            ("fed early within the window", "10:00", Some("07:00"), 0.5, FeedingStatus::Normal),
+            ("fed too early for the slot", "10:00", Some("06:59"), 0.5, FeedingStatus::Overdue),
+            ("never fed", "10:00", None, 0.0, FeedingStatus::Overdue),
+            ("missed but still in grace", "09:00", None, 0.0, FeedingStatus::Normal),
+            ("missed just past grace", "09:01", None, 0.0, FeedingStatus::Overdue),
+            ("at the overfeeding limit", "10:00", Some("08:00"), 1.25, FeedingStatus::Normal),
+            ("over the overfeeding limit", "10:00", Some("08:00"), 1.26, FeedingStatus::Overfed),
+            ("overfed outranks overdue", "10:00", Some("06:00"), 2.0, FeedingStatus::Overfed),
+        ];
+
+        for (name, now, last_fed_at, fed_grams, expected) in cases {
+            let at = |time: &str| utc(&format!("2025-06-01T{}:00Z", time));
+            let status = assess_feeding_status(&schedule(&["08:00", "20:00"]), last_fed_at.map(at), fed_grams, at(now));
+
+            assert_eq!(status.status, expected, "{}", name);
+            assert_eq!(status.last_due_at, Some(at("08:00")), "{}", name);
+            assert_eq!(status.next_due_at, Some(at("20:00")), "{}", name);
+            assert_eq!(status.reasons.is_empty(), expected == FeedingStatus::Normal, "{}", name);
+        }
+    }
+
+    #[test]
+    fn overfed_and_overdue_reasons_are_both_given() {
+        let now = utc("2025-06-01T10:00:00Z");
+        let status = assess_feeding_status(&schedule(&["08:00"]), Some(utc("2025-06-01T06:00:00Z")), 2.0, now);
+
+        assert_eq!(status.status, FeedingStatus::Overfed);
+        assert_eq!(
+            status.reasons,
+            vec![
+                "2.00 g fed in the last 24 hours against a scheduled 1.00 g/day".to_string(),
+                "Feeding due at 08:00 UTC was missed; last feeding logged at 2025-06-01T06:00:00+00:00".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn tanks_without_a_schedule_are_unknown() {
+        let status = assess_feeding_status(&schedule(&[]), None, 0.0, utc("2025-06-01T10:00:00Z"));
+
+        assert_eq!(status.status, FeedingStatus::Unknown);
+        assert_eq!(status.reasons, vec!["Tank has no inhabitants with a feeding schedule"]);
+        assert_eq!((status.last_due_at, status.next_due_at), (None, None));
+    }
+}
@@ -0,0 +1,169 @@
+use serde::{Deserialize, Serialize};
+use shuttle_axum::axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use sqlx::PgPool;
+
+use crate::{ApiError, AppState, Species};
+
+#[derive(Serialize, Clone, sqlx::FromRow)]
+pub struct Food {
+    pub id: i32,
+    pub name: String,
+    pub diet_types: Vec<String>,
+    pub protein_percent: f64,
+    pub portion_unit: String,
+}
+
+impl Food {
+    pub fn suits(&self, diet_type: &str) -> bool {
+        self.diet_types.iter().any(|d| d == diet_type)
+    }
+}
+
+#[derive(Deserialize)]
+pub struct FoodQuery {
+    diet_type: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct FoodPayload {
+    name: String,
+    diet_types: Vec<String>,
+    protein_percent: f64,
+    portion_unit: String,
+}
+
+pub async fn fetch_food_by_name(pool: &PgPool, name: &str) -> Result<Option<Food>, sqlx::Error> {
+    sqlx::query_as::<_, Food>("SELECT * FROM foods WHERE LOWER(name) = LOWER($1)")
+        .bind(name.trim())
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn fetch_foods_for_diet(pool: &PgPool, diet_type: &str) -> Result<Vec<Food>, sqlx::Error> {
+    sqlx::query_as::<_, Food>("SELECT * FROM foods WHERE $1 = ANY(diet_types) ORDER BY name")
+        .bind(diet_type)
+        .fetch_all(pool)
+        .await
+}
+
+/// Checks a requested custom diet against the foods catalog for a species.
+///
+/// Returns the catalog entry so callers use its canonical name, or a
+/// `ScheduleError` listing the foods that would suit the species instead.
+pub async fn validate_custom_diet(
+    pool: &PgPool,
+    species: &Species,
+    custom_diet: &str,
+) -> Result<Food, ApiError> {
+    let food = fetch_food_by_name(pool, custom_diet).await?;
+
+    match food {
+        Some(food) if food.suits(&species.diet_type) => Ok(food),
+        found => {
+            let alternatives = fetch_foods_for_diet(pool, &species.diet_type)
+                .await?
+                .into_iter()
+                .map(|f| f.name)
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            let problem = match found {
+                Some(food) => format!(
+                    "'{}' is not suitable for {} ({} diet)",
+                    food.name, species.name, species.diet_type
+                ),
+                None => format!("'{}' is not in the foods catalog", custom_diet.trim()),
+            };
+
+            Err(ApiError::ScheduleError(format!(
+                "{}; suitable foods: {}",
+                problem, alternatives
+            )))
+        }
+    }
+}
+
+/// Lists the foods catalog, optionally filtered to one diet type.
+pub async fn list_foods(
+    Query(params): Query<FoodQuery>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let foods = match &params.diet_type {
+        Some(diet_type) => fetch_foods_for_diet(&state.pool, diet_type).await?,
+        None => {
+            sqlx::query_as::<_, Food>("SELECT * FROM foods ORDER BY name")
+                .fetch_all(&state.pool)
+                .await?
+        }
+    };
+
+    Ok(Json(foods))
+}
+
+/// Creates a food, or replaces the existing entry with the same name.
+pub async fn upsert_food(
+    State(state): State<AppState>,
+    Json(payload): Json<FoodPayload>,
+) -> Result<impl IntoResponse, ApiError> {
+    let name = payload.name.trim().to_lowercase();
+    if name.is_empty() {
+        return Err(ApiError::InvalidInput("name is required".to_string()));
+    }
+    if payload.diet_types.is_empty() {
+        return Err(ApiError::InvalidInput(
+            "diet_types must list at least one diet".to_string(),
+        ));
+    }
+    if !(0.0..=100.0).contains(&payload.protein_percent) {
+        return Err(ApiError::InvalidInput(
+            "protein_percent must be between 0 and 100".to_string(),
+        ));
+    }
+    if payload.portion_unit.trim().is_empty() {
+        return Err(ApiError::InvalidInput("portion_unit is required".to_string()));
+    }
+
+    let food = sqlx::query_as::<_, Food>(
+        "INSERT INTO foods (name, diet_types, protein_percent, portion_unit) \
+         VALUES ($1, $2, $3, $4) \
+         ON CONFLICT (name) DO UPDATE SET diet_types = EXCLUDED.diet_types, \
+         protein_percent = EXCLUDED.protein_percent, portion_unit = EXCLUDED.portion_unit \
+         RETURNING *",
+    )
+    .bind(&name)
+    .bind(&payload.diet_types)
+    .bind(payload.protein_percent)
+    .bind(payload.portion_unit.trim())
+    .fetch_one(&state.pool)
+    .await?;
+
+    tracing::info!(
+        food_id = food.id,
+        food_name = %food.name,
+        operation = "upsert_food",
+        "Food catalog entry saved"
+    );
+
+    Ok((StatusCode::CREATED, Json(food)))
+}
+
+pub async fn delete_food(
+    Path(id): Path<i32>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let result = sqlx::query("DELETE FROM foods WHERE id = $1")
+        .bind(id)
+        .execute(&state.pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound(format!("Food with ID {} not found", id)));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,639 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use shuttle_axum::axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use sqlx::{postgres::PgRow, PgConnection, Row};
+
+use crate::{
+    inhabitants::ensure_tank_exists,
+    ApiError, AppState,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IncidentStatus {
+    Open,
+    Closed,
+}
+
+impl IncidentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IncidentStatus::Open => "open",
+            IncidentStatus::Closed => "closed",
+        }
+    }
+
+    fn from_db(value: &str) -> Result<Self, sqlx::Error> {
+        match value {
+            "open" => Ok(IncidentStatus::Open),
+            "closed" => Ok(IncidentStatus::Closed),
+            other => Err(sqlx::Error::Decode(
+                format!("unknown incident status: {}", other).into(),
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IncidentOutcome {
+    Recovered,
+    PartiallyRecovered,
+    Died,
+    Euthanized,
+}
+
+impl IncidentOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IncidentOutcome::Recovered => "recovered",
+            IncidentOutcome::PartiallyRecovered => "partially_recovered",
+            IncidentOutcome::Died => "died",
+            IncidentOutcome::Euthanized => "euthanized",
+        }
+    }
+
+    fn from_db(value: &str) -> Result<Self, sqlx::Error> {
+        match value {
+            "recovered" => Ok(IncidentOutcome::Recovered),
+            "partially_recovered" => Ok(IncidentOutcome::PartiallyRecovered),
+            "died" => Ok(IncidentOutcome::Died),
+            "euthanized" => Ok(IncidentOutcome::Euthanized),
+            other => Err(sqlx::Error::Decode(
+                format!("unknown incident outcome: {}", other).into(),
+            )),
+        }
+    }
+}
+
+#[derive(Serialize, Clone, sqlx::FromRow)]
+pub struct Medication {
+    pub id: i32,
+    pub name: String,
+    pub active_ingredient: Option<String>,
+    pub toxic_to_invertebrates: bool,
+    pub toxicity_notes: Option<String>,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+pub struct Treatment {
+    pub id: i32,
+    pub incident_id: i32,
+    pub medication_id: i32,
+    pub medication_name: String,
+    pub dosage_amount: f64,
+    pub dosage_unit: String,
+    pub administered_at: DateTime<Utc>,
+    pub notes: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct HealthIncident {
+    pub id: i32,
+    pub tank_id: String,
+    pub species_id: i32,
+    pub species_name: String,
+    pub status: IncidentStatus,
+    pub symptoms: String,
+    pub diagnosis: Option<String>,
+    pub affected_count: Option<i32>,
+    pub outcome: Option<IncidentOutcome>,
+    pub notes: Option<String>,
+    pub opened_at: DateTime<Utc>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub treatments: Vec<Treatment>,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+pub struct ExposedPopulation {
+    pub species_id: i32,
+    pub species_name: String,
+    pub count: i32,
+}
+
+/// Raised when a medication known to harm invertebrates is dosed into a tank that houses them.
+#[derive(Serialize)]
+pub struct MedicationWarning {
+    pub medication: String,
+    pub tank_id: String,
+    pub message: String,
+    pub exposed: Vec<ExposedPopulation>,
+}
+
+#[derive(Serialize)]
+pub struct TreatmentResponse {
+    pub treatment: Treatment,
+    pub warnings: Vec<MedicationWarning>,
+}
+
+#[derive(Serialize)]
+pub struct MedicationCheck {
+    pub medication: Medication,
+    pub tank_id: String,
+    pub safe: bool,
+    pub warnings: Vec<MedicationWarning>,
+}
+
+#[derive(Deserialize)]
+pub struct IncidentQuery {
+    status: Option<IncidentStatus>,
+}
+
+#[derive(Deserialize)]
+pub struct OpenIncidentRequest {
+    species_id: i32,
+    symptoms: String,
+    diagnosis: Option<String>,
+    affected_count: Option<i32>,
+    opened_at: Option<DateTime<Utc>>,
+    notes: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateIncidentRequest {
+    symptoms: Option<String>,
+    diagnosis: Option<String>,
+    affected_count: Option<i32>,
+    notes: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct CloseIncidentRequest {
+    outcome: IncidentOutcome,
+    closed_at: Option<DateTime<Utc>>,
+    notes: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct TreatmentRequest {
+    medication: String,
+    dosage_amount: f64,
+    dosage_unit: String,
+    administered_at: Option<DateTime<Utc>>,
+    notes: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct MedicationCheckQuery {
+    medication: String,
+}
+
+#[derive(Deserialize)]
+pub struct MedicationPayload {
+    name: String,
+    active_ingredient: Option<String>,
+    toxic_to_invertebrates: bool,
+    toxicity_notes: Option<String>,
+}
+
+const INCIDENT_SELECT: &str = "SELECT h.id, h.tank_id, h.species_id, s.name AS species_name, h.status, \
+     h.symptoms, h.diagnosis, h.affected_count, h.outcome, h.notes, h.opened_at, h.closed_at \
+     FROM health_incidents h \
+     JOIN species s ON s.id = h.species_id";
+
+const TREATMENT_SELECT: &str = "SELECT t.id, t.incident_id, t.medication_id, m.name AS medication_name, \
+     t.dosage_amount, t.dosage_unit, t.administered_at, t.notes \
+     FROM incident_treatments t \
+     JOIN medications m ON m.id = t.medication_id";
+
+fn incident_from_row(row: PgRow) -> Result<HealthIncident, sqlx::Error> {
+    let status: String = row.try_get("status")?;
+    let outcome: Option<String> = row.try_get("outcome")?;
+    Ok(HealthIncident {
+        id: row.try_get("id")?,
+        tank_id: row.try_get("tank_id")?,
+        species_id: row.try_get("species_id")?,
+        species_name: row.try_get("species_name")?,
+        status: IncidentStatus::from_db(&status)?,
+        symptoms: row.try_get("symptoms")?,
+        diagnosis: row.try_get("diagnosis")?,
+        affected_count: row.try_get("affected_count")?,
+        outcome: outcome.as_deref().map(IncidentOutcome::from_db).transpose()?,
+        notes: row.try_get("notes")?,
+        opened_at: row.try_get("opened_at")?,
+        closed_at: row.try_get("closed_at")?,
+        treatments: Vec::new(),
+    })
+}
+
+/// Loads the treatments for each incident and attaches them.
+async fn attach_treatments(
+    conn: &mut PgConnection,
+    mut incidents: Vec<HealthIncident>,
+) -> Result<Vec<HealthIncident>, sqlx::Error> {
+    let ids: Vec<i32> = incidents.iter().map(|incident| incident.id).collect();
+    let treatments = sqlx::query_as::<_, Treatment>(&format!(
+        "{} WHERE t.incident_id = ANY($1) ORDER BY t.administered_at, t.id",
+        TREATMENT_SELECT
+    ))
+    .bind(&ids)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for treatment in treatments {
+        if let Some(incident) = incidents.iter_mut().find(|i| i.id == treatment.incident_id) {
+            incident.treatments.push(treatment);
+        }
+    }
+    Ok(incidents)
+}
+
+async fn fetch_incident(conn: &mut PgConnection, incident_id: i32) -> Result<HealthIncident, ApiError> {
+    let incident = sqlx::query(&format!("{} WHERE h.id = $1", INCIDENT_SELECT))
+        .bind(incident_id)
+        .try_map(incident_from_row)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Health incident with ID {} not found", incident_id)))?;
+
+    let mut incidents = attach_treatments(conn, vec![incident]).await?;
+    incidents
+        .pop()
+        .ok_or_else(|| ApiError::InternalError(format!("Health incident {} disappeared", incident_id)))
+}
+
+async fn fetch_medication_by_name(conn: &mut PgConnection, name: &str) -> Result<Medication, ApiError> {
+    sqlx::query_as::<_, Medication>("SELECT * FROM medications WHERE LOWER(name) = LOWER($1)")
+        .bind(name.trim())
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Medication '{}' is not in the catalog", name.trim())))
+}
+
+/// Checks a medication against the populations living in a tank.
+///
+/// Only populations of invertebrate orders are exposed. Species not yet placed in the
+/// taxonomy are counted too, since nothing says they are safe.
+async fn medication_warnings(
+    conn: &mut PgConnection,
+    medication: &Medication,
+    tank_id: &str,
+) -> Result<Vec<MedicationWarning>, ApiError> {
+    if !medication.toxic_to_invertebrates {
+        return Ok(Vec::new());
+    }
+
+    let exposed = sqlx::query_as::<_, ExposedPopulation>(
+        "SELECT i.species_id, s.name AS species_name, i.count \
+         FROM tank_inhabitants i \
+         JOIN species s ON s.id = i.species_id \
+         LEFT JOIN taxonomy_genera g ON g.id = s.genus_id \
+         LEFT JOIN taxonomy_families f ON f.id = g.family_id \
+         LEFT JOIN taxonomy_orders o ON o.id = f.order_id \
+         WHERE i.tank_id = $1 AND i.removed_at IS NULL AND o.invertebrate IS NOT FALSE \
+         ORDER BY s.name",
+    )
+    .bind(tank_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    if exposed.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let names = exposed
+        .iter()
+        .map(|population| format!("{} {}", population.count, population.species_name))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut message = format!(
+        "{} is toxic to invertebrates and tank {} houses {}",
+        medication.name, tank_id, names
+    );
+    if let Some(notes) = &medication.toxicity_notes {
+        message = format!("{}. {}", message, notes);
+    }
+
+    Ok(vec![MedicationWarning {
+        medication: medication.name.clone(),
+        tank_id: tank_id.to_string(),
+        message,
+        exposed,
+    }])
+}
+
+/// Opens a health incident for a species in a tank.
+pub async fn open_incident(
+    Path(tank_id): Path<String>,
+    State(state): State<AppState>,
+    Json(request): Json<OpenIncidentRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    if request.symptoms.trim().is_empty() {
+        return Err(ApiError::InvalidInput("symptoms are required".to_string()));
+    }
+    if request.affected_count.is_some_and(|count| count <= 0) {
+        return Err(ApiError::InvalidInput(
+            "affected_count must be a positive number of animals".to_string(),
+        ));
+    }
+
+    let mut tx = state.pool.begin().await?;
+    ensure_tank_exists(&mut tx, &tank_id).await?;
+
+    let species_exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM species WHERE id = $1)")
+        .bind(request.species_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    if !species_exists {
+        return Err(ApiError::SpeciesNotFound(format!(
+            "Species with ID {} not found",
+            request.species_id
+        )));
+    }
+
+    let id: i32 = sqlx::query_scalar(
+        "INSERT INTO health_incidents (tank_id, species_id, symptoms, diagnosis, affected_count, notes, opened_at) \
+         VALUES ($1, $2, $3, $4, $5, $6, COALESCE($7, NOW())) RETURNING id",
+    )
+    .bind(&tank_id)
+    .bind(request.species_id)
+    .bind(request.symptoms.trim())
+    .bind(&request.diagnosis)
+    .bind(request.affected_count)
+    .bind(&request.notes)
+    .bind(request.opened_at)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let incident = fetch_incident(&mut tx, id).await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        tank_id = %tank_id,
+        species_id = request.species_id,
+        incident_id = id,
+        operation = "open_health_incident",
+        "Health incident opened"
+    );
+
+    Ok((StatusCode::CREATED, Json(incident)))
+}
+
+/// Lists health incidents across all tanks, optionally filtered by status.
+pub async fn list_incidents(
+    Query(query): Query<IncidentQuery>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut conn = state.pool.acquire().await?;
+
+    let incidents = sqlx::query(&format!(
+        "{} WHERE ($1::TEXT IS NULL OR h.status = $1) ORDER BY h.opened_at DESC, h.id DESC",
+        INCIDENT_SELECT
+    ))
+    .bind(query.status.map(|status| status.as_str()))
+    .try_map(incident_from_row)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(Json(attach_treatments(&mut conn, incidents).await?))
+}
+
+/// Lists a tank's health incidents, optionally filtered by status.
+pub async fn list_tank_incidents(
+    Path(tank_id): Path<String>,
+    Query(query): Query<IncidentQuery>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut conn = state.pool.acquire().await?;
+    ensure_tank_exists(&mut conn, &tank_id).await?;
+
+    let incidents = sqlx::query(&format!(
+        "{} WHERE h.tank_id = $1 AND ($2::TEXT IS NULL OR h.status = $2) ORDER BY h.opened_at DESC, h.id DESC",
+        INCIDENT_SELECT
+    ))
+    .bind(&tank_id)
+    .bind(query.status.map(|status| status.as_str()))
+    .try_map(incident_from_row)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(Json(attach_treatments(&mut conn, incidents).await?))
+}
+
+pub async fn get_incident(
+    Path(incident_id): Path<i32>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut conn = state.pool.acquire().await?;
+    Ok(Json(fetch_incident(&mut conn, incident_id).await?))
+}
+
+/// Updates the symptoms, diagnosis, affected count or notes of an open incident.
+pub async fn update_incident(
+    Path(incident_id): Path<i32>,
+    State(state): State<AppState>,
+    Json(request): Json<UpdateIncidentRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    if request.affected_count.is_some_and(|count| count <= 0) {
+        return Err(ApiError::InvalidInput(
+            "affected_count must be a positive number of animals".to_string(),
+        ));
+    }
+
+    let mut tx = state.pool.begin().await?;
+    let incident = fetch_incident(&mut tx, incident_id).await?;
+    if incident.status == IncidentStatus::Closed {
+        return Err(ApiError::InvalidInput(format!(
+            "Health incident {} is closed",
+            incident_id
+        )));
+    }
+
+    sqlx::query(
+        "UPDATE health_incidents SET symptoms = $2, diagnosis = $3, affected_count = $4, notes = $5 WHERE id = $1",
+    )
+    .bind(incident_id)
+    .bind(request.symptoms.as_deref().map(str::trim).unwrap_or(&incident.symptoms))
+    .bind(request.diagnosis.or(incident.diagnosis))
+    .bind(request.affected_count.or(incident.affected_count))
+    .bind(request.notes.or(incident.notes))
+    .execute(&mut *tx)
+    .await?;
+
+    let incident = fetch_incident(&mut tx, incident_id).await?;
+    tx.commit().await?;
+
+    Ok(Json(incident))
+}
+
+/// Closes an incident with its outcome.
+pub async fn close_incident(
+    Path(incident_id): Path<i32>,
+    State(state): State<AppState>,
+    Json(request): Json<CloseIncidentRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut tx = state.pool.begin().await?;
+
+    let result = sqlx::query(
+        "UPDATE health_incidents \
+         SET status = 'closed', outcome = $2, closed_at = COALESCE($3, NOW()), notes = COALESCE($4, notes) \
+         WHERE id = $1 AND status = 'open'",
+    )
+    .bind(incident_id)
+    .bind(request.outcome.as_str())
+    .bind(request.closed_at)
+    .bind(&request.notes)
+    .execute(&mut *tx)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        // Distinguish a missing incident from one that was already closed
+        fetch_incident(&mut tx, incident_id).await?;
+        return Err(ApiError::InvalidInput(format!(
+            "Health incident {} is already closed",
+            incident_id
+        )));
+    }
+
+    let incident = fetch_incident(&mut tx, incident_id).await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        incident_id = incident_id,
+        tank_id = %incident.tank_id,
+        outcome = request.outcome.as_str(),
+        operation = "close_health_incident",
+        "Health incident closed"
+    );
+
+    Ok(Json(incident))
+}
+
+/// Logs a treatment for an open incident.
+///
+/// The treatment is always recorded; if the medication endangers the tank's
+/// invertebrates, the warnings come back with it.
+pub async fn add_treatment(
+    Path(incident_id): Path<i32>,
+    State(state): State<AppState>,
+    Json(request): Json<TreatmentRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    if !request.dosage_amount.is_finite() || request.dosage_amount <= 0.0 {
+        return Err(ApiError::InvalidInput(
+            "dosage_amount must be a positive number".to_string(),
+        ));
+    }
+    if request.dosage_unit.trim().is_empty() {
+        return Err(ApiError::InvalidInput("dosage_unit is required".to_string()));
+    }
+
+    let mut tx = state.pool.begin().await?;
+    let incident = fetch_incident(&mut tx, incident_id).await?;
+    if incident.status == IncidentStatus::Closed {
+        return Err(ApiError::InvalidInput(format!(
+            "Health incident {} is closed",
+            incident_id
+        )));
+    }
+    let medication = fetch_medication_by_name(&mut tx, &request.medication).await?;
+
+    let warnings = medication_warnings(&mut tx, &medication, &incident.tank_id).await?;
+
+    let treatment_id: i32 = sqlx::query_scalar(
+        "INSERT INTO incident_treatments (incident_id, medication_id, dosage_amount, dosage_unit, administered_at, notes) \
+         VALUES ($1, $2, $3, $4, COALESCE($5, NOW()), $6) RETURNING id",
+    )
+    .bind(incident_id)
+    .bind(medication.id)
+    .bind(request.dosage_amount)
+    .bind(request.dosage_unit.trim())
+    .bind(request.administered_at)
+    .bind(&request.notes)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let treatment = sqlx::query_as::<_, Treatment>(&format!("{} WHERE t.id = $1", TREATMENT_SELECT))
+        .bind(treatment_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    for warning in &warnings {
+        tracing::warn!(
+            incident_id = incident_id,
+            tank_id = %incident.tank_id,
+            medication = %medication.name,
+            exposed_populations = warning.exposed.len(),
+            operation = "add_incident_treatment",
+            "Medication toxic to invertebrates dosed into an occupied tank"
+        );
+    }
+
+    tracing::info!(
+        incident_id = incident_id,
+        treatment_id = treatment.id,
+        medication = %medication.name,
+        dosage_amount = treatment.dosage_amount,
+        dosage_unit = %treatment.dosage_unit,
+        operation = "add_incident_treatment",
+        "Treatment recorded"
+    );
+
+    Ok((StatusCode::CREATED, Json(TreatmentResponse { treatment, warnings })))
+}
+
+/// Checks whether a medication is safe to dose into a tank before treating.
+pub async fn check_medication(
+    Path(tank_id): Path<String>,
+    Query(query): Query<MedicationCheckQuery>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut conn = state.pool.acquire().await?;
+    ensure_tank_exists(&mut conn, &tank_id).await?;
+    let medication = fetch_medication_by_name(&mut conn, &query.medication).await?;
+    let warnings = medication_warnings(&mut conn, &medication, &tank_id).await?;
+
+    Ok(Json(MedicationCheck {
+        safe: warnings.is_empty(),
+        medication,
+        tank_id,
+        warnings,
+    }))
+}
+
+pub async fn list_medications(State(state): State<AppState>) -> Result<impl IntoResponse, ApiError> {
+    let medications = sqlx::query_as::<_, Medication>("SELECT * FROM medications ORDER BY name")
+        .fetch_all(&state.pool)
+        .await?;
+
+    Ok(Json(medications))
+}
+
+/// Creates a medication, or replaces the existing entry with the same name.
+pub async fn upsert_medication(
+    State(state): State<AppState>,
+    Json(payload): Json<MedicationPayload>,
+) -> Result<impl IntoResponse, ApiError> {
+    let name = payload.name.trim().to_lowercase();
+    if name.is_empty() {
+        return Err(ApiError::InvalidInput("name is required".to_string()));
+    }
+
+    let medication = sqlx::query_as::<_, Medication>(
+        "INSERT INTO medications (name, active_ingredient, toxic_to_invertebrates, toxicity_notes) \
+         VALUES ($1, $2, $3, $4) \
+         ON CONFLICT (name) DO UPDATE SET active_ingredient = EXCLUDED.active_ingredient, \
+         toxic_to_invertebrates = EXCLUDED.toxic_to_invertebrates, toxicity_notes = EXCLUDED.toxicity_notes \
+         RETURNING *",
+    )
+    .bind(&name)
+    .bind(&payload.active_ingredient)
+    .bind(payload.toxic_to_invertebrates)
+    .bind(&payload.toxicity_notes)
+    .fetch_one(&state.pool)
+    .await?;
+
+    tracing::info!(
+        medication_id = medication.id,
+        medication_name = %medication.name,
+        toxic_to_invertebrates = medication.toxic_to_invertebrates,
+        operation = "upsert_medication",
+        "Medication catalog entry saved"
+    );
+
+    Ok((StatusCode::CREATED, Json(medication)))
+}
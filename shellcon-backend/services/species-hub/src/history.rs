@@ -0,0 +1,598 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use shuttle_axum::axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    response::IntoResponse,
+    Json,
+};
+use sqlx::{postgres::PgRow, PgConnection, Row};
+
+use crate::{ApiError, AppState};
+
+/// Request header naming who made a change; recorded on every revision.
+pub const CHANGED_BY_HEADER: &str = "x-changed-by";
+const UNKNOWN_EDITOR: &str = "anonymous";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryEntity {
+    Species,
+    Tank,
+}
+
+impl HistoryEntity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HistoryEntity::Species => "species",
+            HistoryEntity::Tank => "tank",
+        }
+    }
+
+    fn table(&self) -> &'static str {
+        match self {
+            HistoryEntity::Species => "species",
+            HistoryEntity::Tank => "tanks",
+        }
+    }
+
+    /// Matches the row whose id is bound as text in `$1`.
+    fn key_condition(&self) -> &'static str {
+        match self {
+            HistoryEntity::Species => "t.id = $1::INTEGER",
+            HistoryEntity::Tank => "t.id = $1",
+        }
+    }
+
+    /// Columns restored by a rollback; the id is never changed.
+    fn restorable_columns(&self) -> &'static [&'static str] {
+        match self {
+            HistoryEntity::Species => &[
+                "name",
+                "scientific_name",
+                "description",
+                "min_temperature",
+                "max_temperature",
+                "min_ph",
+                "max_ph",
+                "diet_type",
+                "min_salinity",
+                "max_salinity",
+                "min_oxygen",
+                "min_gh",
+                "max_gh",
+                "min_kh",
+                "max_kh",
+                "water_type",
+                "care_level",
+                "genus_id",
+            ],
+            HistoryEntity::Tank => &[
+                "name",
+                "tank_type",
+                "volume",
+                "description",
+                "timezone",
+                "cleaning_interval_days",
+                "last_cleaned_at",
+            ],
+        }
+    }
+
+    /// Describes what a rollback collided with when it breaks a unique constraint.
+    fn unique_conflict(&self) -> &'static str {
+        match self {
+            HistoryEntity::Species => "another species already has that scientific name",
+            HistoryEntity::Tank => "another tank already uses one of the restored values",
+        }
+    }
+
+    fn not_found(&self, entity_id: &str) -> ApiError {
+        match self {
+            HistoryEntity::Species => {
+                ApiError::SpeciesNotFound(format!("Species with ID {} not found", entity_id))
+            }
+            HistoryEntity::Tank => ApiError::TankNotFound(entity_id.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RevisionOperation {
+    Create,
+    Update,
+    Rollback,
+}
+
+impl RevisionOperation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RevisionOperation::Create => "create",
+            RevisionOperation::Update => "update",
+            RevisionOperation::Rollback => "rollback",
+        }
+    }
+
+    fn from_db(value: &str) -> Result<Self, sqlx::Error> {
+        match value {
+            "create" => Ok(RevisionOperation::Create),
+            "update" => Ok(RevisionOperation::Update),
+            "rollback" => Ok(RevisionOperation::Rollback),
+            other => Err(sqlx::Error::Decode(
+                format!("unknown revision operation: {}", other).into(),
+            )),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct Revision {
+    pub entity_type: HistoryEntity,
+    pub entity_id: String,
+    pub revision: i32,
+    pub operation: RevisionOperation,
+    pub changed_by: String,
+    pub reason: Option<String>,
+    pub changed_at: DateTime<Utc>,
+    pub snapshot: Value,
+}
+
+#[derive(Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub from: Value,
+    pub to: Value,
+}
+
+#[derive(Serialize)]
+pub struct RevisionDiff {
+    pub entity_type: HistoryEntity,
+    pub entity_id: String,
+    pub from_revision: i32,
+    /// None when comparing against the current row
+    pub to_revision: Option<i32>,
+    pub changes: Vec<FieldChange>,
+}
+
+#[derive(Deserialize)]
+pub struct DiffQuery {
+    from: i32,
+    to: Option<i32>,
+}
+
+#[derive(Serialize)]
+pub struct RollbackResponse {
+    pub revision: Revision,
+    pub changes: Vec<FieldChange>,
+}
+
+/// Reads the editor from the `X-Changed-By` header.
+pub fn changed_by(headers: &HeaderMap) -> String {
+    headers
+        .get(CHANGED_BY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(|value| value.chars().take(100).collect())
+        .unwrap_or_else(|| UNKNOWN_EDITOR.to_string())
+}
+
+fn revision_from_row(row: PgRow) -> Result<Revision, sqlx::Error> {
+    let entity_type: String = row.try_get("entity_type")?;
+    let operation: String = row.try_get("operation")?;
+    Ok(Revision {
+        entity_type: match entity_type.as_str() {
+            "species" => HistoryEntity::Species,
+            "tank" => HistoryEntity::Tank,
+            other => {
+                return Err(sqlx::Error::Decode(
+                    format!("unknown history entity: {}", other).into(),
+                ))
+            }
+        },
+        entity_id: row.try_get("entity_id")?,
+        revision: row.try_get("revision")?,
+        operation: RevisionOperation::from_db(&operation)?,
+        changed_by: row.try_get("changed_by")?,
+        reason: row.try_get("reason")?,
+        changed_at: row.try_get("changed_at")?,
+        snapshot: row.try_get("snapshot")?,
+    })
+}
+
+/// Snapshots the current row as the next revision.
+///
+/// Call this inside the transaction that made the change, after the write, so the
+/// history can never disagree with the table. The row is locked first so concurrent
+/// writers number their revisions one after the other.
+pub async fn record_revision(
+    conn: &mut PgConnection,
+    entity: HistoryEntity,
+    entity_id: &str,
+    operation: RevisionOperation,
+    changed_by: &str,
+    reason: Option<&str>,
+) -> Result<Revision, sqlx::Error> {
+    // A separate statement, so the MAX(revision) below is read after the lock is granted
+    sqlx::query(&format!(
+        "SELECT 1 FROM {} t WHERE {} FOR UPDATE",
+        entity.table(),
+        entity.key_condition()
+    ))
+    .bind(entity_id)
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(&format!(
+        "INSERT INTO entity_revisions (entity_type, entity_id, revision, operation, snapshot, changed_by, reason) \
+         SELECT $2, $1, \
+                COALESCE((SELECT MAX(revision) FROM entity_revisions WHERE entity_type = $2 AND entity_id = $1), 0) + 1, \
+                $3, to_jsonb(t), $4, $5 \
+         FROM {} t WHERE {} \
+         RETURNING *",
+        entity.table(),
+        entity.key_condition()
+    ))
+    .bind(entity_id)
+    .bind(entity.as_str())
+    .bind(operation.as_str())
+    .bind(changed_by)
+    .bind(reason)
+    .try_map(revision_from_row)
+    .fetch_one(conn)
+    .await
+}
+
+async fn fetch_revision(
+    conn: &mut PgConnection,
+    entity: HistoryEntity,
+    entity_id: &str,
+    revision: i32,
+) -> Result<Revision, ApiError> {
+    sqlx::query("SELECT * FROM entity_revisions WHERE entity_type = $1 AND entity_id = $2 AND revision = $3")
+        .bind(entity.as_str())
+        .bind(entity_id)
+        .bind(revision)
+        .try_map(revision_from_row)
+        .fetch_optional(conn)
+        .await?
+        .ok_or_else(|| {
+            ApiError::NotFound(format!(
+                "Revision {} of {} {} not found",
+                revision,
+                entity.as_str(),
+                entity_id
+            ))
+        })
+}
+
+async fn current_snapshot(
+    conn: &mut PgConnection,
+    entity: HistoryEntity,
+    entity_id: &str,
+) -> Result<Value, ApiError> {
+    sqlx::query_scalar::<_, Value>(&format!(
+        "SELECT to_jsonb(t) FROM {} t WHERE {}",
+        entity.table(),
+        entity.key_condition()
+    ))
+    .bind(entity_id)
+    .fetch_optional(conn)
+    .await?
+    .ok_or_else(|| entity.not_found(entity_id))
+}
+
+/// Field-by-field differences between two row snapshots.
+fn diff_snapshots(from: &Value, to: &Value) -> Vec<FieldChange> {
+    let empty = Map::new();
+    let from = from.as_object().unwrap_or(&empty);
+    let to = to.as_object().unwrap_or(&empty);
+
+    let mut fields: Vec<&String> = from.keys().chain(to.keys()).collect();
+    fields.sort();
+    fields.dedup();
+
+    fields
+        .into_iter()
+        .filter_map(|field| {
+            let before = from.get(field).cloned().unwrap_or(Value::Null);
+            let after = to.get(field).cloned().unwrap_or(Value::Null);
+            (before != after).then(|| FieldChange {
+                field: field.clone(),
+                from: before,
+                to: after,
+            })
+        })
+        .collect()
+}
+
+async fn list_revisions(state: &AppState, entity: HistoryEntity, entity_id: &str) -> Result<Vec<Revision>, ApiError> {
+    let mut conn = state.pool.acquire().await?;
+    current_snapshot(&mut conn, entity, entity_id).await?;
+
+    let revisions = sqlx::query(
+        "SELECT * FROM entity_revisions WHERE entity_type = $1 AND entity_id = $2 ORDER BY revision DESC",
+    )
+    .bind(entity.as_str())
+    .bind(entity_id)
+    .try_map(revision_from_row)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(revisions)
+}
+
+async fn diff_revisions(
+    state: &AppState,
+    entity: HistoryEntity,
+    entity_id: &str,
+    query: DiffQuery,
+) -> Result<RevisionDiff, ApiError> {
+    let mut conn = state.pool.acquire().await?;
+    let from = fetch_revision(&mut conn, entity, entity_id, query.from).await?;
+    let to = match query.to {
+        Some(revision) => fetch_revision(&mut conn, entity, entity_id, revision).await?.snapshot,
+        None => current_snapshot(&mut conn, entity, entity_id).await?,
+    };
+
+    Ok(RevisionDiff {
+        entity_type: entity,
+        entity_id: entity_id.to_string(),
+        from_revision: query.from,
+        to_revision: query.to,
+        changes: diff_snapshots(&from.snapshot, &to),
+    })
+}
+
+/// Reports a rollback that would duplicate another row's unique value as bad input.
+fn rollback_error(entity: HistoryEntity, entity_id: &str, revision: i32, e: sqlx::Error) -> ApiError {
+    match e.as_database_error() {
+        Some(db) if db.is_unique_violation() => ApiError::InvalidInput(format!(
+            "Cannot roll back {} {} to revision {}: {}",
+            entity.as_str(),
+            entity_id,
+            revision,
+            entity.unique_conflict()
+        )),
+        _ => e.into(),
+    }
+}
+
+/// Restores the row to an earlier revision and records the rollback as a new revision.
+///
+/// Columns added after the revision was taken keep their current values.
+async fn rollback_to_revision(
+    state: &AppState,
+    entity: HistoryEntity,
+    entity_id: &str,
+    revision: i32,
+    changed_by: &str,
+) -> Result<RollbackResponse, ApiError> {
+    let mut tx = state.pool.begin().await?;
+
+    let target = fetch_revision(&mut tx, entity, entity_id, revision).await?;
+    let before = current_snapshot(&mut tx, entity, entity_id).await?;
+
+    let assignments = entity
+        .restorable_columns()
+        .iter()
+        .map(|column| format!("{0} = CASE WHEN $2::JSONB ? '{0}' THEN r.{0} ELSE t.{0} END", column))
+        .collect::<Vec<_>>()
+        .join(", ");
+    sqlx::query(&format!(
+        "UPDATE {table} t SET {assignments} \
+         FROM jsonb_populate_record(NULL::{table}, $2::JSONB) r \
+         WHERE {condition}",
+        table = entity.table(),
+        assignments = assignments,
+        condition = entity.key_condition(),
+    ))
+    .bind(entity_id)
+    .bind(&target.snapshot)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| rollback_error(entity, entity_id, revision, e))?;
+
+    let reason = format!("Rolled back to revision {}", revision);
+    let recorded = record_revision(
+        &mut tx,
+        entity,
+        entity_id,
+        RevisionOperation::Rollback,
+        changed_by,
+        Some(&reason),
+    )
+    .await?;
+    tx.commit().await?;
+    if entity == HistoryEntity::Species {
+        state.catalog_cache.invalidate();
+    }
+
+    tracing::info!(
+        entity_type = entity.as_str(),
+        entity_id = %entity_id,
+        restored_revision = revision,
+        new_revision = recorded.revision,
+        changed_by = %changed_by,
+        operation = "rollback_revision",
+        "Entity rolled back to an earlier revision"
+    );
+
+    let changes = diff_snapshots(&before, &recorded.snapshot);
+    Ok(RollbackResponse {
+        revision: recorded,
+        changes,
+    })
+}
+
+pub async fn list_species_revisions(
+    Path(species_id): Path<i32>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    Ok(Json(
+        list_revisions(&state, HistoryEntity::Species, &species_id.to_string()).await?,
+    ))
+}
+
+pub async fn diff_species_revisions(
+    Path(species_id): Path<i32>,
+    Query(query): Query<DiffQuery>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    Ok(Json(
+        diff_revisions(&state, HistoryEntity::Species, &species_id.to_string(), query).await?,
+    ))
+}
+
+pub async fn rollback_species(
+    Path((species_id, revision)): Path<(i32, i32)>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, ApiError> {
+    Ok(Json(
+        rollback_to_revision(
+            &state,
+            HistoryEntity::Species,
+            &species_id.to_string(),
+            revision,
+            &changed_by(&headers),
+        )
+        .await?,
+    ))
+}
+
+pub async fn list_tank_revisions(
+    Path(tank_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    Ok(Json(list_revisions(&state, HistoryEntity::Tank, &tank_id).await?))
+}
+
+pub async fn diff_tank_revisions(
+    Path(tank_id): Path<String>,
+    Query(query): Query<DiffQuery>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    Ok(Json(diff_revisions(&state, HistoryEntity::Tank, &tank_id, query).await?))
+}
+
+pub async fn rollback_tank(
+    Path((tank_id, revision)): Path<(String, i32)>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, ApiError> {
+    Ok(Json(
+        rollback_to_revision(&state, HistoryEntity::Tank, &tank_id, revision, &changed_by(&headers)).await?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn diff_lists_changed_fields_in_name_order() {
+        let changes = diff_snapshots(
+            &json!({ "name": "Main Tank", "volume": 500.0, "timezone": null, "id": "tank1" }),
+            &json!({ "name": "Display Tank", "volume": 450.0, "timezone": null, "id": "tank1" }),
+        );
+
+        let fields: Vec<&str> = changes.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(fields, vec!["name", "volume"]);
+        assert_eq!(changes[0].from, json!("Main Tank"));
+        assert_eq!(changes[0].to, json!("Display Tank"));
+    }
+
+    #[test]
+    fn added_and_removed_fields_diff_against_null() {
+        let changes = diff_snapshots(&json!({ "a": 1, "b": 2 }), &json!({ "b": 2, "c": 3 }));
+
+        assert_eq!(changes.len(), 2);
+        assert_eq!((changes[0].field.as_str(), &changes[0].from, &changes[0].to), ("a", &json!(1), &Value::Null));
+        assert_eq!((changes[1].field.as_str(), &changes[1].from, &changes[1].to), ("c", &Value::Null, &json!(3)));
+    }
+
+    #[test]
+    fn identical_snapshots_have_no_changes() {
+        let snapshot = json!({ "id": 3, "tags": ["a", "b"] });
+        assert!(diff_snapshots(&snapshot, &snapshot).is_empty());
+    }
+
+    #[test]
+    fn non_object_snapshots_count_as_empty() {
+        let changes = diff_snapshots(&Value::Null, &json!({ "id": 3 }));
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].field, "id");
+        assert_eq!(changes[0].from, Value::Null);
+    }
+
+    /// A database error that is or isn't a unique violation.
+    #[derive(Debug)]
+    struct FakeDatabaseError {
+        unique_violation: bool,
+    }
+
+    impl std::fmt::Display for FakeDatabaseError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(sqlx::error::DatabaseError::message(self))
+        }
+    }
+
+    impl std::error::Error for FakeDatabaseError {}
+
+    impl sqlx::error::DatabaseError for FakeDatabaseError {
+        fn message(&self) -> &str {
+            "fake database error"
+        }
+
+        fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+            self
+        }
+
+        fn kind(&self) -> sqlx::error::ErrorKind {
+            if self.unique_violation {
+                sqlx::error::ErrorKind::UniqueViolation
+            } else {
+                sqlx::error::ErrorKind::Other
+            }
+        }
+    }
+
+    #[test]
+    fn rollback_onto_a_taken_scientific_name_is_invalid_input() {
+        let error = sqlx::Error::Database(Box::new(FakeDatabaseError { unique_violation: true }));
+
+        match rollback_error(HistoryEntity::Species, "4", 2, error) {
+            ApiError::InvalidInput(message) => assert_eq!(
+                message,
+                "Cannot roll back species 4 to revision 2: another species already has that scientific name"
+            ),
+            other => panic!("expected InvalidInput, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn other_rollback_failures_stay_database_errors() {
+        let error = sqlx::Error::Database(Box::new(FakeDatabaseError { unique_violation: false }));
+        assert!(matches!(
+            rollback_error(HistoryEntity::Tank, "Tank-A1", 1, error),
+            ApiError::Database(_)
+        ));
+        assert!(matches!(
+            rollback_error(HistoryEntity::Tank, "Tank-A1", 1, sqlx::Error::RowNotFound),
+            ApiError::Database(_)
+        ));
+    }
+}
@@ -0,0 +1,406 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use shuttle_axum::axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use sqlx::{postgres::PgRow, PgConnection, PgPool, Row};
+
+use crate::{ApiError, AppState};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PopulationChangeType {
+    Added,
+    Removed,
+    MovedIn,
+    MovedOut,
+}
+
+impl PopulationChangeType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PopulationChangeType::Added => "added",
+            PopulationChangeType::Removed => "removed",
+            PopulationChangeType::MovedIn => "moved_in",
+            PopulationChangeType::MovedOut => "moved_out",
+        }
+    }
+
+    fn from_db(value: &str) -> Result<Self, sqlx::Error> {
+        match value {
+            "added" => Ok(PopulationChangeType::Added),
+            "removed" => Ok(PopulationChangeType::Removed),
+            "moved_in" => Ok(PopulationChangeType::MovedIn),
+            "moved_out" => Ok(PopulationChangeType::MovedOut),
+            other => Err(sqlx::Error::Decode(
+                format!("unknown population change type: {}", other).into(),
+            )),
+        }
+    }
+}
+
+#[derive(Serialize, Clone, sqlx::FromRow)]
+pub struct TankInhabitant {
+    pub id: i32,
+    pub tank_id: String,
+    pub species_id: i32,
+    pub species_name: String,
+    pub count: i32,
+    pub added_at: DateTime<Utc>,
+    pub removed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize)]
+pub struct PopulationChange {
+    pub id: i32,
+    pub inhabitant_id: i32,
+    pub tank_id: String,
+    pub species_id: i32,
+    pub change_type: PopulationChangeType,
+    pub count_delta: i32,
+    pub count_after: i32,
+    pub related_tank_id: Option<String>,
+    pub note: Option<String>,
+    pub changed_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+pub struct AddInhabitantsRequest {
+    species_id: i32,
+    count: i32,
+    note: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct RemoveInhabitantsRequest {
+    count: i32,
+    note: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct MoveInhabitantsRequest {
+    to_tank_id: String,
+    count: i32,
+    note: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct MoveInhabitantsResponse {
+    pub from: TankInhabitant,
+    pub to: TankInhabitant,
+}
+
+const INHABITANT_SELECT: &str = "SELECT i.id, i.tank_id, i.species_id, s.name AS species_name, \
+     i.count, i.added_at, i.removed_at \
+     FROM tank_inhabitants i \
+     JOIN species s ON s.id = i.species_id";
+
+fn population_change_from_row(row: PgRow) -> Result<PopulationChange, sqlx::Error> {
+    let change_type: String = row.try_get("change_type")?;
+    Ok(PopulationChange {
+        id: row.try_get("id")?,
+        inhabitant_id: row.try_get("inhabitant_id")?,
+        tank_id: row.try_get("tank_id")?,
+        species_id: row.try_get("species_id")?,
+        change_type: PopulationChangeType::from_db(&change_type)?,
+        count_delta: row.try_get("count_delta")?,
+        count_after: row.try_get("count_after")?,
+        related_tank_id: row.try_get("related_tank_id")?,
+        note: row.try_get("note")?,
+        changed_at: row.try_get("changed_at")?,
+    })
+}
+
+/// Returns the populations currently living in a tank.
+pub async fn fetch_active_inhabitants(
+    pool: &PgPool,
+    tank_id: &str,
+) -> Result<Vec<TankInhabitant>, sqlx::Error> {
+    sqlx::query_as::<_, TankInhabitant>(&format!(
+        "{} WHERE i.tank_id = $1 AND i.removed_at IS NULL ORDER BY s.name",
+        INHABITANT_SELECT
+    ))
+    .bind(tank_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn ensure_tank_exists(conn: &mut PgConnection, tank_id: &str) -> Result<(), ApiError> {
+    let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM tanks WHERE id = $1)")
+        .bind(tank_id)
+        .fetch_one(conn)
+        .await?;
+
+    if exists {
+        Ok(())
+    } else {
+        Err(ApiError::TankNotFound(tank_id.to_string()))
+    }
+}
+
+fn validate_count(count: i32) -> Result<(), ApiError> {
+    if count <= 0 {
+        return Err(ApiError::InvalidInput(
+            "Count must be a positive number of animals".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Applies `delta` to the active population of a species in a tank and records the change.
+///
+/// A population is created on the first addition and marked removed once it drops to
+/// zero, so later additions start a fresh row while the old one stays in the history.
+async fn adjust_population(
+    conn: &mut PgConnection,
+    tank_id: &str,
+    species_id: i32,
+    delta: i32,
+    change_type: PopulationChangeType,
+    related_tank_id: Option<&str>,
+    note: Option<&str>,
+) -> Result<TankInhabitant, ApiError> {
+    let current: Option<(i32, i32)> = sqlx::query_as(
+        "SELECT id, count FROM tank_inhabitants \
+         WHERE tank_id = $1 AND species_id = $2 AND removed_at IS NULL \
+         FOR UPDATE",
+    )
+    .bind(tank_id)
+    .bind(species_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    let (inhabitant_id, count_after) = match current {
+        Some((id, count)) => {
+            let count_after = count + delta;
+            if count_after < 0 {
+                return Err(ApiError::InvalidInput(format!(
+                    "Tank {} only has {} of species {}",
+                    tank_id, count, species_id
+                )));
+            }
+            sqlx::query(
+                "UPDATE tank_inhabitants \
+                 SET count = $2, removed_at = CASE WHEN $2 = 0 THEN NOW() ELSE NULL END \
+                 WHERE id = $1",
+            )
+            .bind(id)
+            .bind(count_after)
+            .execute(&mut *conn)
+            .await?;
+            (id, count_after)
+        }
+        None if delta > 0 => {
+            // A concurrent first addition may have created the population since the lookup;
+            // add to it rather than tripping the one-active-population index
+            sqlx::query_as(
+                "INSERT INTO tank_inhabitants (tank_id, species_id, count) VALUES ($1, $2, $3) \
+                 ON CONFLICT (tank_id, species_id) WHERE removed_at IS NULL \
+                 DO UPDATE SET count = tank_inhabitants.count + EXCLUDED.count \
+                 RETURNING id, count",
+            )
+            .bind(tank_id)
+            .bind(species_id)
+            .bind(delta)
+            .fetch_one(&mut *conn)
+            .await?
+        }
+        None => {
+            return Err(ApiError::NotFound(format!(
+                "No species {} living in tank {}",
+                species_id, tank_id
+            )));
+        }
+    };
+
+    sqlx::query(
+        "INSERT INTO tank_population_changes \
+         (inhabitant_id, tank_id, species_id, change_type, count_delta, count_after, related_tank_id, note) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+    )
+    .bind(inhabitant_id)
+    .bind(tank_id)
+    .bind(species_id)
+    .bind(change_type.as_str())
+    .bind(delta)
+    .bind(count_after)
+    .bind(related_tank_id)
+    .bind(note)
+    .execute(&mut *conn)
+    .await?;
+
+    let inhabitant = sqlx::query_as::<_, TankInhabitant>(&format!("{} WHERE i.id = $1", INHABITANT_SELECT))
+        .bind(inhabitant_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+    Ok(inhabitant)
+}
+
+/// Lists the species currently living in a tank with their population counts.
+pub async fn get_tank_inhabitants(
+    Path(tank_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut conn = state.pool.acquire().await?;
+    ensure_tank_exists(&mut conn, &tank_id).await?;
+    drop(conn);
+
+    let inhabitants = fetch_active_inhabitants(&state.pool, &tank_id).await?;
+    Ok(Json(inhabitants))
+}
+
+/// Returns the population change history for a tank, newest first.
+pub async fn get_population_history(
+    Path(tank_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut conn = state.pool.acquire().await?;
+    ensure_tank_exists(&mut conn, &tank_id).await?;
+    drop(conn);
+
+    let history = sqlx::query(
+        "SELECT * FROM tank_population_changes WHERE tank_id = $1 ORDER BY changed_at DESC, id DESC",
+    )
+    .bind(&tank_id)
+    .try_map(population_change_from_row)
+    .fetch_all(&state.pool)
+    .await?;
+
+    Ok(Json(history))
+}
+
+/// Adds animals of a species to a tank.
+pub async fn add_inhabitants(
+    Path(tank_id): Path<String>,
+    State(state): State<AppState>,
+    Json(request): Json<AddInhabitantsRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    validate_count(request.count)?;
+
+    let mut tx = state.pool.begin().await?;
+    ensure_tank_exists(&mut tx, &tank_id).await?;
+
+    let species_exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM species WHERE id = $1)")
+        .bind(request.species_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    if !species_exists {
+        return Err(ApiError::SpeciesNotFound(format!(
+            "Species with ID {} not found",
+            request.species_id
+        )));
+    }
+
+    let inhabitant = adjust_population(
+        &mut tx,
+        &tank_id,
+        request.species_id,
+        request.count,
+        PopulationChangeType::Added,
+        None,
+        request.note.as_deref(),
+    )
+    .await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        tank_id = %tank_id,
+        species_id = request.species_id,
+        count_added = request.count,
+        count_after = inhabitant.count,
+        operation = "add_tank_inhabitants",
+        "Animals added to tank"
+    );
+
+    Ok((StatusCode::CREATED, Json(inhabitant)))
+}
+
+/// Removes animals of a species from a tank (sold, died, rehomed).
+pub async fn remove_inhabitants(
+    Path((tank_id, species_id)): Path<(String, i32)>,
+    State(state): State<AppState>,
+    Json(request): Json<RemoveInhabitantsRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    validate_count(request.count)?;
+
+    let mut tx = state.pool.begin().await?;
+    ensure_tank_exists(&mut tx, &tank_id).await?;
+
+    let inhabitant = adjust_population(
+        &mut tx,
+        &tank_id,
+        species_id,
+        -request.count,
+        PopulationChangeType::Removed,
+        None,
+        request.note.as_deref(),
+    )
+    .await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        tank_id = %tank_id,
+        species_id = species_id,
+        count_removed = request.count,
+        count_after = inhabitant.count,
+        operation = "remove_tank_inhabitants",
+        "Animals removed from tank"
+    );
+
+    Ok(Json(inhabitant))
+}
+
+/// Moves animals of a species from one tank to another in a single transaction.
+pub async fn move_inhabitants(
+    Path((tank_id, species_id)): Path<(String, i32)>,
+    State(state): State<AppState>,
+    Json(request): Json<MoveInhabitantsRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    validate_count(request.count)?;
+    if request.to_tank_id == tank_id {
+        return Err(ApiError::InvalidInput(
+            "Source and destination tanks must differ".to_string(),
+        ));
+    }
+
+    let mut tx = state.pool.begin().await?;
+    ensure_tank_exists(&mut tx, &tank_id).await?;
+    ensure_tank_exists(&mut tx, &request.to_tank_id).await?;
+
+    let from = adjust_population(
+        &mut tx,
+        &tank_id,
+        species_id,
+        -request.count,
+        PopulationChangeType::MovedOut,
+        Some(&request.to_tank_id),
+        request.note.as_deref(),
+    )
+    .await?;
+    let to = adjust_population(
+        &mut tx,
+        &request.to_tank_id,
+        species_id,
+        request.count,
+        PopulationChangeType::MovedIn,
+        Some(&tank_id),
+        request.note.as_deref(),
+    )
+    .await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        from_tank_id = %tank_id,
+        to_tank_id = %request.to_tank_id,
+        species_id = species_id,
+        count_moved = request.count,
+        operation = "move_tank_inhabitants",
+        "Animals moved between tanks"
+    );
+
+    Ok(Json(MoveInhabitantsResponse { from, to }))
+}
@@ -0,0 +1,512 @@
+use serde::{Deserialize, Serialize};
+use shuttle_axum::axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use sqlx::{postgres::PgRow, Row};
+use tracing::Instrument;
+
+use crate::{ApiError, AppState, Species};
+
+/// How dangerous a predator is to its prey when they share a tank.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AggressionLevel {
+    Low,
+    Moderate,
+    High,
+}
+
+impl AggressionLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AggressionLevel::Low => "low",
+            AggressionLevel::Moderate => "moderate",
+            AggressionLevel::High => "high",
+        }
+    }
+
+    fn from_db(value: &str) -> Result<Self, sqlx::Error> {
+        match value {
+            "low" => Ok(AggressionLevel::Low),
+            "moderate" => Ok(AggressionLevel::Moderate),
+            "high" => Ok(AggressionLevel::High),
+            other => Err(sqlx::Error::Decode(
+                format!("unknown aggression level: {}", other).into(),
+            )),
+        }
+    }
+
+    /// The verdict a single interaction at this level forces on a species mix.
+    fn verdict(&self) -> CompatibilityVerdict {
+        match self {
+            AggressionLevel::Low => CompatibilityVerdict::Compatible,
+            AggressionLevel::Moderate => CompatibilityVerdict::Caution,
+            AggressionLevel::High => CompatibilityVerdict::Incompatible,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompatibilityVerdict {
+    Compatible,
+    Caution,
+    Incompatible,
+}
+
+#[derive(Serialize, Clone)]
+pub struct SpeciesInteraction {
+    pub id: i32,
+    pub predator_id: i32,
+    pub predator_name: String,
+    pub prey_id: i32,
+    pub prey_name: String,
+    pub aggression_level: AggressionLevel,
+    pub notes: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct InteractionPayload {
+    predator_id: i32,
+    prey_id: i32,
+    aggression_level: AggressionLevel,
+    notes: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct InteractionQuery {
+    species_id: Option<i32>,
+}
+
+#[derive(Deserialize)]
+pub struct CompatibilityQuery {
+    /// Comma-separated list of species IDs, e.g. `?species_ids=5,12`; a single ID checks
+    /// whether the species can be kept with its own kind
+    species_ids: String,
+}
+
+#[derive(Serialize)]
+pub struct CompatibilityReason {
+    pub verdict: CompatibilityVerdict,
+    pub species_ids: Vec<i32>,
+    pub reason: String,
+}
+
+#[derive(Serialize)]
+pub struct CompatibilityReport {
+    pub species_ids: Vec<i32>,
+    pub verdict: CompatibilityVerdict,
+    pub reasons: Vec<CompatibilityReason>,
+}
+
+const INTERACTION_SELECT: &str = "SELECT i.id, i.predator_id, predator.name AS predator_name, \
+     i.prey_id, prey.name AS prey_name, i.aggression_level, i.notes \
+     FROM species_interactions i \
+     JOIN species predator ON predator.id = i.predator_id \
+     JOIN species prey ON prey.id = i.prey_id";
+
+fn interaction_from_row(row: PgRow) -> Result<SpeciesInteraction, sqlx::Error> {
+    let aggression_level: String = row.try_get("aggression_level")?;
+    Ok(SpeciesInteraction {
+        id: row.try_get("id")?,
+        predator_id: row.try_get("predator_id")?,
+        predator_name: row.try_get("predator_name")?,
+        prey_id: row.try_get("prey_id")?,
+        prey_name: row.try_get("prey_name")?,
+        aggression_level: AggressionLevel::from_db(&aggression_level)?,
+        notes: row.try_get("notes")?,
+    })
+}
+
+/// Loads every curated interaction where both the predator and the prey are in `species_ids`.
+pub async fn fetch_interactions_among(
+    pool: &sqlx::PgPool,
+    species_ids: &[i32],
+) -> Result<Vec<SpeciesInteraction>, sqlx::Error> {
+    sqlx::query(&format!(
+        "{} WHERE i.predator_id = ANY($1) AND i.prey_id = ANY($1) ORDER BY i.id",
+        INTERACTION_SELECT
+    ))
+    .bind(species_ids)
+    .try_map(interaction_from_row)
+    .fetch_all(pool)
+    .await
+}
+
+/// Combines water parameter overlap and curated predation data into a single verdict.
+///
+/// Every pair of species must share a temperature, pH and salinity window, and every known
+/// predator/prey relationship inside the group contributes a reason at the severity
+/// of its aggression level. The overall verdict is the most severe reason found.
+pub fn assess_compatibility(
+    species: &[Species],
+    interactions: &[SpeciesInteraction],
+) -> CompatibilityReport {
+    let mut reasons = Vec::new();
+
+    for (index, a) in species.iter().enumerate() {
+        for b in &species[index + 1..] {
+            let min_temperature = a.min_temperature.max(b.min_temperature);
+            let max_temperature = a.max_temperature.min(b.max_temperature);
+            if min_temperature > max_temperature {
+                reasons.push(CompatibilityReason {
+                    verdict: CompatibilityVerdict::Incompatible,
+                    species_ids: vec![a.id, b.id],
+                    reason: format!(
+                        "{} ({:.1}–{:.1}°C) and {} ({:.1}–{:.1}°C) have no common temperature range",
+                        a.name, a.min_temperature, a.max_temperature,
+                        b.name, b.min_temperature, b.max_temperature,
+                    ),
+                });
+            }
+
+            let min_ph = a.min_ph.max(b.min_ph);
+            let max_ph = a.max_ph.min(b.max_ph);
+            if min_ph > max_ph {
+                reasons.push(CompatibilityReason {
+                    verdict: CompatibilityVerdict::Incompatible,
+                    species_ids: vec![a.id, b.id],
+                    reason: format!(
+                        "{} (pH {:.1}–{:.1}) and {} (pH {:.1}–{:.1}) have no common pH range",
+                        a.name, a.min_ph, a.max_ph, b.name, b.min_ph, b.max_ph,
+                    ),
+                });
+            }
+
+            let min_salinity = a.min_salinity.max(b.min_salinity);
+            let max_salinity = a.max_salinity.min(b.max_salinity);
+            if min_salinity > max_salinity {
+                reasons.push(CompatibilityReason {
+                    verdict: CompatibilityVerdict::Incompatible,
+                    species_ids: vec![a.id, b.id],
+                    reason: format!(
+                        "{} ({} water, {:.1}–{:.1} ppt) and {} ({} water, {:.1}–{:.1} ppt) have no common salinity range",
+                        a.name, a.water_type, a.min_salinity, a.max_salinity,
+                        b.name, b.water_type, b.min_salinity, b.max_salinity,
+                    ),
+                });
+            }
+        }
+    }
+
+    for interaction in interactions {
+        let reason = if interaction.predator_id == interaction.prey_id {
+            format!(
+                "{} shows {} aggression towards its own kind",
+                interaction.predator_name,
+                interaction.aggression_level.as_str(),
+            )
+        } else {
+            format!(
+                "{} shows {} aggression towards {}",
+                interaction.predator_name,
+                interaction.aggression_level.as_str(),
+                interaction.prey_name,
+            )
+        };
+        let reason = match &interaction.notes {
+            Some(notes) => format!("{}: {}", reason, notes),
+            None => reason,
+        };
+
+        reasons.push(CompatibilityReason {
+            verdict: interaction.aggression_level.verdict(),
+            species_ids: vec![interaction.predator_id, interaction.prey_id],
+            reason,
+        });
+    }
+
+    let verdict = reasons
+        .iter()
+        .map(|r| r.verdict)
+        .max()
+        .unwrap_or(CompatibilityVerdict::Compatible);
+
+    CompatibilityReport {
+        species_ids: species.iter().map(|s| s.id).collect(),
+        verdict,
+        reasons,
+    }
+}
+
+/// Returns a compatibility verdict with reasons for a group of species, or for one species kept with its own kind.
+pub async fn get_compatibility(
+    Query(params): Query<CompatibilityQuery>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    let span = tracing::info_span!("species_compatibility_check", %request_id);
+    async move {
+        let mut species_ids = params
+            .species_ids
+            .split(',')
+            .map(|id| id.trim())
+            .filter(|id| !id.is_empty())
+            .map(|id| {
+                id.parse::<i32>()
+                    .map_err(|_| ApiError::InvalidQuery(format!("Invalid species ID: {}", id)))
+            })
+            .collect::<Result<Vec<i32>, ApiError>>()?;
+        species_ids.sort_unstable();
+        species_ids.dedup();
+
+        // A single species is checked against its own kind
+        if species_ids.is_empty() {
+            return Err(ApiError::InvalidQuery(
+                "At least one species ID is required".to_string(),
+            ));
+        }
+
+        tracing::info!(
+            request_id = %request_id,
+            operation = "species_compatibility_check",
+            species_count = species_ids.len(),
+            "Checking species compatibility"
+        );
+
+        let species = sqlx::query_as::<_, Species>("SELECT * FROM species WHERE id = ANY($1) ORDER BY id")
+            .bind(&species_ids)
+            .fetch_all(&state.pool)
+            .await
+            .map_err(ApiError::Database)?;
+
+        if let Some(missing) = species_ids
+            .iter()
+            .find(|id| !species.iter().any(|s| s.id == **id))
+        {
+            return Err(ApiError::SpeciesNotFound(format!(
+                "Species with ID {} not found",
+                missing
+            )));
+        }
+
+        let interactions = fetch_interactions_among(&state.pool, &species_ids)
+            .await
+            .map_err(ApiError::Database)?;
+
+        let report = assess_compatibility(&species, &interactions);
+
+        tracing::info!(
+            request_id = %request_id,
+            verdict = ?report.verdict,
+            reasons = report.reasons.len(),
+            operation_status = "success",
+            "Species compatibility check completed"
+        );
+
+        Ok::<_, ApiError>(Json(report))
+    }
+    .instrument(span)
+    .await
+}
+
+/// Lists curated interactions, optionally limited to those involving one species.
+pub async fn list_interactions(
+    Query(params): Query<InteractionQuery>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let interactions = match params.species_id {
+        Some(species_id) => sqlx::query(&format!(
+            "{} WHERE i.predator_id = $1 OR i.prey_id = $1 ORDER BY i.id",
+            INTERACTION_SELECT
+        ))
+        .bind(species_id)
+        .try_map(interaction_from_row)
+        .fetch_all(&state.pool)
+        .await?,
+        None => sqlx::query(&format!("{} ORDER BY i.id", INTERACTION_SELECT))
+            .try_map(interaction_from_row)
+            .fetch_all(&state.pool)
+            .await?,
+    };
+
+    Ok(Json(interactions))
+}
+
+/// Creates an interaction, or replaces the existing one for the same predator/prey pair.
+pub async fn upsert_interaction(
+    State(state): State<AppState>,
+    Json(payload): Json<InteractionPayload>,
+) -> Result<impl IntoResponse, ApiError> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+
+    for species_id in [payload.predator_id, payload.prey_id] {
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM species WHERE id = $1)")
+            .bind(species_id)
+            .fetch_one(&state.pool)
+            .await?;
+        if !exists {
+            return Err(ApiError::SpeciesNotFound(format!(
+                "Species with ID {} not found",
+                species_id
+            )));
+        }
+    }
+
+    let id: i32 = sqlx::query_scalar(
+        "INSERT INTO species_interactions (predator_id, prey_id, aggression_level, notes) \
+         VALUES ($1, $2, $3, $4) \
+         ON CONFLICT (predator_id, prey_id) \
+         DO UPDATE SET aggression_level = EXCLUDED.aggression_level, notes = EXCLUDED.notes \
+         RETURNING id",
+    )
+    .bind(payload.predator_id)
+    .bind(payload.prey_id)
+    .bind(payload.aggression_level.as_str())
+    .bind(&payload.notes)
+    .fetch_one(&state.pool)
+    .await?;
+
+    let interaction = sqlx::query(&format!("{} WHERE i.id = $1", INTERACTION_SELECT))
+        .bind(id)
+        .try_map(interaction_from_row)
+        .fetch_one(&state.pool)
+        .await?;
+
+    tracing::info!(
+        request_id = %request_id,
+        interaction_id = id,
+        predator_id = payload.predator_id,
+        prey_id = payload.prey_id,
+        aggression_level = payload.aggression_level.as_str(),
+        operation = "upsert_species_interaction",
+        "Species interaction saved"
+    );
+
+    Ok((StatusCode::CREATED, Json(interaction)))
+}
+
+pub async fn delete_interaction(
+    Path(id): Path<i32>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let result = sqlx::query("DELETE FROM species_interactions WHERE id = $1")
+        .bind(id)
+        .execute(&state.pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(ApiError::NotFound(format!(
+            "Species interaction with ID {} not found",
+            id
+        )));
+    }
+
+    tracing::info!(
+        interaction_id = id,
+        operation = "delete_species_interaction",
+        "Species interaction deleted"
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn species(id: i32, name: &str, temperature: (f64, f64), ph: (f64, f64), salinity: (f64, f64)) -> Species {
+        Species {
+            min_temperature: temperature.0,
+            max_temperature: temperature.1,
+            min_ph: ph.0,
+            max_ph: ph.1,
+            min_salinity: salinity.0,
+            max_salinity: salinity.1,
+            water_type: if salinity.1 > 0.5 { "marine" } else { "fresh" }.to_string(),
+            ..test_support::species(id, name)
+        }
+    }
+
+    fn freshwater(id: i32, name: &str) -> Species {
+        test_support::species(id, name)
+    }
+
+    fn interaction(predator: &Species, prey: &Species, level: AggressionLevel, notes: Option<&str>) -> SpeciesInteraction {
+        SpeciesInteraction {
+            id: 1,
+            predator_id: predator.id,
+            predator_name: predator.name.clone(),
+            prey_id: prey.id,
+            prey_name: prey.name.clone(),
+            aggression_level: level,
+            notes: notes.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn overlapping_species_without_interactions_are_compatible() {
+        let report = assess_compatibility(&[freshwater(1, "Cherry"), freshwater(2, "Amano")], &[]);
+
+        assert_eq!(report.verdict, CompatibilityVerdict::Compatible);
+        assert_eq!(report.species_ids, vec![1, 2]);
+        assert!(report.reasons.is_empty());
+    }
+
+    #[test]
+    fn disjoint_water_parameters_are_incompatible() {
+        let cold = species(1, "Cold", (10.0, 18.0), (7.0, 8.0), (0.0, 0.5));
+        let marine = species(2, "Marine", (22.0, 28.0), (8.1, 8.4), (30.0, 36.0));
+        let report = assess_compatibility(&[cold, marine], &[]);
+
+        assert_eq!(report.verdict, CompatibilityVerdict::Incompatible);
+        assert_eq!(report.reasons.len(), 3);
+        assert!(report.reasons.iter().all(|r| r.species_ids == vec![1, 2]));
+        assert!(report.reasons[0].reason.contains("no common temperature range"));
+        assert!(report.reasons[1].reason.contains("no common pH range"));
+        assert!(report.reasons[2].reason.contains("no common salinity range"));
+    }
+
+    #[test]
+    fn ranges_that_touch_still_overlap() {
+        let low = species(1, "Low", (18.0, 22.0), (6.0, 7.0), (0.0, 0.5));
+        let high = species(2, "High", (22.0, 26.0), (7.0, 8.0), (0.5, 5.0));
+        let report = assess_compatibility(&[low, high], &[]);
+
+        assert_eq!(report.verdict, CompatibilityVerdict::Compatible);
+    }
+
+    #[test]
+    fn interactions_set_the_verdict_at_their_aggression_level() {
+        let species = [freshwater(1, "Crayfish"), freshwater(2, "Shrimp")];
+        let [crayfish, shrimp] = &species;
+
+        let report = assess_compatibility(
+            &species,
+            &[interaction(crayfish, shrimp, AggressionLevel::Moderate, Some("Hunts at night"))],
+        );
+        assert_eq!(report.verdict, CompatibilityVerdict::Caution);
+        assert_eq!(report.reasons[0].reason, "Crayfish shows moderate aggression towards Shrimp: Hunts at night");
+
+        let report = assess_compatibility(
+            &species,
+            &[
+                interaction(crayfish, shrimp, AggressionLevel::Low, None),
+                interaction(crayfish, crayfish, AggressionLevel::High, None),
+            ],
+        );
+        assert_eq!(report.verdict, CompatibilityVerdict::Incompatible);
+        assert_eq!(report.reasons[1].reason, "Crayfish shows high aggression towards its own kind");
+    }
+
+    #[test]
+    fn a_single_species_is_checked_against_its_own_kind() {
+        let crayfish = freshwater(1, "Crayfish");
+
+        let report = assess_compatibility(std::slice::from_ref(&crayfish), &[]);
+        assert_eq!(report.verdict, CompatibilityVerdict::Compatible);
+        assert!(report.reasons.is_empty());
+
+        let report = assess_compatibility(
+            std::slice::from_ref(&crayfish),
+            &[interaction(&crayfish, &crayfish, AggressionLevel::Moderate, None)],
+        );
+        assert_eq!(report.verdict, CompatibilityVerdict::Caution);
+        assert_eq!(report.species_ids, vec![1]);
+        assert_eq!(report.reasons[0].species_ids, vec![1, 1]);
+    }
+}
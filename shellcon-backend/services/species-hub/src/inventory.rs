@@ -0,0 +1,687 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use shuttle_axum::axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use sqlx::{postgres::PgRow, PgConnection, Row};
+use tracing::Instrument;
+
+use crate::{
+    feeding::build_tank_feeding_schedule, feeding_events::FeedingEvent, ApiError, AppState, Tank,
+};
+
+// Window used to estimate consumption of items that no feeding schedule covers
+const RECENT_USAGE_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InventoryCategory {
+    Food,
+    Additive,
+}
+
+impl InventoryCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InventoryCategory::Food => "food",
+            InventoryCategory::Additive => "additive",
+        }
+    }
+
+    fn from_db(value: &str) -> Result<Self, sqlx::Error> {
+        match value {
+            "food" => Ok(InventoryCategory::Food),
+            "additive" => Ok(InventoryCategory::Additive),
+            other => Err(sqlx::Error::Decode(
+                format!("unknown inventory category: {}", other).into(),
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MovementType {
+    Shipment,
+    Feeding,
+    /// Manual usage such as dosing an additive
+    Adjustment,
+    /// A correction after counting stock; not consumption
+    Stocktake,
+}
+
+impl MovementType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MovementType::Shipment => "shipment",
+            MovementType::Feeding => "feeding",
+            MovementType::Adjustment => "adjustment",
+            MovementType::Stocktake => "stocktake",
+        }
+    }
+
+    fn from_db(value: &str) -> Result<Self, sqlx::Error> {
+        match value {
+            "shipment" => Ok(MovementType::Shipment),
+            "feeding" => Ok(MovementType::Feeding),
+            "adjustment" => Ok(MovementType::Adjustment),
+            "stocktake" => Ok(MovementType::Stocktake),
+            other => Err(sqlx::Error::Decode(
+                format!("unknown inventory movement type: {}", other).into(),
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageSource {
+    /// Daily amounts from the generated feeding schedules of every tank
+    FeedingSchedule,
+    /// Average consumption recorded over the last 30 days
+    RecentUsage,
+    /// No consumption is expected
+    None,
+}
+
+#[derive(Serialize)]
+pub struct InventoryItem {
+    pub id: i32,
+    pub name: String,
+    pub category: InventoryCategory,
+    pub food_id: Option<i32>,
+    pub food_name: Option<String>,
+    pub unit: String,
+    pub quantity: f64,
+    pub reorder_lead_days: i32,
+    pub safety_stock: f64,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+pub struct InventoryMovement {
+    pub id: i32,
+    pub item_id: i32,
+    pub movement_type: MovementType,
+    pub quantity_delta: f64,
+    pub quantity_after: f64,
+    pub feeding_event_id: Option<i32>,
+    pub note: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+pub struct InventoryForecast {
+    pub item_id: i32,
+    pub name: String,
+    pub category: InventoryCategory,
+    pub unit: String,
+    pub quantity: f64,
+    pub daily_usage: f64,
+    pub usage_source: UsageSource,
+    pub days_until_empty: Option<f64>,
+    pub runs_out_on: Option<NaiveDate>,
+    /// Stock level at which to reorder: usage over the lead time plus safety stock
+    pub reorder_point: f64,
+    pub reorder_now: bool,
+    pub reorder_by: Option<NaiveDate>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateItemRequest {
+    name: String,
+    category: InventoryCategory,
+    food_id: Option<i32>,
+    unit: Option<String>,
+    quantity: Option<f64>,
+    reorder_lead_days: Option<i32>,
+    safety_stock: Option<f64>,
+}
+
+#[derive(Deserialize)]
+pub struct ShipmentRequest {
+    quantity: f64,
+    note: Option<String>,
+    received_at: Option<DateTime<Utc>>,
+}
+
+/// Either manual usage, given as `quantity_delta`, or a stocktake, given as `counted_quantity`.
+#[derive(Deserialize)]
+pub struct AdjustmentRequest {
+    quantity_delta: Option<f64>,
+    /// Corrects the stock to a counted amount rather than recording usage; the
+    /// difference is worked out against the stock at the time it's applied
+    counted_quantity: Option<f64>,
+    note: Option<String>,
+}
+
+/// How a movement changes an item's stock.
+#[derive(Debug, Clone, Copy)]
+enum StockChange {
+    By(f64),
+    /// Sets the stock outright, e.g. to a counted amount
+    To(f64),
+}
+
+const ITEM_SELECT: &str = "SELECT i.id, i.name, i.category, i.food_id, f.name AS food_name, \
+     i.unit, i.quantity, i.reorder_lead_days, i.safety_stock, i.updated_at \
+     FROM inventory_items i \
+     LEFT JOIN foods f ON f.id = i.food_id";
+
+fn item_from_row(row: PgRow) -> Result<InventoryItem, sqlx::Error> {
+    let category: String = row.try_get("category")?;
+    Ok(InventoryItem {
+        id: row.try_get("id")?,
+        name: row.try_get("name")?,
+        category: InventoryCategory::from_db(&category)?,
+        food_id: row.try_get("food_id")?,
+        food_name: row.try_get("food_name")?,
+        unit: row.try_get("unit")?,
+        quantity: row.try_get("quantity")?,
+        reorder_lead_days: row.try_get("reorder_lead_days")?,
+        safety_stock: row.try_get("safety_stock")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+fn movement_from_row(row: PgRow) -> Result<InventoryMovement, sqlx::Error> {
+    let movement_type: String = row.try_get("movement_type")?;
+    Ok(InventoryMovement {
+        id: row.try_get("id")?,
+        item_id: row.try_get("item_id")?,
+        movement_type: MovementType::from_db(&movement_type)?,
+        quantity_delta: row.try_get("quantity_delta")?,
+        quantity_after: row.try_get("quantity_after")?,
+        feeding_event_id: row.try_get("feeding_event_id")?,
+        note: row.try_get("note")?,
+        occurred_at: row.try_get("occurred_at")?,
+    })
+}
+
+/// Changes an item's stock and records the movement.
+///
+/// Feedings are logged after the food is gone, so their depletion clamps stock at zero;
+/// any other movement that would take stock below zero is rejected.
+async fn apply_movement(
+    conn: &mut PgConnection,
+    item_id: i32,
+    movement_type: MovementType,
+    change: StockChange,
+    feeding_event_id: Option<i32>,
+    note: Option<&str>,
+    occurred_at: Option<DateTime<Utc>>,
+) -> Result<InventoryMovement, ApiError> {
+    let quantity: Option<f64> =
+        sqlx::query_scalar("SELECT quantity FROM inventory_items WHERE id = $1 FOR UPDATE")
+            .bind(item_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+    let quantity = quantity
+        .ok_or_else(|| ApiError::NotFound(format!("Inventory item with ID {} not found", item_id)))?;
+
+    let quantity_after = match change {
+        StockChange::By(quantity_delta) => quantity + quantity_delta,
+        StockChange::To(quantity_after) => quantity_after,
+    };
+    let quantity_after = if quantity_after >= 0.0 {
+        quantity_after
+    } else if movement_type == MovementType::Feeding {
+        tracing::warn!(
+            item_id = item_id,
+            quantity = quantity,
+            change = ?change,
+            "Feeding used more than the recorded stock; stock clamped to zero"
+        );
+        0.0
+    } else {
+        return Err(ApiError::InvalidInput(format!(
+            "Inventory item {} only has {} in stock; cannot apply {}",
+            item_id,
+            quantity,
+            quantity_after - quantity
+        )));
+    };
+
+    sqlx::query("UPDATE inventory_items SET quantity = $2, updated_at = NOW() WHERE id = $1")
+        .bind(item_id)
+        .bind(quantity_after)
+        .execute(&mut *conn)
+        .await?;
+
+    let movement = sqlx::query(
+        "INSERT INTO inventory_movements \
+         (item_id, movement_type, quantity_delta, quantity_after, feeding_event_id, note, occurred_at) \
+         VALUES ($1, $2, $3, $4, $5, $6, COALESCE($7, NOW())) \
+         RETURNING *",
+    )
+    .bind(item_id)
+    .bind(movement_type.as_str())
+    .bind(quantity_after - quantity)
+    .bind(quantity_after)
+    .bind(feeding_event_id)
+    .bind(note)
+    .bind(occurred_at)
+    .try_map(movement_from_row)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(movement)
+}
+
+/// Takes a logged feeding out of stock, if the food it used is inventoried.
+///
+/// Runs inside the caller's transaction so the feeding and the depletion are recorded together.
+pub async fn deplete_for_feeding(
+    conn: &mut PgConnection,
+    event: &FeedingEvent,
+) -> Result<Option<InventoryMovement>, ApiError> {
+    let item_id: Option<i32> = sqlx::query_scalar(
+        "SELECT i.id FROM inventory_items i JOIN foods f ON f.id = i.food_id \
+         WHERE LOWER(f.name) = LOWER($1)",
+    )
+    .bind(&event.food_type)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    let Some(item_id) = item_id else {
+        tracing::debug!(
+            food_type = %event.food_type,
+            "No inventory item tracks this food; skipping depletion"
+        );
+        return Ok(None);
+    };
+
+    let movement = apply_movement(
+        conn,
+        item_id,
+        MovementType::Feeding,
+        StockChange::By(-event.amount_grams),
+        Some(event.id),
+        None,
+        Some(event.fed_at),
+    )
+    .await?;
+
+    Ok(Some(movement))
+}
+
+/// Computes days to run-out and the reorder point for one item.
+pub fn forecast_item(
+    item: InventoryItem,
+    daily_usage: f64,
+    usage_source: UsageSource,
+    today: NaiveDate,
+) -> InventoryForecast {
+    let reorder_point = daily_usage * item.reorder_lead_days as f64 + item.safety_stock;
+    let (days_until_empty, runs_out_on, reorder_by) = if daily_usage > 0.0 {
+        let days = item.quantity / daily_usage;
+        let days_until_reorder = ((item.quantity - reorder_point) / daily_usage).max(0.0);
+        (
+            Some(days),
+            Some(today + Duration::days(days.floor() as i64)),
+            Some(today + Duration::days(days_until_reorder.floor() as i64)),
+        )
+    } else {
+        (None, None, None)
+    };
+
+    InventoryForecast {
+        item_id: item.id,
+        reorder_now: daily_usage > 0.0 && item.quantity <= reorder_point,
+        name: item.name,
+        category: item.category,
+        unit: item.unit,
+        quantity: item.quantity,
+        daily_usage,
+        usage_source,
+        days_until_empty,
+        runs_out_on,
+        reorder_point,
+        reorder_by,
+    }
+}
+
+pub async fn list_items(State(state): State<AppState>) -> Result<impl IntoResponse, ApiError> {
+    let items = sqlx::query(&format!("{} ORDER BY i.name", ITEM_SELECT))
+        .try_map(item_from_row)
+        .fetch_all(&state.pool)
+        .await?;
+
+    Ok(Json(items))
+}
+
+pub async fn create_item(
+    State(state): State<AppState>,
+    Json(request): Json<CreateItemRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(ApiError::InvalidInput("name is required".to_string()));
+    }
+    let quantity = request.quantity.unwrap_or(0.0);
+    let safety_stock = request.safety_stock.unwrap_or(0.0);
+    let reorder_lead_days = request.reorder_lead_days.unwrap_or(7);
+    if quantity < 0.0 || safety_stock < 0.0 || reorder_lead_days < 0 {
+        return Err(ApiError::InvalidInput(
+            "quantity, safety_stock and reorder_lead_days cannot be negative".to_string(),
+        ));
+    }
+
+    // Feedings are logged in grams, so food stock must be too
+    let unit = match (request.category, request.food_id, request.unit.as_deref()) {
+        (InventoryCategory::Food, Some(_), Some(unit)) if unit != "g" => {
+            return Err(ApiError::InvalidInput(
+                "Food items linked to the catalog are stocked in grams (unit \"g\")".to_string(),
+            ));
+        }
+        (InventoryCategory::Food, Some(_), _) => "g".to_string(),
+        (InventoryCategory::Additive, Some(_), _) => {
+            return Err(ApiError::InvalidInput(
+                "Only food items can be linked to the foods catalog".to_string(),
+            ));
+        }
+        (_, None, Some(unit)) if !unit.trim().is_empty() => unit.trim().to_string(),
+        (_, None, _) => {
+            return Err(ApiError::InvalidInput("unit is required".to_string()));
+        }
+    };
+
+    let mut tx = state.pool.begin().await?;
+
+    if let Some(food_id) = request.food_id {
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM foods WHERE id = $1)")
+            .bind(food_id)
+            .fetch_one(&mut *tx)
+            .await?;
+        if !exists {
+            return Err(ApiError::NotFound(format!("Food with ID {} not found", food_id)));
+        }
+    }
+
+    let item_id: i32 = sqlx::query_scalar(
+        "INSERT INTO inventory_items (name, category, food_id, unit, quantity, reorder_lead_days, safety_stock) \
+         VALUES ($1, $2, $3, $4, 0, $5, $6) RETURNING id",
+    )
+    .bind(name)
+    .bind(request.category.as_str())
+    .bind(request.food_id)
+    .bind(&unit)
+    .bind(reorder_lead_days)
+    .bind(safety_stock)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if quantity > 0.0 {
+        apply_movement(
+            &mut tx,
+            item_id,
+            MovementType::Adjustment,
+            StockChange::By(quantity),
+            None,
+            Some("Opening stock"),
+            None,
+        )
+        .await?;
+    }
+
+    let item = sqlx::query(&format!("{} WHERE i.id = $1", ITEM_SELECT))
+        .bind(item_id)
+        .try_map(item_from_row)
+        .fetch_one(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        item_id = item.id,
+        item_name = %item.name,
+        category = item.category.as_str(),
+        operation = "create_inventory_item",
+        "Inventory item created"
+    );
+
+    Ok((StatusCode::CREATED, Json(item)))
+}
+
+/// Records a received shipment and adds it to stock.
+pub async fn receive_shipment(
+    Path(item_id): Path<i32>,
+    State(state): State<AppState>,
+    Json(request): Json<ShipmentRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    if request.quantity <= 0.0 || !request.quantity.is_finite() {
+        return Err(ApiError::InvalidInput(
+            "Shipment quantity must be a positive number".to_string(),
+        ));
+    }
+
+    let mut tx = state.pool.begin().await?;
+    let movement = apply_movement(
+        &mut tx,
+        item_id,
+        MovementType::Shipment,
+        StockChange::By(request.quantity),
+        None,
+        request.note.as_deref(),
+        request.received_at,
+    )
+    .await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        item_id = item_id,
+        quantity_received = request.quantity,
+        quantity_after = movement.quantity_after,
+        operation = "receive_inventory_shipment",
+        "Inventory shipment received"
+    );
+
+    Ok((StatusCode::CREATED, Json(movement)))
+}
+
+/// Records manual usage (e.g. dosing an additive) or a stocktake correction.
+pub async fn adjust_stock(
+    Path(item_id): Path<i32>,
+    State(state): State<AppState>,
+    Json(request): Json<AdjustmentRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let (movement_type, change) = match (request.quantity_delta, request.counted_quantity) {
+        (Some(quantity_delta), None) if quantity_delta != 0.0 && quantity_delta.is_finite() => {
+            (MovementType::Adjustment, StockChange::By(quantity_delta))
+        }
+        (Some(_), None) => {
+            return Err(ApiError::InvalidInput(
+                "quantity_delta must be a non-zero number".to_string(),
+            ));
+        }
+        (None, Some(counted)) if counted >= 0.0 && counted.is_finite() => {
+            (MovementType::Stocktake, StockChange::To(counted))
+        }
+        (None, Some(_)) => {
+            return Err(ApiError::InvalidInput(
+                "counted_quantity must be a non-negative number".to_string(),
+            ));
+        }
+        _ => {
+            return Err(ApiError::InvalidInput(
+                "Give either quantity_delta for usage or counted_quantity for a stocktake".to_string(),
+            ));
+        }
+    };
+
+    // A stocktake's delta is taken against the stock read under the item's row lock,
+    // so a movement committed concurrently can't be counted twice
+    let mut tx = state.pool.begin().await?;
+    let movement = apply_movement(
+        &mut tx,
+        item_id,
+        movement_type,
+        change,
+        None,
+        request.note.as_deref(),
+        None,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok((StatusCode::CREATED, Json(movement)))
+}
+
+pub async fn list_movements(
+    Path(item_id): Path<i32>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM inventory_items WHERE id = $1)")
+        .bind(item_id)
+        .fetch_one(&state.pool)
+        .await?;
+    if !exists {
+        return Err(ApiError::NotFound(format!(
+            "Inventory item with ID {} not found",
+            item_id
+        )));
+    }
+
+    let movements = sqlx::query(
+        "SELECT * FROM inventory_movements WHERE item_id = $1 ORDER BY occurred_at DESC, id DESC",
+    )
+    .bind(item_id)
+    .try_map(movement_from_row)
+    .fetch_all(&state.pool)
+    .await?;
+
+    Ok(Json(movements))
+}
+
+/// Predicts when each item runs out and whether it should be reordered.
+///
+/// Foods use the daily amounts from every tank's generated feeding schedule;
+/// other items fall back to their average recorded usage over the last 30 days.
+pub async fn get_forecast(State(state): State<AppState>) -> Result<impl IntoResponse, ApiError> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    let span = tracing::info_span!("inventory_forecast", %request_id);
+    async move {
+        let tanks = sqlx::query_as::<_, Tank>("SELECT * FROM tanks ORDER BY id")
+            .fetch_all(&state.pool)
+            .await?;
+
+        let mut scheduled_daily_usage: HashMap<String, f64> = HashMap::new();
+        for tank in &tanks {
+            let schedule = build_tank_feeding_schedule(&state, tank).await?;
+            for food in schedule.foods {
+                *scheduled_daily_usage.entry(food.food_type.to_lowercase()).or_default() +=
+                    food.daily_amount_grams;
+            }
+        }
+
+        let recent_usage: HashMap<i32, f64> = sqlx::query_as::<_, (i32, f64)>(
+            "SELECT item_id, -SUM(quantity_delta) FROM inventory_movements \
+             WHERE quantity_delta < 0 AND movement_type <> 'stocktake' \
+               AND occurred_at > NOW() - make_interval(days => $1) \
+             GROUP BY item_id",
+        )
+        .bind(RECENT_USAGE_DAYS as i32)
+        .fetch_all(&state.pool)
+        .await?
+        .into_iter()
+        .map(|(item_id, used)| (item_id, used / RECENT_USAGE_DAYS as f64))
+        .collect();
+
+        let items = sqlx::query(&format!("{} ORDER BY i.name", ITEM_SELECT))
+            .try_map(item_from_row)
+            .fetch_all(&state.pool)
+            .await?;
+
+        let today = Utc::now().date_naive();
+        let forecasts: Vec<InventoryForecast> = items
+            .into_iter()
+            .map(|item| {
+                let scheduled = item
+                    .food_name
+                    .as_ref()
+                    .and_then(|food| scheduled_daily_usage.get(&food.to_lowercase()))
+                    .copied();
+                let (daily_usage, usage_source) = match (scheduled, recent_usage.get(&item.id)) {
+                    (Some(usage), _) => (usage, UsageSource::FeedingSchedule),
+                    (None, Some(usage)) => (*usage, UsageSource::RecentUsage),
+                    (None, None) => (0.0, UsageSource::None),
+                };
+                forecast_item(item, daily_usage, usage_source, today)
+            })
+            .collect();
+
+        tracing::info!(
+            request_id = %request_id,
+            items_forecast = forecasts.len(),
+            items_to_reorder = forecasts.iter().filter(|f| f.reorder_now).count(),
+            operation_status = "success",
+            "Inventory forecast completed"
+        );
+
+        Ok::<_, ApiError>(Json(forecasts))
+    }
+    .instrument(span)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(quantity: f64) -> InventoryItem {
+        InventoryItem {
+            id: 1,
+            name: "Algae wafers".to_string(),
+            category: InventoryCategory::Food,
+            food_id: Some(3),
+            food_name: Some("algae wafers".to_string()),
+            unit: "g".to_string(),
+            quantity,
+            reorder_lead_days: 3,
+            safety_stock: 5.0,
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn forecast_reorder_point_and_run_out() {
+        let today = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+        let day = |offset: i64| today + Duration::days(offset);
+        // 3 days' lead time at 10 g/day plus 5 g of safety stock reorders at 35 g
+        let cases = [
+            ("well stocked", 100.0, Some(10.0), Some(day(10)), Some(day(6)), false),
+            ("part days round down", 25.0, Some(2.5), Some(day(2)), Some(day(0)), true),
+            ("exactly at the reorder point", 35.0, Some(3.5), Some(day(3)), Some(day(0)), true),
+            ("below the reorder point", 30.0, Some(3.0), Some(day(3)), Some(day(0)), true),
+            ("out of stock", 0.0, Some(0.0), Some(day(0)), Some(day(0)), true),
+        ];
+
+        for (name, quantity, days_until_empty, runs_out_on, reorder_by, reorder_now) in cases {
+            let forecast = forecast_item(item(quantity), 10.0, UsageSource::FeedingSchedule, today);
+
+            assert_eq!(forecast.reorder_point, 35.0, "{}", name);
+            assert_eq!(forecast.days_until_empty, days_until_empty, "{}", name);
+            assert_eq!(forecast.runs_out_on, runs_out_on, "{}", name);
+            assert_eq!(forecast.reorder_by, reorder_by, "{}", name);
+            assert_eq!(forecast.reorder_now, reorder_now, "{}", name);
+        }
+    }
+
+    #[test]
+    fn unused_items_never_run_out() {
+        let today = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+        let forecast = forecast_item(item(2.0), 0.0, UsageSource::None, today);
+
+        assert_eq!(forecast.reorder_point, 5.0);
+        assert_eq!(forecast.days_until_empty, None);
+        assert_eq!(forecast.runs_out_on, None);
+        assert_eq!(forecast.reorder_by, None);
+        // Below safety stock, but nothing is being used
+        assert!(!forecast.reorder_now);
+        assert_eq!(forecast.usage_source, UsageSource::None);
+    }
+}
@@ -1,10 +1,31 @@
+mod aqua_monitor;
+mod bioload;
+mod breeding;
+mod calendar;
+mod catalog;
+mod catalog_cache;
 mod challenges;
+mod feeding;
+mod feeding_events;
+mod foods;
+mod health;
+mod history;
+mod inhabitants;
+mod interactions;
+mod inventory;
+mod molts;
+mod stocking;
+mod tank_sync;
+mod tanks;
+mod taxonomy;
+#[cfg(test)]
+mod test_support;
 
 use shuttle_axum::axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
-    routing::get,
+    routing::{delete, get, post},
     Json, Router,
 };
 // CORS removed - managed by frontend
@@ -13,6 +34,7 @@ use serde_json::json;
 use sqlx::{PgPool, Row};
 use std::fs;
 use thiserror::Error;
+use tracing::Instrument;
 
 // Custom Error Type for species-hub service
 #[derive(Debug, Error)]
@@ -23,12 +45,24 @@ pub enum ApiError {
     #[error("Species not found: {0}")]
     SpeciesNotFound(String),
     
+    #[error("Tank not found: {0}")]
+    TankNotFound(String),
+    
     #[error("Invalid query parameter: {0}")]
     InvalidQuery(String),
     
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+    
+    #[error("Not found: {0}")]
+    NotFound(String),
+    
     #[error("Feeding schedule error: {0}")]
     ScheduleError(String),
     
+    #[error("External service error: {0}")]
+    ExternalService(#[from] reqwest::Error),
+    
     #[error("Internal server error: {0}")]
     InternalError(String),
 }
@@ -39,8 +73,12 @@ impl IntoResponse for ApiError {
         let (status, error_message) = match &self {
             ApiError::Database(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string()),
             ApiError::SpeciesNotFound(id) => (StatusCode::NOT_FOUND, format!("Species not found: {}", id)),
+            ApiError::TankNotFound(id) => (StatusCode::NOT_FOUND, format!("Tank not found: {}", id)),
             ApiError::InvalidQuery(msg) => (StatusCode::BAD_REQUEST, format!("Invalid query: {}", msg)),
+            ApiError::InvalidInput(msg) => (StatusCode::BAD_REQUEST, format!("Invalid input: {}", msg)),
+            ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, format!("Not found: {}", msg)),
             ApiError::ScheduleError(msg) => (StatusCode::UNPROCESSABLE_ENTITY, format!("Feeding schedule error: {}", msg)),
+            ApiError::ExternalService(_) => (StatusCode::BAD_GATEWAY, "External service error".to_string()),
             ApiError::InternalError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg.to_string()),
         };
         
@@ -67,6 +105,11 @@ impl IntoResponse for ApiError {
 #[derive(Clone)]
 struct AppState {
     pool: PgPool,
+    aqua_monitor: aqua_monitor::AquaMonitorClient,
+    // Timezone for feeding times of tanks without their own
+    facility_timezone: chrono_tz::Tz,
+    // Serialized species responses, invalidated on catalog writes
+    catalog_cache: catalog_cache::CatalogCache,
 }
 
 #[derive(Serialize, Deserialize, sqlx::FromRow)]
@@ -80,12 +123,30 @@ struct Species {
     min_ph: f64,
     max_ph: f64,
     diet_type: String,
+    // Salinity in ppt; freshwater species use 0.0–0.5
+    min_salinity: f64,
+    max_salinity: f64,
+    // Minimum dissolved oxygen in mg/L
+    min_oxygen: f64,
+    // General and carbonate hardness in dGH/dKH; GH is unset for marine species
+    min_gh: Option<f64>,
+    max_gh: Option<f64>,
+    min_kh: Option<f64>,
+    max_kh: Option<f64>,
+    // "fresh", "brackish" or "marine"
+    water_type: String,
+    // "beginner", "intermediate" or "advanced"
+    care_level: String,
 }
 
 #[derive(Deserialize)]
 struct SpeciesQuery {
     name: Option<String>,
     scientific_name: Option<String>,
+    // Taxonomy node names, matched case-insensitively
+    order: Option<String>,
+    family: Option<String>,
+    genus: Option<String>,
 }
 
 #[derive(Deserialize, Default)]
@@ -97,9 +158,32 @@ struct FeedingScheduleParams {
 #[derive(Serialize)]
 struct FeedingSchedule {
     species_id: i32,
+    // Local "HH:MM" times in `timezone`
     feeding_times: Vec<String>,
+    timezone: String,
     food_type: String,
+    // Amount per feeding; daily_amount_grams is spread evenly across feeding_times
     amount_grams: f64,
+    daily_amount_grams: f64,
+    factors: Vec<FeedingFactor>,
+}
+
+// One step of the feeding amount model, returned so staff can see why an amount was chosen
+#[derive(Serialize)]
+struct FeedingFactor {
+    factor: &'static str,
+    multiplier: f64,
+    reason: String,
+}
+
+// Tank conditions that scale a species' feeding amount; unknown values leave the amount unchanged
+#[derive(Default, Clone)]
+struct FeedingConditions {
+    timezone: Option<chrono_tz::Tz>,
+    tank_type: Option<String>,
+    volume_liters: Option<f64>,
+    population: Option<i32>,
+    water_temperature: Option<f64>,
 }
 
 // Tank information structure - mirrors data from aqua-monitor
@@ -110,11 +194,16 @@ struct Tank {
     tank_type: String,
     volume: f64,
     description: Option<String>,
+    // IANA timezone name; None means the facility timezone applies
+    timezone: Option<String>,
+    cleaning_interval_days: i32,
+    last_cleaned_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 #[shuttle_runtime::main]
 async fn axum(
     #[shuttle_shared_db::Postgres] pool: PgPool,
+    #[shuttle_runtime::Secrets] secrets: shuttle_runtime::SecretStore,
 ) -> shuttle_axum::ShuttleAxum {
     // Initialize database with logging and proper error handling
     tracing::info!("Running database migrations for species-hub...");
@@ -124,15 +213,110 @@ async fn axum(
     }
     tracing::info!("Database migrations completed successfully for species-hub.");
     
+    // Shared client for aqua-monitor; set AQUA_MONITOR_URL = "stub" to use canned readings
+    let aqua_monitor = aqua_monitor::AquaMonitorClient::from_config(secrets.get("AQUA_MONITOR_URL"))
+        .map_err(anyhow::Error::msg)?;
+    tracing::info!(stub = aqua_monitor.is_stub(), "Configured aqua-monitor client for species-hub.");
+    
+    // Feeding times are wall-clock times in the tank's timezone, falling back to the facility's
+    let facility_timezone = match secrets.get("FACILITY_TIMEZONE") {
+        Some(name) => name
+            .parse::<chrono_tz::Tz>()
+            .map_err(|e| anyhow::anyhow!("Invalid FACILITY_TIMEZONE {name:?}: {e}"))?,
+        None => chrono_tz::Tz::UTC,
+    };
+    tracing::info!(timezone = %facility_timezone, "Configured facility timezone for species-hub.");
+    
     // Initialize state
-    let state = AppState { pool };
+    let state = AppState {
+        pool,
+        aqua_monitor,
+        facility_timezone,
+        catalog_cache: catalog_cache::CatalogCache::default(),
+    };
     
     // Build router
     let router = Router::new()
         .layer(tower_http::trace::TraceLayer::new_for_http())
-        .route("/api/species", get(challenges::get_species))
+        .route("/api/species", get(search_species))
         .route("/api/species/:id", get(get_species_by_id))
         .route("/api/species/:species_id/feeding-schedule", get(challenges::get_feeding_schedule))
+        .route("/api/species/compatibility", get(interactions::get_compatibility))
+        .route("/api/species/export", get(catalog::export_species))
+        .route("/api/admin/species/import", post(catalog::import_species))
+        .route("/api/admin/species/:species_id/taxonomy", post(taxonomy::assign_species_genus))
+        .route("/api/taxonomy", get(taxonomy::get_taxonomy_tree))
+        .route("/api/admin/taxonomy", post(taxonomy::upsert_taxonomy_path))
+        .route("/api/species/:species_id/revisions", get(history::list_species_revisions))
+        .route("/api/species/:species_id/revisions/diff", get(history::diff_species_revisions))
+        .route(
+            "/api/species/:species_id/revisions/:revision/rollback",
+            post(history::rollback_species),
+        )
+        .route("/api/tanks/:tank_id/revisions", get(history::list_tank_revisions))
+        .route("/api/tanks/:tank_id/revisions/diff", get(history::diff_tank_revisions))
+        .route("/api/tanks/:tank_id/revisions/:revision/rollback", post(history::rollback_tank))
+        .route("/api/foods", get(foods::list_foods))
+        .route("/api/admin/foods", post(foods::upsert_food))
+        .route("/api/admin/foods/:id", delete(foods::delete_food))
+        .route("/api/inventory", get(inventory::list_items).post(inventory::create_item))
+        .route("/api/inventory/forecast", get(inventory::get_forecast))
+        .route("/api/inventory/:item_id/shipments", post(inventory::receive_shipment))
+        .route("/api/inventory/:item_id/adjustments", post(inventory::adjust_stock))
+        .route("/api/inventory/:item_id/movements", get(inventory::list_movements))
+        .route("/api/feeding/schedule", get(feeding::get_feeding_schedules))
+        .route("/api/tanks/:tank_id/feeding-schedule", get(feeding::get_tank_feeding_schedule))
+        .route("/api/feeding/status", get(feeding_events::get_feeding_status))
+        .route("/api/tanks/:tank_id/feeding-status", get(feeding_events::get_tank_feeding_status))
+        .route(
+            "/api/tanks/:tank_id/feedings",
+            get(feeding_events::list_feedings).post(feeding_events::log_feeding),
+        )
+        .route("/api/tanks", get(tanks::list_tanks))
+        .route("/api/tanks/consistency", get(tank_sync::check_tank_consistency))
+        .route("/api/admin/tanks/sync", post(tank_sync::sync_tanks))
+        .route("/api/tanks/:tank_id", get(tanks::get_tank).patch(tanks::update_tank))
+        .route("/api/tanks/:tank_id/cleanings", post(tanks::record_cleaning))
+        .route("/api/calendar.ics", get(calendar::get_facility_calendar))
+        .route("/api/tanks/:tank_id/calendar.ics", get(calendar::get_tank_calendar))
+        .route("/api/tanks/:tank_id/suitable-species", get(stocking::get_suitable_species))
+        .route("/api/tanks/:tank_id/bioload", get(bioload::get_tank_bioload))
+        .route("/api/bioload-profiles", get(bioload::list_bioload_profiles))
+        .route("/api/admin/bioload-profiles", post(bioload::upsert_bioload_profile))
+        .route(
+            "/api/tanks/:tank_id/inhabitants",
+            get(inhabitants::get_tank_inhabitants).post(inhabitants::add_inhabitants),
+        )
+        .route("/api/tanks/:tank_id/inhabitants/history", get(inhabitants::get_population_history))
+        .route("/api/tanks/:tank_id/inhabitants/:species_id/move", post(inhabitants::move_inhabitants))
+        .route("/api/tanks/:tank_id/inhabitants/:species_id/remove", post(inhabitants::remove_inhabitants))
+        .route("/api/tanks/:tank_id/inhabitants/:species_id/molts", post(molts::log_molt))
+        .route("/api/tanks/:tank_id/molts", get(molts::list_tank_molts))
+        .route("/api/tanks/:tank_id/molts/forecast", get(molts::get_tank_molt_forecast))
+        .route("/api/molts/forecast", get(molts::get_molt_forecast))
+        .route("/api/molt-intervals", get(molts::list_molt_intervals))
+        .route("/api/admin/molt-intervals", post(molts::upsert_molt_interval))
+        .route("/api/tanks/:tank_id/inhabitants/:species_id/broods", post(breeding::record_brood))
+        .route("/api/tanks/:tank_id/broods", get(breeding::list_tank_broods))
+        .route("/api/broods/:brood_id/survival", post(breeding::record_survival))
+        .route("/api/reproduction/stats", get(breeding::get_reproduction_stats))
+        .route("/api/species/:species_id/reproduction-stats", get(breeding::get_species_reproduction_stats))
+        .route(
+            "/api/tanks/:tank_id/incidents",
+            get(health::list_tank_incidents).post(health::open_incident),
+        )
+        .route("/api/tanks/:tank_id/medication-check", get(health::check_medication))
+        .route("/api/incidents", get(health::list_incidents))
+        .route("/api/incidents/:incident_id", get(health::get_incident).patch(health::update_incident))
+        .route("/api/incidents/:incident_id/treatments", post(health::add_treatment))
+        .route("/api/incidents/:incident_id/close", post(health::close_incident))
+        .route("/api/medications", get(health::list_medications))
+        .route("/api/admin/medications", post(health::upsert_medication))
+        .route(
+            "/api/admin/species-interactions",
+            get(interactions::list_interactions).post(interactions::upsert_interaction),
+        )
+        .route("/api/admin/species-interactions/:id", delete(interactions::delete_interaction))
         .route(
             "/api/challenges/2/validate",
             get(validate_query_optimization),
@@ -143,9 +327,56 @@ async fn axum(
     Ok(router.into())
 }
 
+/// Answers conditional and repeated catalog searches from the catalog version, running
+/// the search itself only on a cache miss.
+async fn search_species(
+    Query(params): Query<SpeciesQuery>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, ApiError> {
+    let span = tracing::info_span!("species_catalog_revalidation");
+    async move {
+        let version = catalog_cache::fetch_version(&state.pool).await?;
+        if version.matches(&headers) {
+            tracing::info!(
+                operation = "species_catalog_search",
+                operation_status = "not_modified",
+                catalog_version = version.version,
+                "Species catalog unchanged since client's copy"
+            );
+            return Ok(version.not_modified());
+        }
+        let cache_key = catalog_cache::search_key(&params);
+        if let Some(body) = state.catalog_cache.search(version.version, &cache_key) {
+            tracing::info!(
+                operation = "species_catalog_search",
+                operation_status = "cache_hit",
+                catalog_version = version.version,
+                "Species catalog search served from cache"
+            );
+            return Ok(version.respond(body));
+        }
+
+        // Keep the search's JSON body as is rather than serializing the results again
+        let response = challenges::get_species(Query(params), State(state.clone()))
+            .await?
+            .into_response();
+        let body = shuttle_axum::axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .map_err(|e| ApiError::InternalError(format!("Failed to read species search response: {}", e)))?;
+        state
+            .catalog_cache
+            .store_search(version.version, cache_key, body.clone());
+        Ok::<_, ApiError>(version.respond(body))
+    }
+    .instrument(span)
+    .await
+}
+
 async fn get_species_by_id(
     Path(id): Path<i32>,
     State(state): State<AppState>,
+    headers: HeaderMap,
 ) -> Result<impl IntoResponse, ApiError> {
     // Add request ID for correlation
     let request_id = uuid::Uuid::new_v4().to_string();
@@ -153,71 +384,106 @@ async fn get_species_by_id(
     
     // Create a span with request ID context to avoid nesting issues
     let span = tracing::info_span!("species_profile_lookup", %request_id);
-    let _guard = span.enter();
-    
-    tracing::info!(
-        request_id = %request_id,
-        species_id = id,
-        operation = "species_profile_lookup",
-        "Starting species profile lookup"
-    );
-    
-    // Check if ID is valid
-    if id <= 0 {
-        return Err(ApiError::InvalidQuery(format!("Invalid species ID: {}", id)));
-    }
-    
-    // Use runtime query instead of compile-time checked macro
-    let species = sqlx::query("SELECT * FROM species WHERE id = $1")
-        .bind(id)
-        .map(|row: sqlx::postgres::PgRow| {
-            Species {
-                id: row.get("id"),
-                name: row.get("name"),
-                scientific_name: row.get("scientific_name"),
-                description: row.get("description"),
-                min_temperature: row.get("min_temperature"),
-                max_temperature: row.get("max_temperature"),
-                min_ph: row.get("min_ph"),
-                max_ph: row.get("max_ph"),
-                diet_type: row.get("diet_type"),
-            }
-        })
-        .fetch_optional(&state.pool)
-        .await
-        .map_err(ApiError::Database)?;
+    async move {
+        tracing::info!(
+            request_id = %request_id,
+            species_id = id,
+            operation = "species_profile_lookup",
+            "Starting species profile lookup"
+        );
     
-    // Calculate operation duration
-    let elapsed = start_time.elapsed().as_millis() as f64;
+        // Check if ID is valid
+        if id <= 0 {
+            return Err(ApiError::InvalidQuery(format!("Invalid species ID: {}", id)));
+        }
     
-    // Log based on operation result
-    match species {
-        Some(s) => {
-            // Extract name for logging before moving s into the response
-            let species_name = s.name.clone();
-            
+        let version = catalog_cache::fetch_version(&state.pool).await?;
+        if version.matches(&headers) {
             tracing::info!(
                 request_id = %request_id,
                 species_id = id,
-                species_name = %species_name,
-                db_query_time_ms = elapsed,
-                operation_status = "success",
-                "Species profile lookup succeeded"
+                operation_status = "not_modified",
+                catalog_version = version.version,
+                "Species profile unchanged since client's copy"
             );
-            
-            Ok(Json(s))
-        },
-        None => {
-            tracing::warn!(
+            return Ok(version.not_modified());
+        }
+        if let Some(body) = state.catalog_cache.profile(version.version, id) {
+            tracing::info!(
                 request_id = %request_id,
                 species_id = id,
-                db_query_time_ms = elapsed,
-                operation_status = "not_found",
-                "Species profile lookup failed: species not found"
+                operation_status = "cache_hit",
+                catalog_version = version.version,
+                "Species profile served from cache"
             );
-            Err(ApiError::SpeciesNotFound(format!("Species with ID {} not found", id)))
+            return Ok(version.respond(body));
+        }
+
+        // Use runtime query instead of compile-time checked macro
+        let species = sqlx::query("SELECT * FROM species WHERE id = $1")
+            .bind(id)
+            .map(|row: sqlx::postgres::PgRow| {
+                Species {
+                    id: row.get("id"),
+                    name: row.get("name"),
+                    scientific_name: row.get("scientific_name"),
+                    description: row.get("description"),
+                    min_temperature: row.get("min_temperature"),
+                    max_temperature: row.get("max_temperature"),
+                    min_ph: row.get("min_ph"),
+                    max_ph: row.get("max_ph"),
+                    diet_type: row.get("diet_type"),
+                    min_salinity: row.get("min_salinity"),
+                    max_salinity: row.get("max_salinity"),
+                    min_oxygen: row.get("min_oxygen"),
+                    min_gh: row.get("min_gh"),
+                    max_gh: row.get("max_gh"),
+                    min_kh: row.get("min_kh"),
+                    max_kh: row.get("max_kh"),
+                    water_type: row.get("water_type"),
+                    care_level: row.get("care_level"),
+                }
+            })
+            .fetch_optional(&state.pool)
+            .await
+            .map_err(ApiError::Database)?;
+    
+        // Calculate operation duration
+        let elapsed = start_time.elapsed().as_millis() as f64;
+    
+        // Log based on operation result
+        match species {
+            Some(s) => {
+                // Extract name for logging before moving s into the response
+                let species_name = s.name.clone();
+            
+                tracing::info!(
+                    request_id = %request_id,
+                    species_id = id,
+                    species_name = %species_name,
+                    db_query_time_ms = elapsed,
+                    operation_status = "success",
+                    "Species profile lookup succeeded"
+                );
+            
+                let body = catalog_cache::serialize(&s)?;
+                state.catalog_cache.store_profile(version.version, id, body.clone());
+                Ok(version.respond(body))
+            },
+            None => {
+                tracing::warn!(
+                    request_id = %request_id,
+                    species_id = id,
+                    db_query_time_ms = elapsed,
+                    operation_status = "not_found",
+                    "Species profile lookup failed: species not found"
+                );
+                Err(ApiError::SpeciesNotFound(format!("Species with ID {} not found", id)))
+            }
         }
     }
+    .instrument(span)
+    .await
 }
 
 async fn health_check() -> impl IntoResponse {
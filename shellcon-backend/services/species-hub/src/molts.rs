@@ -0,0 +1,566 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use shuttle_axum::axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use sqlx::{postgres::PgRow, Row};
+
+use crate::{inhabitants::ensure_tank_exists, ApiError, AppState};
+
+// Default look-ahead for upcoming molts
+const DEFAULT_HORIZON_DAYS: i64 = 7;
+const MAX_HORIZON_DAYS: i64 = 90;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MoltOutcome {
+    Complete,
+    Failed,
+}
+
+impl MoltOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MoltOutcome::Complete => "complete",
+            MoltOutcome::Failed => "failed",
+        }
+    }
+
+    fn from_db(value: &str) -> Result<Self, sqlx::Error> {
+        match value {
+            "complete" => Ok(MoltOutcome::Complete),
+            "failed" => Ok(MoltOutcome::Failed),
+            other => Err(sqlx::Error::Decode(
+                format!("unknown molt outcome: {}", other).into(),
+            )),
+        }
+    }
+}
+
+/// Where a population is in its molt cycle, ordered from least to most urgent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MoltStatus {
+    /// The species has no typical interval on record
+    Unknown,
+    NotDue,
+    /// The molt window opens within the requested horizon
+    Upcoming,
+    /// The molt window is open now
+    Due,
+    /// The molt window has closed without a recorded molt
+    Overdue,
+}
+
+/// What the expected molt date is counted from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MoltBaseline {
+    LastMolt,
+    /// No complete molt has been logged, so the date the animals were added is used
+    AddedToTank,
+}
+
+#[derive(Serialize)]
+pub struct MoltEvent {
+    pub id: i32,
+    pub inhabitant_id: i32,
+    pub tank_id: String,
+    pub species_id: i32,
+    pub species_name: String,
+    pub observed_at: DateTime<Utc>,
+    pub outcome: MoltOutcome,
+    pub notes: Option<String>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+pub struct MoltInterval {
+    pub species_id: i32,
+    pub species_name: String,
+    pub typical_interval_days: i32,
+    pub window_days: i32,
+    pub notes: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct MoltPrediction {
+    pub inhabitant_id: i32,
+    pub tank_id: String,
+    pub species_id: i32,
+    pub species_name: String,
+    pub count: i32,
+    pub status: MoltStatus,
+    pub last_molt_at: Option<DateTime<Utc>>,
+    pub last_molt_outcome: Option<MoltOutcome>,
+    pub baseline: MoltBaseline,
+    pub typical_interval_days: Option<i32>,
+    pub expected_molt_at: Option<DateTime<Utc>>,
+    pub window_opens_at: Option<DateTime<Utc>>,
+    pub window_closes_at: Option<DateTime<Utc>>,
+    /// Whole days since the window closed, for overdue populations
+    pub days_overdue: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct MoltForecast {
+    pub generated_at: DateTime<Utc>,
+    pub horizon_days: i64,
+    pub overdue: usize,
+    pub due: usize,
+    pub upcoming: usize,
+    pub predictions: Vec<MoltPrediction>,
+}
+
+#[derive(Deserialize)]
+pub struct LogMoltRequest {
+    observed_at: Option<DateTime<Utc>>,
+    outcome: MoltOutcome,
+    notes: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct MoltForecastQuery {
+    days: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct MoltIntervalPayload {
+    species_id: i32,
+    typical_interval_days: i32,
+    window_days: Option<i32>,
+    notes: Option<String>,
+}
+
+/// An active population with the molt data needed to predict its next molt.
+#[derive(sqlx::FromRow)]
+struct MoltCycleRow {
+    inhabitant_id: i32,
+    tank_id: String,
+    species_id: i32,
+    species_name: String,
+    count: i32,
+    added_at: DateTime<Utc>,
+    typical_interval_days: Option<i32>,
+    window_days: Option<i32>,
+    last_complete_molt_at: Option<DateTime<Utc>>,
+    last_molt_at: Option<DateTime<Utc>>,
+    last_molt_outcome: Option<String>,
+}
+
+const MOLT_EVENT_SELECT: &str = "SELECT e.id, e.inhabitant_id, i.tank_id, i.species_id, s.name AS species_name, \
+     e.observed_at, e.outcome, e.notes, e.recorded_at \
+     FROM molt_events e \
+     JOIN tank_inhabitants i ON i.id = e.inhabitant_id \
+     JOIN species s ON s.id = i.species_id";
+
+const MOLT_CYCLE_SELECT: &str = "SELECT i.id AS inhabitant_id, i.tank_id, i.species_id, s.name AS species_name, \
+     i.count, i.added_at, m.typical_interval_days, m.window_days, \
+     (SELECT MAX(c.observed_at) FROM molt_events c WHERE c.inhabitant_id = i.id AND c.outcome = 'complete') AS last_complete_molt_at, \
+     latest.observed_at AS last_molt_at, latest.outcome AS last_molt_outcome \
+     FROM tank_inhabitants i \
+     JOIN species s ON s.id = i.species_id \
+     LEFT JOIN species_molt_intervals m ON m.species_id = i.species_id \
+     LEFT JOIN LATERAL ( \
+         SELECT e.observed_at, e.outcome FROM molt_events e \
+         WHERE e.inhabitant_id = i.id ORDER BY e.observed_at DESC LIMIT 1 \
+     ) latest ON TRUE \
+     WHERE i.removed_at IS NULL";
+
+fn molt_event_from_row(row: PgRow) -> Result<MoltEvent, sqlx::Error> {
+    let outcome: String = row.try_get("outcome")?;
+    Ok(MoltEvent {
+        id: row.try_get("id")?,
+        inhabitant_id: row.try_get("inhabitant_id")?,
+        tank_id: row.try_get("tank_id")?,
+        species_id: row.try_get("species_id")?,
+        species_name: row.try_get("species_name")?,
+        observed_at: row.try_get("observed_at")?,
+        outcome: MoltOutcome::from_db(&outcome)?,
+        notes: row.try_get("notes")?,
+        recorded_at: row.try_get("recorded_at")?,
+    })
+}
+
+/// Predicts the next molt of a population from its last complete molt and the species' typical interval.
+///
+/// Failed molts don't restart the cycle: the animals that survived still carry the old
+/// shell, so the prediction keeps counting from the last successful molt.
+fn predict_molt(row: MoltCycleRow, now: DateTime<Utc>, horizon: Duration) -> Result<MoltPrediction, sqlx::Error> {
+    let last_molt_outcome = row
+        .last_molt_outcome
+        .as_deref()
+        .map(MoltOutcome::from_db)
+        .transpose()?;
+    let (baseline, baseline_at) = match row.last_complete_molt_at {
+        Some(molted_at) => (MoltBaseline::LastMolt, molted_at),
+        None => (MoltBaseline::AddedToTank, row.added_at),
+    };
+
+    let mut prediction = MoltPrediction {
+        inhabitant_id: row.inhabitant_id,
+        tank_id: row.tank_id,
+        species_id: row.species_id,
+        species_name: row.species_name,
+        count: row.count,
+        status: MoltStatus::Unknown,
+        last_molt_at: row.last_molt_at,
+        last_molt_outcome,
+        baseline,
+        typical_interval_days: row.typical_interval_days,
+        expected_molt_at: None,
+        window_opens_at: None,
+        window_closes_at: None,
+        days_overdue: None,
+    };
+
+    let Some(interval_days) = row.typical_interval_days else {
+        return Ok(prediction);
+    };
+    let window = Duration::days(row.window_days.unwrap_or(0) as i64);
+    let expected = baseline_at + Duration::days(interval_days as i64);
+    let opens = expected - window;
+    let closes = expected + window;
+
+    prediction.status = if now > closes {
+        prediction.days_overdue = Some((now - closes).num_days());
+        MoltStatus::Overdue
+    } else if now >= opens {
+        MoltStatus::Due
+    } else if opens - now <= horizon {
+        MoltStatus::Upcoming
+    } else {
+        MoltStatus::NotDue
+    };
+    prediction.expected_molt_at = Some(expected);
+    prediction.window_opens_at = Some(opens);
+    prediction.window_closes_at = Some(closes);
+
+    Ok(prediction)
+}
+
+/// Builds a forecast for the given populations, most urgent first.
+fn build_forecast(rows: Vec<MoltCycleRow>, horizon_days: i64) -> Result<MoltForecast, sqlx::Error> {
+    let now = Utc::now();
+    let horizon = Duration::days(horizon_days);
+
+    let mut predictions = rows
+        .into_iter()
+        .map(|row| predict_molt(row, now, horizon))
+        .collect::<Result<Vec<_>, _>>()?;
+    predictions.sort_by(|a, b| {
+        b.status
+            .cmp(&a.status)
+            .then(a.expected_molt_at.cmp(&b.expected_molt_at))
+    });
+
+    let count = |status: MoltStatus| predictions.iter().filter(|p| p.status == status).count();
+    Ok(MoltForecast {
+        generated_at: now,
+        horizon_days,
+        overdue: count(MoltStatus::Overdue),
+        due: count(MoltStatus::Due),
+        upcoming: count(MoltStatus::Upcoming),
+        predictions,
+    })
+}
+
+fn horizon_days(query: &MoltForecastQuery) -> Result<i64, ApiError> {
+    let days = query.days.unwrap_or(DEFAULT_HORIZON_DAYS);
+    if !(0..=MAX_HORIZON_DAYS).contains(&days) {
+        return Err(ApiError::InvalidQuery(format!(
+            "days must be between 0 and {}",
+            MAX_HORIZON_DAYS
+        )));
+    }
+    Ok(days)
+}
+
+/// Records a molt observed in a tank's population of a species.
+pub async fn log_molt(
+    Path((tank_id, species_id)): Path<(String, i32)>,
+    State(state): State<AppState>,
+    Json(request): Json<LogMoltRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let observed_at = request.observed_at.unwrap_or_else(Utc::now);
+    if observed_at > Utc::now() {
+        return Err(ApiError::InvalidInput(
+            "observed_at cannot be in the future".to_string(),
+        ));
+    }
+
+    let mut conn = state.pool.acquire().await?;
+    ensure_tank_exists(&mut conn, &tank_id).await?;
+
+    let inhabitant_id: i32 = sqlx::query_scalar(
+        "SELECT id FROM tank_inhabitants WHERE tank_id = $1 AND species_id = $2 AND removed_at IS NULL",
+    )
+    .bind(&tank_id)
+    .bind(species_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or_else(|| {
+        ApiError::NotFound(format!("No species {} living in tank {}", species_id, tank_id))
+    })?;
+
+    let id: i32 = sqlx::query_scalar(
+        "INSERT INTO molt_events (inhabitant_id, observed_at, outcome, notes) VALUES ($1, $2, $3, $4) RETURNING id",
+    )
+    .bind(inhabitant_id)
+    .bind(observed_at)
+    .bind(request.outcome.as_str())
+    .bind(&request.notes)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let event = sqlx::query(&format!("{} WHERE e.id = $1", MOLT_EVENT_SELECT))
+        .bind(id)
+        .try_map(molt_event_from_row)
+        .fetch_one(&mut *conn)
+        .await?;
+
+    if request.outcome == MoltOutcome::Failed {
+        tracing::warn!(
+            tank_id = %tank_id,
+            species_id = species_id,
+            molt_event_id = id,
+            operation = "log_molt",
+            "Failed molt recorded"
+        );
+    } else {
+        tracing::info!(
+            tank_id = %tank_id,
+            species_id = species_id,
+            molt_event_id = id,
+            operation = "log_molt",
+            "Molt recorded"
+        );
+    }
+
+    Ok((StatusCode::CREATED, Json(event)))
+}
+
+/// Lists the molts recorded in a tank, newest first.
+pub async fn list_tank_molts(
+    Path(tank_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut conn = state.pool.acquire().await?;
+    ensure_tank_exists(&mut conn, &tank_id).await?;
+
+    let events = sqlx::query(&format!(
+        "{} WHERE i.tank_id = $1 ORDER BY e.observed_at DESC, e.id DESC",
+        MOLT_EVENT_SELECT
+    ))
+    .bind(&tank_id)
+    .try_map(molt_event_from_row)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(Json(events))
+}
+
+/// Predicts upcoming molts across every tank and flags populations past their molt window.
+pub async fn get_molt_forecast(
+    Query(query): Query<MoltForecastQuery>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let horizon_days = horizon_days(&query)?;
+
+    let rows = sqlx::query_as::<_, MoltCycleRow>(&format!("{} ORDER BY i.tank_id, s.name", MOLT_CYCLE_SELECT))
+        .fetch_all(&state.pool)
+        .await?;
+    let forecast = build_forecast(rows, horizon_days)?;
+
+    tracing::info!(
+        populations = forecast.predictions.len(),
+        overdue = forecast.overdue,
+        due = forecast.due,
+        upcoming = forecast.upcoming,
+        operation = "molt_forecast",
+        "Molt forecast generated"
+    );
+
+    Ok(Json(forecast))
+}
+
+/// Predicts upcoming molts for the populations in one tank.
+pub async fn get_tank_molt_forecast(
+    Path(tank_id): Path<String>,
+    Query(query): Query<MoltForecastQuery>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let horizon_days = horizon_days(&query)?;
+
+    let mut conn = state.pool.acquire().await?;
+    ensure_tank_exists(&mut conn, &tank_id).await?;
+
+    let rows = sqlx::query_as::<_, MoltCycleRow>(&format!(
+        "{} AND i.tank_id = $1 ORDER BY s.name",
+        MOLT_CYCLE_SELECT
+    ))
+    .bind(&tank_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(Json(build_forecast(rows, horizon_days)?))
+}
+
+/// Lists the typical molt interval of every species that has one.
+pub async fn list_molt_intervals(State(state): State<AppState>) -> Result<impl IntoResponse, ApiError> {
+    let intervals = sqlx::query_as::<_, MoltInterval>(
+        "SELECT m.species_id, s.name AS species_name, m.typical_interval_days, m.window_days, m.notes \
+         FROM species_molt_intervals m JOIN species s ON s.id = m.species_id \
+         ORDER BY s.name",
+    )
+    .fetch_all(&state.pool)
+    .await?;
+
+    Ok(Json(intervals))
+}
+
+/// Creates or replaces the typical molt interval for a species.
+pub async fn upsert_molt_interval(
+    State(state): State<AppState>,
+    Json(payload): Json<MoltIntervalPayload>,
+) -> Result<impl IntoResponse, ApiError> {
+    let window_days = payload.window_days.unwrap_or(7);
+    if payload.typical_interval_days <= 0 || window_days < 0 {
+        return Err(ApiError::InvalidInput(
+            "typical_interval_days must be positive and window_days cannot be negative".to_string(),
+        ));
+    }
+
+    let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM species WHERE id = $1)")
+        .bind(payload.species_id)
+        .fetch_one(&state.pool)
+        .await?;
+    if !exists {
+        return Err(ApiError::SpeciesNotFound(format!(
+            "Species with ID {} not found",
+            payload.species_id
+        )));
+    }
+
+    let interval = sqlx::query_as::<_, MoltInterval>(
+        "WITH saved AS ( \
+             INSERT INTO species_molt_intervals (species_id, typical_interval_days, window_days, notes) \
+             VALUES ($1, $2, $3, $4) \
+             ON CONFLICT (species_id) DO UPDATE SET \
+                 typical_interval_days = EXCLUDED.typical_interval_days, \
+                 window_days = EXCLUDED.window_days, \
+                 notes = EXCLUDED.notes \
+             RETURNING * \
+         ) \
+         SELECT saved.species_id, s.name AS species_name, saved.typical_interval_days, saved.window_days, saved.notes \
+         FROM saved JOIN species s ON s.id = saved.species_id",
+    )
+    .bind(payload.species_id)
+    .bind(payload.typical_interval_days)
+    .bind(window_days)
+    .bind(&payload.notes)
+    .fetch_one(&state.pool)
+    .await?;
+
+    tracing::info!(
+        species_id = payload.species_id,
+        typical_interval_days = payload.typical_interval_days,
+        window_days = window_days,
+        operation = "upsert_molt_interval",
+        "Molt interval saved"
+    );
+
+    Ok(Json(interval))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(offset: i64) -> DateTime<Utc> {
+        "2025-06-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap() + Duration::days(offset)
+    }
+
+    fn row(typical_interval_days: Option<i32>, last_complete_molt_at: Option<DateTime<Utc>>) -> MoltCycleRow {
+        MoltCycleRow {
+            inhabitant_id: 1,
+            tank_id: "Tank-C3".to_string(),
+            species_id: 2,
+            species_name: "Daggerblade Grass Shrimp".to_string(),
+            count: 12,
+            added_at: day(-60),
+            typical_interval_days,
+            window_days: Some(5),
+            last_complete_molt_at,
+            last_molt_at: last_complete_molt_at,
+            last_molt_outcome: last_complete_molt_at.map(|_| "complete".to_string()),
+        }
+    }
+
+    #[test]
+    fn status_moves_through_the_molt_window() {
+        // Molted on day 0 with a 30 day interval and a 5 day window: due from day 25 to 35
+        let cases = [
+            (10, MoltStatus::NotDue, None),
+            (17, MoltStatus::NotDue, None),
+            (18, MoltStatus::Upcoming, None),
+            (25, MoltStatus::Due, None),
+            (35, MoltStatus::Due, None),
+            (38, MoltStatus::Overdue, Some(3)),
+        ];
+
+        for (now, status, days_overdue) in cases {
+            let prediction = predict_molt(row(Some(30), Some(day(0))), day(now), Duration::days(7)).unwrap();
+
+            assert_eq!(prediction.status, status, "day {}", now);
+            assert_eq!(prediction.days_overdue, days_overdue, "day {}", now);
+            assert_eq!(prediction.baseline, MoltBaseline::LastMolt);
+            assert_eq!(prediction.expected_molt_at, Some(day(30)));
+            assert_eq!(prediction.window_opens_at, Some(day(25)));
+            assert_eq!(prediction.window_closes_at, Some(day(35)));
+        }
+    }
+
+    #[test]
+    fn species_without_an_interval_are_unknown() {
+        let prediction = predict_molt(row(None, Some(day(0))), day(10), Duration::days(7)).unwrap();
+
+        assert_eq!(prediction.status, MoltStatus::Unknown);
+        assert_eq!(prediction.expected_molt_at, None);
+        assert_eq!(prediction.days_overdue, None);
+    }
+
+    #[test]
+    fn populations_never_seen_molting_count_from_being_added() {
+        let prediction = predict_molt(row(Some(30), None), day(0), Duration::days(7)).unwrap();
+
+        assert_eq!(prediction.baseline, MoltBaseline::AddedToTank);
+        assert_eq!(prediction.expected_molt_at, Some(day(-30)));
+        assert_eq!(prediction.status, MoltStatus::Overdue);
+        assert_eq!(prediction.days_overdue, Some(25));
+    }
+
+    #[test]
+    fn failed_molts_keep_counting_from_the_last_complete_one() {
+        let mut failed = row(Some(30), Some(day(0)));
+        failed.last_molt_at = Some(day(20));
+        failed.last_molt_outcome = Some("failed".to_string());
+
+        let prediction = predict_molt(failed, day(26), Duration::days(7)).unwrap();
+
+        assert_eq!(prediction.last_molt_outcome, Some(MoltOutcome::Failed));
+        assert_eq!(prediction.expected_molt_at, Some(day(30)));
+        assert_eq!(prediction.status, MoltStatus::Due);
+    }
+
+    #[test]
+    fn unknown_outcomes_are_decode_errors() {
+        let mut corrupt = row(Some(30), Some(day(0)));
+        corrupt.last_molt_outcome = Some("partial".to_string());
+
+        assert!(predict_molt(corrupt, day(0), Duration::days(7)).is_err());
+    }
+}
@@ -0,0 +1,224 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use shuttle_axum::axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use tracing::Instrument;
+
+use crate::{aqua_monitor::TankReading, ApiError, AppState, Species};
+
+#[derive(Serialize)]
+pub struct TankConditions {
+    pub temperature: f64,
+    pub ph: f64,
+    pub readings_count: usize,
+    pub latest_reading_at: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+pub struct SuitableSpecies {
+    pub species_id: i32,
+    pub name: String,
+    pub scientific_name: String,
+    /// Distance in °C from the current temperature to the nearest edge of the species' range
+    pub temperature_margin: f64,
+    /// Distance in pH units from the current pH to the nearest edge of the species' range
+    pub ph_margin: f64,
+    /// Smallest margin as a fraction of its range width (0.5 means dead centre)
+    pub margin_score: f64,
+}
+
+#[derive(Serialize)]
+pub struct SuitableSpeciesResponse {
+    pub tank_id: String,
+    pub conditions: TankConditions,
+    pub species: Vec<SuitableSpecies>,
+}
+
+/// Averages recent readings so a single noisy sample doesn't decide the recommendation.
+pub fn summarize_conditions(readings: &[TankReading]) -> Option<TankConditions> {
+    let latest_reading_at = readings.iter().map(|r| r.timestamp).max()?;
+    let count = readings.len() as f64;
+
+    Some(TankConditions {
+        temperature: readings.iter().map(|r| r.temperature).sum::<f64>() / count,
+        ph: readings.iter().map(|r| r.ph).sum::<f64>() / count,
+        readings_count: readings.len(),
+        latest_reading_at,
+    })
+}
+
+fn margin_within(value: f64, min: f64, max: f64) -> f64 {
+    (value - min).min(max - value)
+}
+
+/// Returns the species whose temperature and pH ranges contain the given conditions,
+/// best-centred first.
+pub fn rank_suitable_species(species: Vec<Species>, conditions: &TankConditions) -> Vec<SuitableSpecies> {
+    let mut suitable: Vec<SuitableSpecies> = species
+        .into_iter()
+        .filter_map(|s| {
+            let temperature_margin =
+                margin_within(conditions.temperature, s.min_temperature, s.max_temperature);
+            let ph_margin = margin_within(conditions.ph, s.min_ph, s.max_ph);
+            if temperature_margin < 0.0 || ph_margin < 0.0 {
+                return None;
+            }
+
+            let temperature_width = (s.max_temperature - s.min_temperature).max(f64::EPSILON);
+            let ph_width = (s.max_ph - s.min_ph).max(f64::EPSILON);
+            let margin_score = (temperature_margin / temperature_width).min(ph_margin / ph_width);
+
+            Some(SuitableSpecies {
+                species_id: s.id,
+                name: s.name,
+                scientific_name: s.scientific_name,
+                temperature_margin,
+                ph_margin,
+                margin_score,
+            })
+        })
+        .collect();
+
+    suitable.sort_by(|a, b| b.margin_score.total_cmp(&a.margin_score));
+    suitable
+}
+
+/// Recommends species for a tank based on its live conditions from aqua-monitor.
+pub async fn get_suitable_species(
+    Path(tank_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    let start = std::time::Instant::now();
+
+    let span = tracing::info_span!("stocking_recommendation", %request_id, tank_id = %tank_id);
+    async move {
+        tracing::info!(
+            request_id = %request_id,
+            tank_id = %tank_id,
+            operation = "get_suitable_species",
+            "Fetching live tank conditions from aqua-monitor"
+        );
+
+        let readings = state.aqua_monitor.recent_readings(&tank_id).await?;
+        let conditions = summarize_conditions(&readings).ok_or_else(|| {
+            ApiError::TankNotFound(format!("No readings for tank ID: {}", tank_id))
+        })?;
+
+        let species = sqlx::query_as::<_, Species>("SELECT * FROM species")
+            .fetch_all(&state.pool)
+            .await
+            .map_err(|e| {
+                tracing::error!(
+                    request_id = %request_id,
+                    error.type = "database",
+                    error.message = %e,
+                    "Error loading species for stocking recommendation",
+                );
+                ApiError::Database(e)
+            })?;
+
+        let suitable = rank_suitable_species(species, &conditions);
+
+        tracing::info!(
+            request_id = %request_id,
+            tank_id = %tank_id,
+            temperature = conditions.temperature,
+            ph = conditions.ph,
+            results_count = suitable.len(),
+            duration_ms = start.elapsed().as_millis() as f64,
+            operation_status = "success",
+            "Stocking recommendation completed"
+        );
+
+        Ok::<_, ApiError>(Json(SuitableSpeciesResponse {
+            tank_id,
+            conditions,
+            species: suitable,
+        }))
+    }
+    .instrument(span)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{aqua_monitor::AquaMonitorClient, test_support};
+
+    fn species(id: i32, name: &str, temperature: (f64, f64), ph: (f64, f64)) -> Species {
+        Species {
+            min_temperature: temperature.0,
+            max_temperature: temperature.1,
+            min_ph: ph.0,
+            max_ph: ph.1,
+            ..test_support::species(id, name)
+        }
+    }
+
+    async fn stub_conditions(tank_id: &str) -> TankConditions {
+        let readings = AquaMonitorClient::stub().recent_readings(tank_id).await.unwrap();
+        summarize_conditions(&readings).unwrap()
+    }
+
+    #[tokio::test]
+    async fn summarize_conditions_averages_stub_readings() {
+        let readings = AquaMonitorClient::stub().recent_readings("Tank-A1").await.unwrap();
+        let conditions = summarize_conditions(&readings).unwrap();
+
+        assert_eq!(conditions.readings_count, 5);
+        assert!((conditions.temperature - 25.48).abs() < 1e-9);
+        assert!((conditions.ph - 7.6).abs() < 1e-9);
+        assert_eq!(conditions.latest_reading_at, readings[0].timestamp);
+    }
+
+    #[tokio::test]
+    async fn stub_has_no_readings_for_unknown_tanks() {
+        let result = AquaMonitorClient::stub().recent_readings("Tank-Z9").await;
+        assert!(matches!(result, Err(ApiError::TankNotFound(_))));
+    }
+
+    #[test]
+    fn summarize_conditions_needs_readings() {
+        assert!(summarize_conditions(&[]).is_none());
+    }
+
+    #[tokio::test]
+    async fn rank_suitable_species_orders_by_margin_and_drops_misfits() {
+        // Tank-A1 averages 25.48°C and pH 7.6
+        let conditions = stub_conditions("Tank-A1").await;
+        let ranked = rank_suitable_species(
+            vec![
+                species(1, "Wide", (20.0, 30.0), (7.0, 8.0)),
+                species(2, "Centred", (24.0, 27.0), (6.5, 8.5)),
+                species(3, "Cold", (10.0, 20.0), (7.0, 8.0)),
+                species(4, "Acidic", (20.0, 30.0), (5.5, 7.0)),
+            ],
+            &conditions,
+        );
+
+        let ids: Vec<i32> = ranked.iter().map(|s| s.species_id).collect();
+        assert_eq!(ids, vec![2, 1]);
+        assert!((ranked[0].margin_score - 0.45).abs() < 1e-9);
+        assert!((ranked[1].temperature_margin - 4.52).abs() < 1e-9);
+        assert!((ranked[1].ph_margin - 0.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rank_suitable_species_keeps_species_at_the_edge_of_range() {
+        let conditions = TankConditions {
+            temperature: 18.0,
+            ph: 7.0,
+            readings_count: 1,
+            latest_reading_at: Utc::now(),
+        };
+        let ranked = rank_suitable_species(vec![species(1, "Edge", (18.0, 25.0), (6.0, 7.5))], &conditions);
+
+        assert_eq!(ranked.len(), 1);
+        assert_eq!(ranked[0].temperature_margin, 0.0);
+        assert_eq!(ranked[0].margin_score, 0.0);
+    }
+}
@@ -0,0 +1,393 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use shuttle_axum::axum::{
+    extract::{Query, State},
+    http::HeaderMap,
+    response::IntoResponse,
+    Json,
+};
+
+use crate::{
+    aqua_monitor::MonitoredTank,
+    history::{self, HistoryEntity, RevisionOperation},
+    ApiError, AppState, Tank,
+};
+
+/// Volumes closer than this are treated as equal (rounding between the two registries).
+const VOLUME_TOLERANCE_LITERS: f64 = 0.5;
+
+/// Tank type given to tanks created from aqua-monitor, which has no notion of tank types.
+const SYNCED_TANK_TYPE: &str = "unassigned";
+
+const DEFAULT_CLEANING_INTERVAL_DAYS: i32 = 14;
+
+#[derive(Serialize)]
+pub struct FieldMismatch<T> {
+    pub tank_id: String,
+    pub species_hub: T,
+    pub aqua_monitor: T,
+}
+
+/// Differences between species-hub's tanks and aqua-monitor's registry.
+#[derive(Serialize)]
+pub struct DriftReport {
+    pub checked_at: DateTime<Utc>,
+    pub in_sync: bool,
+    /// Configured in aqua-monitor but unknown to species-hub
+    pub missing_in_species_hub: Vec<String>,
+    /// Unknown to species-hub and configured in aqua-monitor without a volume,
+    /// so sync can't create them
+    pub missing_volume_in_aqua_monitor: Vec<String>,
+    /// Known to species-hub but absent from aqua-monitor's registry
+    pub missing_in_aqua_monitor: Vec<String>,
+    /// Present in both, but aqua-monitor only knows the tank from its readings,
+    /// so name and volume can't be compared
+    pub unconfigured_in_aqua_monitor: Vec<String>,
+    pub name_mismatches: Vec<FieldMismatch<String>>,
+    pub volume_mismatches: Vec<FieldMismatch<f64>>,
+}
+
+#[derive(Serialize)]
+pub struct SyncReport {
+    pub dry_run: bool,
+    pub applied: bool,
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+    /// Drift found before reconciling
+    pub drift: DriftReport,
+}
+
+#[derive(Deserialize)]
+pub struct SyncQuery {
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// The tank's configured volume, ignoring missing or non-positive values.
+fn configured_volume(tank: &MonitoredTank) -> Option<f64> {
+    tank.volume_liters.filter(|volume| *volume > 0.0)
+}
+
+/// Compares local tanks against aqua-monitor's registry; both lists come out sorted by tank ID.
+pub fn detect_drift(local: &[Tank], remote: &[MonitoredTank]) -> DriftReport {
+    let local_by_id: HashMap<&str, &Tank> = local.iter().map(|tank| (tank.id.as_str(), tank)).collect();
+    let remote_by_id: HashMap<&str, &MonitoredTank> =
+        remote.iter().map(|tank| (tank.id.as_str(), tank)).collect();
+
+    let mut missing_in_species_hub = Vec::new();
+    let mut missing_volume_in_aqua_monitor = Vec::new();
+    let mut unconfigured_in_aqua_monitor = Vec::new();
+    let mut name_mismatches = Vec::new();
+    let mut volume_mismatches = Vec::new();
+
+    for tank in remote {
+        let Some(local_tank) = local_by_id.get(tank.id.as_str()) else {
+            // Tanks aqua-monitor only knows from readings carry nothing to create them from
+            if tank.configured && configured_volume(tank).is_none() {
+                missing_volume_in_aqua_monitor.push(tank.id.clone());
+            } else if tank.configured {
+                missing_in_species_hub.push(tank.id.clone());
+            }
+            continue;
+        };
+
+        if !tank.configured {
+            unconfigured_in_aqua_monitor.push(tank.id.clone());
+            continue;
+        }
+
+        if let Some(name) = &tank.name {
+            if name != &local_tank.name {
+                name_mismatches.push(FieldMismatch {
+                    tank_id: tank.id.clone(),
+                    species_hub: local_tank.name.clone(),
+                    aqua_monitor: name.clone(),
+                });
+            }
+        }
+        if let Some(volume) = configured_volume(tank) {
+            if (volume - local_tank.volume).abs() > VOLUME_TOLERANCE_LITERS {
+                volume_mismatches.push(FieldMismatch {
+                    tank_id: tank.id.clone(),
+                    species_hub: local_tank.volume,
+                    aqua_monitor: volume,
+                });
+            }
+        }
+    }
+
+    let mut missing_in_aqua_monitor: Vec<String> = local
+        .iter()
+        .filter(|tank| !remote_by_id.contains_key(tank.id.as_str()))
+        .map(|tank| tank.id.clone())
+        .collect();
+
+    missing_in_species_hub.sort();
+    missing_volume_in_aqua_monitor.sort();
+    missing_in_aqua_monitor.sort();
+    unconfigured_in_aqua_monitor.sort();
+    name_mismatches.sort_by(|a, b| a.tank_id.cmp(&b.tank_id));
+    volume_mismatches.sort_by(|a, b| a.tank_id.cmp(&b.tank_id));
+
+    DriftReport {
+        checked_at: Utc::now(),
+        in_sync: missing_in_species_hub.is_empty()
+            && missing_volume_in_aqua_monitor.is_empty()
+            && missing_in_aqua_monitor.is_empty()
+            && unconfigured_in_aqua_monitor.is_empty()
+            && name_mismatches.is_empty()
+            && volume_mismatches.is_empty(),
+        missing_in_species_hub,
+        missing_volume_in_aqua_monitor,
+        missing_in_aqua_monitor,
+        unconfigured_in_aqua_monitor,
+        name_mismatches,
+        volume_mismatches,
+    }
+}
+
+/// Reports tanks whose IDs, names or volumes disagree between species-hub and aqua-monitor.
+pub async fn check_tank_consistency(State(state): State<AppState>) -> Result<impl IntoResponse, ApiError> {
+    let remote = state.aqua_monitor.tank_registry().await?;
+    let local = sqlx::query_as::<_, Tank>("SELECT * FROM tanks ORDER BY id")
+        .fetch_all(&state.pool)
+        .await?;
+
+    let report = detect_drift(&local, &remote);
+    tracing::info!(
+        operation = "tank_consistency_check",
+        in_sync = report.in_sync,
+        missing_in_species_hub = report.missing_in_species_hub.len(),
+        missing_volume_in_aqua_monitor = report.missing_volume_in_aqua_monitor.len(),
+        missing_in_aqua_monitor = report.missing_in_aqua_monitor.len(),
+        name_mismatches = report.name_mismatches.len(),
+        volume_mismatches = report.volume_mismatches.len(),
+        "Tank registry consistency checked"
+    );
+
+    Ok(Json(report))
+}
+
+/// Pulls aqua-monitor's registry and reconciles species-hub's tanks against it.
+///
+/// aqua-monitor owns tank identity, names and volumes: configured tanks missing here are
+/// created and mismatched names and volumes are overwritten. Configured tanks without a volume
+/// and tanks absent from aqua-monitor are only reported: the former can't be fed or stocked
+/// without one, and inhabitants and history still refer to the latter. Every change is
+/// recorded as a tank revision. With `dry_run=true` nothing is written.
+pub async fn sync_tanks(
+    Query(query): Query<SyncQuery>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, ApiError> {
+    let remote = state.aqua_monitor.tank_registry().await?;
+    let changed_by = history::changed_by(&headers);
+
+    let mut tx = state.pool.begin().await?;
+    let local = sqlx::query_as::<_, Tank>("SELECT * FROM tanks ORDER BY id FOR UPDATE")
+        .fetch_all(&mut *tx)
+        .await?;
+    let drift = detect_drift(&local, &remote);
+
+    let remote_by_id: HashMap<&str, &MonitoredTank> =
+        remote.iter().map(|tank| (tank.id.as_str(), tank)).collect();
+    let created = drift.missing_in_species_hub.clone();
+    let mut updated: Vec<String> = drift
+        .name_mismatches
+        .iter()
+        .map(|m| m.tank_id.clone())
+        .chain(drift.volume_mismatches.iter().map(|m| m.tank_id.clone()))
+        .collect();
+    updated.sort();
+    updated.dedup();
+
+    let apply = !query.dry_run && (!created.is_empty() || !updated.is_empty());
+    if apply {
+        for tank_id in &created {
+            let tank = remote_by_id[tank_id.as_str()];
+            sqlx::query(
+                "INSERT INTO tanks (id, name, tank_type, volume, description, cleaning_interval_days) \
+                 VALUES ($1, $2, $3, $4, $5, $6)",
+            )
+            .bind(tank_id)
+            .bind(tank.name.as_deref().unwrap_or(tank_id))
+            .bind(SYNCED_TANK_TYPE)
+            .bind(configured_volume(tank))
+            .bind("Created from the aqua-monitor tank registry")
+            .bind(tank.cleaning_interval_days.unwrap_or(DEFAULT_CLEANING_INTERVAL_DAYS))
+            .execute(&mut *tx)
+            .await?;
+            history::record_revision(
+                &mut tx,
+                HistoryEntity::Tank,
+                tank_id,
+                RevisionOperation::Create,
+                &changed_by,
+                Some("Synced from aqua-monitor"),
+            )
+            .await?;
+        }
+
+        for tank_id in &updated {
+            let tank = remote_by_id[tank_id.as_str()];
+            sqlx::query(
+                "UPDATE tanks SET name = COALESCE($2, name), volume = COALESCE($3, volume) WHERE id = $1",
+            )
+            .bind(tank_id)
+            .bind(&tank.name)
+            .bind(configured_volume(tank))
+            .execute(&mut *tx)
+            .await?;
+            history::record_revision(
+                &mut tx,
+                HistoryEntity::Tank,
+                tank_id,
+                RevisionOperation::Update,
+                &changed_by,
+                Some("Synced from aqua-monitor"),
+            )
+            .await?;
+        }
+        tx.commit().await?;
+    } else {
+        tx.rollback().await?;
+    }
+
+    tracing::info!(
+        operation = "tank_registry_sync",
+        dry_run = query.dry_run,
+        applied = apply,
+        created = created.len(),
+        updated = updated.len(),
+        missing_volume_in_aqua_monitor = drift.missing_volume_in_aqua_monitor.len(),
+        missing_in_aqua_monitor = drift.missing_in_aqua_monitor.len(),
+        "Tank registry synchronised with aqua-monitor"
+    );
+
+    Ok(Json(SyncReport {
+        dry_run: query.dry_run,
+        applied: apply,
+        created,
+        updated,
+        drift,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(id: &str, name: &str, volume: f64) -> Tank {
+        Tank {
+            id: id.to_string(),
+            name: name.to_string(),
+            tank_type: "planted".to_string(),
+            volume,
+            description: None,
+            timezone: None,
+            cleaning_interval_days: DEFAULT_CLEANING_INTERVAL_DAYS,
+            last_cleaned_at: None,
+        }
+    }
+
+    fn configured(id: &str, name: &str, volume: f64) -> MonitoredTank {
+        MonitoredTank {
+            id: id.to_string(),
+            name: Some(name.to_string()),
+            volume_liters: Some(volume),
+            cleaning_interval_days: Some(DEFAULT_CLEANING_INTERVAL_DAYS),
+            configured: true,
+        }
+    }
+
+    fn from_readings(id: &str) -> MonitoredTank {
+        MonitoredTank {
+            id: id.to_string(),
+            name: None,
+            volume_liters: None,
+            cleaning_interval_days: None,
+            configured: false,
+        }
+    }
+
+    #[test]
+    fn matching_registries_are_in_sync() {
+        let report = detect_drift(
+            &[local("tank1", "Main Tank", 500.0)],
+            // Volumes within the tolerance are equal
+            &[configured("tank1", "Main Tank", 500.4)],
+        );
+
+        assert!(report.in_sync);
+        assert!(report.volume_mismatches.is_empty());
+    }
+
+    #[test]
+    fn tanks_missing_on_either_side_are_reported_sorted() {
+        let report = detect_drift(
+            &[local("tank9", "Old", 50.0), local("tank3", "Older", 50.0)],
+            &[
+                configured("tank2", "Quarantine Tank", 200.0),
+                configured("tank1", "Main Tank", 500.0),
+                // Readings alone give nothing to create a tank from
+                from_readings("Tank-A1"),
+            ],
+        );
+
+        assert!(!report.in_sync);
+        assert_eq!(report.missing_in_species_hub, vec!["tank1", "tank2"]);
+        assert_eq!(report.missing_in_aqua_monitor, vec!["tank3", "tank9"]);
+        assert!(report.unconfigured_in_aqua_monitor.is_empty());
+    }
+
+    #[test]
+    fn tanks_only_known_from_readings_are_not_compared() {
+        let report = detect_drift(&[local("Tank-A1", "Shrimp Tank", 60.0)], &[from_readings("Tank-A1")]);
+
+        assert!(!report.in_sync);
+        assert_eq!(report.unconfigured_in_aqua_monitor, vec!["Tank-A1"]);
+        assert!(report.name_mismatches.is_empty());
+        assert!(report.volume_mismatches.is_empty());
+    }
+
+    #[test]
+    fn name_and_volume_differences_are_reported() {
+        let report = detect_drift(
+            &[local("tank2", "Quarantine", 200.0), local("tank1", "Main Tank", 450.0)],
+            &[configured("tank1", "Main Tank", 500.0), configured("tank2", "Quarantine Tank", 200.0)],
+        );
+
+        assert!(!report.in_sync);
+        assert_eq!(report.name_mismatches.len(), 1);
+        assert_eq!(report.name_mismatches[0].tank_id, "tank2");
+        assert_eq!(report.name_mismatches[0].species_hub, "Quarantine");
+        assert_eq!(report.name_mismatches[0].aqua_monitor, "Quarantine Tank");
+        assert_eq!(report.volume_mismatches.len(), 1);
+        assert_eq!(report.volume_mismatches[0].tank_id, "tank1");
+        assert_eq!(report.volume_mismatches[0].species_hub, 450.0);
+        assert_eq!(report.volume_mismatches[0].aqua_monitor, 500.0);
+    }
+
+    #[test]
+    fn configured_tanks_without_a_volume_are_reported_instead_of_created() {
+        let mut no_volume = configured("tank3", "Nursery", 0.0);
+        no_volume.volume_liters = None;
+
+        let report = detect_drift(
+            &[local("tank1", "Main Tank", 500.0)],
+            &[
+                configured("tank1", "Main Tank", 0.0),
+                configured("tank2", "Breeder", -5.0),
+                no_volume,
+            ],
+        );
+
+        assert!(!report.in_sync);
+        assert!(report.missing_in_species_hub.is_empty());
+        assert_eq!(report.missing_volume_in_aqua_monitor, vec!["tank2", "tank3"]);
+        // A zero volume doesn't count as a difference on tanks both sides know
+        assert!(report.volume_mismatches.is_empty());
+    }
+}
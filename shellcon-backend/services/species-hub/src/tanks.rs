@@ -0,0 +1,148 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use shuttle_axum::axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    response::IntoResponse,
+    Json,
+};
+
+use crate::{
+    feeding::fetch_tank,
+    history::{self, HistoryEntity, RevisionOperation},
+    ApiError, AppState, Tank,
+};
+
+#[derive(Deserialize)]
+pub struct UpdateTankRequest {
+    /// IANA timezone name, e.g. "Europe/London"; an empty string resets to the facility timezone
+    timezone: Option<String>,
+    cleaning_interval_days: Option<i32>,
+}
+
+#[derive(Deserialize, Default)]
+pub struct CleaningRequest {
+    cleaned_at: Option<DateTime<Utc>>,
+}
+
+pub async fn list_tanks(State(state): State<AppState>) -> Result<impl IntoResponse, ApiError> {
+    let tanks = sqlx::query_as::<_, Tank>("SELECT * FROM tanks ORDER BY id")
+        .fetch_all(&state.pool)
+        .await?;
+
+    Ok(Json(tanks))
+}
+
+pub async fn get_tank(
+    Path(tank_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    Ok(Json(fetch_tank(&state.pool, &tank_id).await?))
+}
+
+/// Updates a tank's local settings (timezone and cleaning interval).
+///
+/// The tank is read under a row lock, so concurrent updates apply one after the other
+/// instead of overwriting each other's fields.
+pub async fn update_tank(
+    Path(tank_id): Path<String>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<UpdateTankRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut tx = state.pool.begin().await?;
+    let mut tank = sqlx::query_as::<_, Tank>("SELECT * FROM tanks WHERE id = $1 FOR UPDATE")
+        .bind(&tank_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| ApiError::TankNotFound(tank_id.clone()))?;
+
+    if let Some(timezone) = request.timezone {
+        let timezone = timezone.trim();
+        tank.timezone = if timezone.is_empty() {
+            None
+        } else {
+            let parsed = timezone.parse::<chrono_tz::Tz>().map_err(|_| {
+                ApiError::InvalidInput(format!("Unknown timezone: {}", timezone))
+            })?;
+            Some(parsed.name().to_string())
+        };
+    }
+
+    if let Some(days) = request.cleaning_interval_days {
+        if days <= 0 {
+            return Err(ApiError::InvalidInput(
+                "cleaning_interval_days must be positive".to_string(),
+            ));
+        }
+        tank.cleaning_interval_days = days;
+    }
+
+    let tank = sqlx::query_as::<_, Tank>(
+        "UPDATE tanks SET timezone = $2, cleaning_interval_days = $3 WHERE id = $1 RETURNING *",
+    )
+    .bind(&tank_id)
+    .bind(&tank.timezone)
+    .bind(tank.cleaning_interval_days)
+    .fetch_one(&mut *tx)
+    .await?;
+    history::record_revision(
+        &mut tx,
+        HistoryEntity::Tank,
+        &tank_id,
+        RevisionOperation::Update,
+        &history::changed_by(&headers),
+        None,
+    )
+    .await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        tank_id = %tank_id,
+        timezone = ?tank.timezone,
+        cleaning_interval_days = tank.cleaning_interval_days,
+        operation = "update_tank",
+        "Tank settings updated"
+    );
+
+    Ok(Json(tank))
+}
+
+/// Records that a tank has been cleaned, restarting its cleaning interval.
+pub async fn record_cleaning(
+    Path(tank_id): Path<String>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    request: Option<Json<CleaningRequest>>,
+) -> Result<impl IntoResponse, ApiError> {
+    let Json(request) = request.unwrap_or_default();
+
+    let mut tx = state.pool.begin().await?;
+    let tank = sqlx::query_as::<_, Tank>(
+        "UPDATE tanks SET last_cleaned_at = COALESCE($2, NOW()) WHERE id = $1 RETURNING *",
+    )
+    .bind(&tank_id)
+    .bind(request.cleaned_at)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| ApiError::TankNotFound(tank_id.clone()))?;
+    history::record_revision(
+        &mut tx,
+        HistoryEntity::Tank,
+        &tank_id,
+        RevisionOperation::Update,
+        &history::changed_by(&headers),
+        Some("Tank cleaned"),
+    )
+    .await?;
+    tx.commit().await?;
+
+    tracing::info!(
+        tank_id = %tank_id,
+        last_cleaned_at = ?tank.last_cleaned_at,
+        operation = "record_tank_cleaning",
+        "Tank cleaning recorded"
+    );
+
+    Ok(Json(tank))
+}
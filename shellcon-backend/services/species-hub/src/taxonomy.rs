@@ -0,0 +1,449 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use shuttle_axum::axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use sqlx::{PgConnection, PgPool, Postgres, QueryBuilder};
+
+use crate::{
+    history::{self, HistoryEntity, RevisionOperation},
+    ApiError, AppState, Species, SpeciesQuery,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaxonRank {
+    Order,
+    Family,
+    Genus,
+}
+
+impl TaxonRank {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaxonRank::Order => "order",
+            TaxonRank::Family => "family",
+            TaxonRank::Genus => "genus",
+        }
+    }
+
+    fn table(&self) -> &'static str {
+        match self {
+            TaxonRank::Order => "taxonomy_orders",
+            TaxonRank::Family => "taxonomy_families",
+            TaxonRank::Genus => "taxonomy_genera",
+        }
+    }
+
+    /// Alias of this rank's table in the species lookup join.
+    fn alias(&self) -> &'static str {
+        match self {
+            TaxonRank::Order => "o",
+            TaxonRank::Family => "f",
+            TaxonRank::Genus => "g",
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct GenusNode {
+    pub id: i32,
+    pub name: String,
+    pub species_count: i64,
+}
+
+#[derive(Serialize)]
+pub struct FamilyNode {
+    pub id: i32,
+    pub name: String,
+    pub common_name: Option<String>,
+    pub species_count: i64,
+    pub genera: Vec<GenusNode>,
+}
+
+#[derive(Serialize)]
+pub struct OrderNode {
+    pub id: i32,
+    pub name: String,
+    pub common_name: Option<String>,
+    /// Species of this order are exposed to medications toxic to invertebrates
+    pub invertebrate: bool,
+    pub species_count: i64,
+    pub families: Vec<FamilyNode>,
+}
+
+#[derive(Serialize)]
+pub struct TaxonomyTree {
+    pub orders: Vec<OrderNode>,
+    /// Species not yet assigned to a genus
+    pub unclassified_species: i64,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+pub struct TaxonomyPath {
+    pub order_id: i32,
+    pub order_name: String,
+    pub family_id: i32,
+    pub family_name: String,
+    pub genus_id: i32,
+    pub genus_name: String,
+}
+
+#[derive(Deserialize)]
+pub struct TaxonomyPayload {
+    order: String,
+    order_common_name: Option<String>,
+    /// Required when the order is new; existing orders keep their flag when this is omitted
+    order_invertebrate: Option<bool>,
+    family: String,
+    family_common_name: Option<String>,
+    genus: String,
+}
+
+#[derive(Deserialize)]
+pub struct AssignGenusRequest {
+    genus: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct OrderRow {
+    id: i32,
+    name: String,
+    common_name: Option<String>,
+    invertebrate: bool,
+}
+
+#[derive(sqlx::FromRow)]
+struct FamilyRow {
+    id: i32,
+    order_id: i32,
+    name: String,
+    common_name: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct GenusRow {
+    id: i32,
+    family_id: i32,
+    name: String,
+    species_count: i64,
+}
+
+const PATH_SELECT: &str = "SELECT o.id AS order_id, o.name AS order_name, f.id AS family_id, f.name AS family_name, \
+     g.id AS genus_id, g.name AS genus_name \
+     FROM taxonomy_genera g \
+     JOIN taxonomy_families f ON f.id = g.family_id \
+     JOIN taxonomy_orders o ON o.id = f.order_id";
+
+/// The taxonomy filters present in a species search, most general first.
+fn taxon_filters(params: &SpeciesQuery) -> Vec<(TaxonRank, &str)> {
+    [
+        (TaxonRank::Order, params.order.as_deref()),
+        (TaxonRank::Family, params.family.as_deref()),
+        (TaxonRank::Genus, params.genus.as_deref()),
+    ]
+    .into_iter()
+    .filter_map(|(rank, name)| name.map(|name| (rank, name.trim())))
+    .collect()
+}
+
+pub fn has_taxon_filter(params: &SpeciesQuery) -> bool {
+    !taxon_filters(params).is_empty()
+}
+
+/// Returns the species under the requested taxonomy nodes, narrowed by the name filters if given.
+///
+/// Node names are matched case-insensitively; an unknown node is reported as not found
+/// rather than silently returning an empty list.
+pub async fn fetch_species_in_taxon(pool: &PgPool, params: &SpeciesQuery) -> Result<Vec<Species>, ApiError> {
+    let filters = taxon_filters(params);
+
+    for (rank, name) in &filters {
+        let exists: bool = sqlx::query_scalar(&format!(
+            "SELECT EXISTS (SELECT 1 FROM {} WHERE LOWER(name) = LOWER($1))",
+            rank.table()
+        ))
+        .bind(name)
+        .fetch_one(pool)
+        .await?;
+        if !exists {
+            return Err(ApiError::NotFound(format!(
+                "Taxonomy {} '{}' not found",
+                rank.as_str(),
+                name
+            )));
+        }
+    }
+
+    let mut builder: QueryBuilder<Postgres> = QueryBuilder::new(
+        "SELECT s.* FROM species s \
+         JOIN taxonomy_genera g ON g.id = s.genus_id \
+         JOIN taxonomy_families f ON f.id = g.family_id \
+         JOIN taxonomy_orders o ON o.id = f.order_id \
+         WHERE 1=1",
+    );
+    for (rank, name) in &filters {
+        builder.push(format!(" AND LOWER({}.name) = LOWER(", rank.alias()));
+        builder.push_bind(name.to_string());
+        builder.push(")");
+    }
+    // Same matching as the unfiltered catalog search
+    if let Some(name) = &params.name {
+        builder.push(" AND s.name LIKE ");
+        builder.push_bind(format!("%{}%", name));
+    }
+    if let Some(scientific_name) = &params.scientific_name {
+        builder.push(" AND s.scientific_name LIKE ");
+        builder.push_bind(format!("%{}%", scientific_name));
+    }
+    builder.push(" ORDER BY s.name");
+
+    Ok(builder.build_query_as::<Species>().fetch_all(pool).await?)
+}
+
+/// Links unclassified species to the genus named by the first word of their scientific name.
+///
+/// Each linked species gets a revision attributed to `changed_by`.
+pub async fn link_genera_by_name(conn: &mut PgConnection, changed_by: &str) -> Result<usize, sqlx::Error> {
+    let linked: Vec<i32> = sqlx::query_scalar(
+        "UPDATE species SET genus_id = g.id \
+         FROM taxonomy_genera g \
+         WHERE species.genus_id IS NULL AND g.name = split_part(species.scientific_name, ' ', 1) \
+         RETURNING species.id",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for species_id in &linked {
+        history::record_revision(
+            conn,
+            HistoryEntity::Species,
+            &species_id.to_string(),
+            RevisionOperation::Update,
+            changed_by,
+            Some("Linked to genus"),
+        )
+        .await?;
+    }
+
+    Ok(linked.len())
+}
+
+/// Returns the taxonomy tree with the number of species under every node.
+pub async fn get_taxonomy_tree(State(state): State<AppState>) -> Result<impl IntoResponse, ApiError> {
+    let orders = sqlx::query_as::<_, OrderRow>("SELECT id, name, common_name, invertebrate FROM taxonomy_orders ORDER BY name")
+        .fetch_all(&state.pool)
+        .await?;
+    let families = sqlx::query_as::<_, FamilyRow>(
+        "SELECT id, order_id, name, common_name FROM taxonomy_families ORDER BY name",
+    )
+    .fetch_all(&state.pool)
+    .await?;
+    let genera = sqlx::query_as::<_, GenusRow>(
+        "SELECT g.id, g.family_id, g.name, COUNT(s.id) AS species_count \
+         FROM taxonomy_genera g LEFT JOIN species s ON s.genus_id = g.id \
+         GROUP BY g.id ORDER BY g.name",
+    )
+    .fetch_all(&state.pool)
+    .await?;
+    let unclassified_species: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM species WHERE genus_id IS NULL")
+        .fetch_one(&state.pool)
+        .await?;
+
+    let mut genera_by_family: HashMap<i32, Vec<GenusNode>> = HashMap::new();
+    for genus in genera {
+        genera_by_family.entry(genus.family_id).or_default().push(GenusNode {
+            id: genus.id,
+            name: genus.name,
+            species_count: genus.species_count,
+        });
+    }
+
+    let mut families_by_order: HashMap<i32, Vec<FamilyNode>> = HashMap::new();
+    for family in families {
+        let genera = genera_by_family.remove(&family.id).unwrap_or_default();
+        families_by_order.entry(family.order_id).or_default().push(FamilyNode {
+            id: family.id,
+            name: family.name,
+            common_name: family.common_name,
+            species_count: genera.iter().map(|g| g.species_count).sum(),
+            genera,
+        });
+    }
+
+    let orders = orders
+        .into_iter()
+        .map(|order| {
+            let families = families_by_order.remove(&order.id).unwrap_or_default();
+            OrderNode {
+                id: order.id,
+                name: order.name,
+                common_name: order.common_name,
+                invertebrate: order.invertebrate,
+                species_count: families.iter().map(|f| f.species_count).sum(),
+                families,
+            }
+        })
+        .collect();
+
+    Ok(Json(TaxonomyTree {
+        orders,
+        unclassified_species,
+    }))
+}
+
+/// Adds an order > family > genus path, creating whichever nodes don't exist yet.
+///
+/// An existing family or genus must already sit under the given parent; taxa are
+/// not silently re-parented.
+pub async fn upsert_taxonomy_path(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<TaxonomyPayload>,
+) -> Result<impl IntoResponse, ApiError> {
+    let (order, family, genus) = (payload.order.trim(), payload.family.trim(), payload.genus.trim());
+    if order.is_empty() || family.is_empty() || genus.is_empty() {
+        return Err(ApiError::InvalidInput(
+            "order, family and genus are all required".to_string(),
+        ));
+    }
+
+    let mut tx = state.pool.begin().await?;
+
+    // Whether an order's species are invertebrates decides medication warnings, so a new
+    // order is only created when the caller says which it is
+    let order_id: i32 = match payload.order_invertebrate {
+        Some(invertebrate) => {
+            sqlx::query_scalar(
+                "INSERT INTO taxonomy_orders (name, common_name, invertebrate) VALUES ($1, $2, $3) \
+                 ON CONFLICT (name) DO UPDATE SET common_name = COALESCE(EXCLUDED.common_name, taxonomy_orders.common_name), \
+                 invertebrate = EXCLUDED.invertebrate \
+                 RETURNING id",
+            )
+            .bind(order)
+            .bind(&payload.order_common_name)
+            .bind(invertebrate)
+            .fetch_one(&mut *tx)
+            .await?
+        }
+        None => sqlx::query_scalar(
+            "UPDATE taxonomy_orders SET common_name = COALESCE($2, common_name) WHERE name = $1 RETURNING id",
+        )
+        .bind(order)
+        .bind(&payload.order_common_name)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| {
+            ApiError::InvalidInput(format!(
+                "Order {} is new; order_invertebrate is required to create it",
+                order
+            ))
+        })?,
+    };
+
+    let (family_id, family_order_id): (i32, i32) = sqlx::query_as(
+        "INSERT INTO taxonomy_families (order_id, name, common_name) VALUES ($1, $2, $3) \
+         ON CONFLICT (name) DO UPDATE SET common_name = COALESCE(EXCLUDED.common_name, taxonomy_families.common_name) \
+         RETURNING id, order_id",
+    )
+    .bind(order_id)
+    .bind(family)
+    .bind(&payload.family_common_name)
+    .fetch_one(&mut *tx)
+    .await?;
+    if family_order_id != order_id {
+        return Err(ApiError::InvalidInput(format!(
+            "Family {} already belongs to a different order",
+            family
+        )));
+    }
+
+    let (genus_id, genus_family_id): (i32, i32) = sqlx::query_as(
+        "INSERT INTO taxonomy_genera (family_id, name) VALUES ($1, $2) \
+         ON CONFLICT (name) DO UPDATE SET name = EXCLUDED.name \
+         RETURNING id, family_id",
+    )
+    .bind(family_id)
+    .bind(genus)
+    .fetch_one(&mut *tx)
+    .await?;
+    if genus_family_id != family_id {
+        return Err(ApiError::InvalidInput(format!(
+            "Genus {} already belongs to a different family",
+            genus
+        )));
+    }
+
+    let linked = link_genera_by_name(&mut tx, &history::changed_by(&headers)).await?;
+    let path = sqlx::query_as::<_, TaxonomyPath>(&format!("{} WHERE g.id = $1", PATH_SELECT))
+        .bind(genus_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    if linked > 0 {
+        state.catalog_cache.invalidate();
+    }
+
+    tracing::info!(
+        order = %path.order_name,
+        family = %path.family_name,
+        genus = %path.genus_name,
+        species_linked = linked,
+        operation = "upsert_taxonomy_path",
+        "Taxonomy path saved"
+    );
+
+    Ok((StatusCode::CREATED, Json(path)))
+}
+
+/// Assigns a species to a genus.
+pub async fn assign_species_genus(
+    Path(species_id): Path<i32>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<AssignGenusRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut tx = state.pool.begin().await?;
+
+    let path = sqlx::query_as::<_, TaxonomyPath>(&format!("{} WHERE LOWER(g.name) = LOWER($1)", PATH_SELECT))
+        .bind(request.genus.trim())
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Taxonomy genus '{}' not found", request.genus.trim())))?;
+
+    let result = sqlx::query("UPDATE species SET genus_id = $2 WHERE id = $1")
+        .bind(species_id)
+        .bind(path.genus_id)
+        .execute(&mut *tx)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(ApiError::SpeciesNotFound(format!(
+            "Species with ID {} not found",
+            species_id
+        )));
+    }
+    history::record_revision(
+        &mut tx,
+        HistoryEntity::Species,
+        &species_id.to_string(),
+        RevisionOperation::Update,
+        &history::changed_by(&headers),
+        Some("Assigned to genus"),
+    )
+    .await?;
+    tx.commit().await?;
+    state.catalog_cache.invalidate();
+
+    tracing::info!(
+        species_id = species_id,
+        genus = %path.genus_name,
+        operation = "assign_species_genus",
+        "Species assigned to genus"
+    );
+
+    Ok(Json(path))
+}
@@ -0,0 +1,27 @@
+//! Fixtures shared by the unit tests.
+
+use crate::Species;
+
+/// A beginner freshwater omnivore; tests override the fields they exercise.
+pub(crate) fn species(id: i32, name: &str) -> Species {
+    Species {
+        id,
+        name: name.to_string(),
+        scientific_name: format!("{} scientificus", name),
+        description: String::new(),
+        min_temperature: 20.0,
+        max_temperature: 28.0,
+        min_ph: 6.5,
+        max_ph: 7.5,
+        diet_type: "omnivore".to_string(),
+        min_salinity: 0.0,
+        max_salinity: 0.5,
+        min_oxygen: 5.0,
+        min_gh: None,
+        max_gh: None,
+        min_kh: None,
+        max_kh: None,
+        water_type: "fresh".to_string(),
+        care_level: "beginner".to_string(),
+    }
+}
@@ -18,7 +18,7 @@ export interface TankAnalysisDetail {
   temperature_status: 'normal' | 'high' | 'low';
   ph_status: 'normal' | 'high' | 'low';
   oxygen_status: 'normal' | 'high' | 'low';
-  feeding_status: 'normal' | 'overdue' | 'excess';
+  feeding_status: 'normal' | 'overdue' | 'overfed' | 'excess' | 'unknown';
   overall_health: 'good' | 'caution' | 'at_risk';
   recommendations: string[];
 }
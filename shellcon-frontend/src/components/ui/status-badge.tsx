@@ -36,7 +36,7 @@ export const StatusBadge: React.FC<StatusBadgeProps> = ({
       }
     }
     
-    if (status === 'high' || status === 'excess') {
+    if (status === 'high' || status === 'excess' || status === 'overfed') {
       return 'bg-orange-500/20 text-orange-400 border-orange-500/30 hover:bg-orange-500/30';
     }
     